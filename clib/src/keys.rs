@@ -3,6 +3,10 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::{fs, io};
+
+#[cfg(unix)]
+use librad::signer::agent::{self, SshAgent};
 use librad::{
     keys::{IntoSecretKeyError, PublicKey, SecretKey},
     profile::Profile,
@@ -11,16 +15,47 @@ use librad::{
 use radicle_keystore::{
     crypto::{Crypto, KdfParams, Pwhash, SecretBoxError},
     file,
-    pinentry::Prompt,
+    pinentry::{Pinentry, Prompt, SecUtf8},
     FileStorage,
     Keystore as _,
 };
 
+pub mod backup;
+pub mod cli;
+
 /// The filename for storing the secret key.
 pub const LIBRAD_KEY_FILE: &str = "librad.key";
 
 pub type Error = file::Error<SecretBoxError<std::io::Error>, IntoSecretKeyError>;
 
+#[cfg(unix)]
+#[derive(Debug, thiserror::Error)]
+pub enum SshAgentError {
+    #[error(transparent)]
+    Keys(#[from] Error),
+
+    #[error(transparent)]
+    Agent(#[from] agent::Error),
+}
+
+/// A passphrase which was obtained up front, eg. by prompting for it twice.
+#[derive(Clone)]
+pub struct Passphrase(SecUtf8);
+
+impl From<SecUtf8> for Passphrase {
+    fn from(passphrase: SecUtf8) -> Self {
+        Self(passphrase)
+    }
+}
+
+impl Pinentry for Passphrase {
+    type Error = io::Error;
+
+    fn get_passphrase(&self) -> Result<SecUtf8, Self::Error> {
+        Ok(self.0.clone())
+    }
+}
+
 /// Create a [`Prompt`] for unlocking the key storage.
 pub fn prompt() -> Pwhash<Prompt<'static>> {
     let prompt = Prompt::new("please enter your passphrase: ");
@@ -49,6 +84,29 @@ where
     FileStorage::new(&profile.paths().keys_dir().join(LIBRAD_KEY_FILE), crypto)
 }
 
+/// Replace the key in the file store by `key`, encrypting it with a passphrase
+/// obtained from `crypto`, eg. [`prompt`].
+///
+/// This is intended for completing a key rotation (see
+/// [`librad::git::rotation`]), after which the old key is no longer usable.
+/// The new key is written to a temporary file first, which then replaces the
+/// key file, so the file store holds either the old or the new key.
+pub fn replace<P>(profile: &Profile, crypto: Pwhash<P>, key: SecretKey) -> Result<(), Error>
+where
+    P: Pinentry<Error = io::Error>,
+{
+    let path = profile.paths().keys_dir().join(LIBRAD_KEY_FILE);
+    let tmp = path.with_extension("key.new");
+    if tmp.exists() {
+        fs::remove_file(&tmp)?;
+    }
+    let mut store: FileStorage<_, PublicKey, _, ()> = FileStorage::new(&tmp, crypto);
+    store.put_key(key)?;
+    fs::rename(&tmp, &path)?;
+
+    Ok(())
+}
+
 /// Get the signer from the file store, decrypting the secret key by asking for
 /// a passphrase via a prompt.
 pub fn signer_prompt(profile: &Profile) -> Result<BoxedSigner, Error> {
@@ -70,3 +128,32 @@ pub fn unsafe_signer_prompt(profile: &Profile) -> Result<BoxedSigner, Error> {
     let key = store.get_key()?.secret_key;
     Ok(key.into())
 }
+
+/// Get a signer which delegates to the `ssh-agent` pointed to by
+/// [`agent::SSH_AUTH_SOCK`].
+///
+/// Only the public key is read from the file store, the secret key is not
+/// decrypted. The agent must hold the secret key, eg. by a previous
+/// [`add_to_ssh_agent`].
+#[cfg(unix)]
+pub fn signer_ssh_agent(profile: &Profile) -> Result<BoxedSigner, SshAgentError> {
+    let store = file_storage(profile, prompt());
+    let key = store.show_key()?;
+    let agent = SshAgent::from_env(key)?.check()?;
+    Ok(agent.into())
+}
+
+/// Add the secret key from the file store to the `ssh-agent` pointed to by
+/// [`agent::SSH_AUTH_SOCK`], decrypting it with a passphrase obtained from
+/// `crypto`, eg. [`prompt`].
+#[cfg(unix)]
+pub fn add_to_ssh_agent(
+    profile: &Profile,
+    crypto: Pwhash<Prompt<'static>>,
+) -> Result<PublicKey, SshAgentError> {
+    let store = file_storage(profile, crypto);
+    let key = store.get_key()?.secret_key;
+    let public = key.public();
+    SshAgent::from_env(public)?.add(&key, &format!("radicle-link {}", public))?;
+    Ok(public)
+}
@@ -0,0 +1,180 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Export and import of the secret key of a [`Profile`].
+//!
+//! A [`Backup`] holds the secret key encrypted with a passphrase which is
+//! independent of the one protecting the key file store, along with the
+//! [`PeerId`] it corresponds to. It is serialised as JSON, see
+//! [`Backup::to_writer`].
+
+use std::io;
+
+use librad::{
+    git::storage::{read, ReadOnly},
+    git_ext::is_not_found_err,
+    keys::{IntoSecretKeyError, PublicKey, SecretKey},
+    peer::PeerId,
+    profile::Profile,
+};
+use radicle_keystore::{
+    crypto::{Crypto as _, Pwhash, SecretBox, SecretBoxError},
+    pinentry::Pinentry,
+    Keystore as _,
+    SecretKeyExt as _,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{file_storage, replace, Error as KeysError, LIBRAD_KEY_FILE};
+
+/// The current version of the [`Backup`] format.
+pub const VERSION: u8 = 1;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("unsupported backup version {0}")]
+    Version(u8),
+
+    #[error("the backup claims to be of {claimed}, but holds the key of {actual}")]
+    Corrupt { claimed: PeerId, actual: PeerId },
+
+    #[error("the key of {key} does not match the peer id {configured} of the storage")]
+    StorageMismatch { key: PeerId, configured: PeerId },
+
+    #[error("the profile already holds the key of {0}")]
+    Exists(PeerId),
+
+    #[error(transparent)]
+    Keys(#[from] KeysError),
+
+    #[error(transparent)]
+    Crypto(#[from] SecretBoxError<io::Error>),
+
+    #[error(transparent)]
+    IntoSecretKey(#[from] IntoSecretKeyError),
+
+    #[error(transparent)]
+    Storage(#[from] read::error::Init),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// An encrypted, versioned copy of a secret key.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    pub version: u8,
+    pub peer_id: PeerId,
+    secret: SecretBox,
+}
+
+impl Backup {
+    pub fn to_writer<W: io::Write>(&self, w: W) -> Result<(), Error> {
+        Ok(serde_json::to_writer_pretty(w, self)?)
+    }
+
+    /// Read a [`Backup`], rejecting unknown versions.
+    pub fn from_reader<R: io::Read>(r: R) -> Result<Self, Error> {
+        let backup: Self = serde_json::from_reader(r)?;
+        if backup.version != VERSION {
+            return Err(Error::Version(backup.version));
+        }
+        Ok(backup)
+    }
+}
+
+/// The [`PeerId`] of the key in the file store of `profile`.
+///
+/// The secret key is not decrypted.
+pub fn peer_id(profile: &Profile) -> Result<PeerId, Error> {
+    let store = file_storage(profile, super::prompt());
+    let key: PublicKey = store.show_key()?;
+    Ok(PeerId::from(key))
+}
+
+/// Ensure `peer_id` matches the peer id the storage of `profile` was
+/// initialised with, if the storage exists.
+pub fn validate(profile: &Profile, peer_id: PeerId) -> Result<(), Error> {
+    match ReadOnly::open(profile.paths()) {
+        Ok(storage) if *storage.peer_id() == peer_id => Ok(()),
+        Ok(storage) => Err(Error::StorageMismatch {
+            key: peer_id,
+            configured: *storage.peer_id(),
+        }),
+        Err(read::error::Init::Git(e)) if is_not_found_err(&e) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Export the secret key of `profile`, decrypted via `unlock`, as a [`Backup`]
+/// encrypted via `lock`.
+pub fn export<U, L>(profile: &Profile, unlock: Pwhash<U>, lock: Pwhash<L>) -> Result<Backup, Error>
+where
+    U: Pinentry<Error = io::Error>,
+    L: Pinentry<Error = io::Error>,
+{
+    let key = file_storage(profile, unlock).get_key()?.secret_key;
+    let peer_id = PeerId::from(key.public());
+    validate(profile, peer_id)?;
+
+    Ok(Backup {
+        version: VERSION,
+        peer_id,
+        secret: lock.seal(key.as_ref())?,
+    })
+}
+
+/// Import the secret key from `backup`, decrypted via `unlock`, into the file
+/// store of `profile`, encrypted via `lock`.
+///
+/// It is an error if the profile already holds a key, or if its storage was
+/// initialised with a different key.
+pub fn import<U, L>(
+    profile: &Profile,
+    backup: Backup,
+    unlock: Pwhash<U>,
+    lock: Pwhash<L>,
+) -> Result<PeerId, Error>
+where
+    U: Pinentry<Error = io::Error>,
+    L: Pinentry<Error = io::Error>,
+{
+    if profile.paths().keys_dir().join(LIBRAD_KEY_FILE).exists() {
+        return Err(Error::Exists(peer_id(profile)?));
+    }
+
+    let key = SecretKey::from_bytes_and_meta(unlock.unseal(backup.secret)?, &())?;
+    let actual = PeerId::from(key.public());
+    if actual != backup.peer_id {
+        return Err(Error::Corrupt {
+            claimed: backup.peer_id,
+            actual,
+        });
+    }
+    validate(profile, actual)?;
+
+    let mut store = file_storage(profile, lock);
+    store.put_key(key)?;
+    Ok(actual)
+}
+
+/// Re-encrypt the secret key of `profile`, decrypted via `old`, via `new`.
+pub fn change_passphrase<O, N>(
+    profile: &Profile,
+    old: Pwhash<O>,
+    new: Pwhash<N>,
+) -> Result<PeerId, Error>
+where
+    O: Pinentry<Error = io::Error>,
+    N: Pinentry<Error = io::Error>,
+{
+    let key = file_storage(profile, old).get_key()?.secret_key;
+    let peer_id = PeerId::from(key.public());
+    validate(profile, peer_id)?;
+    replace(profile, new, key)?;
+    Ok(peer_id)
+}
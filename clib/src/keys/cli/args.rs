@@ -0,0 +1,59 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::path::PathBuf;
+
+use argh::FromArgs;
+
+/// Management of the secret key of your Radicle profile.
+///
+/// This tool allows you to back up your key, restore it into a new profile,
+/// and change the passphrase protecting it.
+#[derive(Debug, FromArgs)]
+pub struct Args {
+    #[argh(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand)]
+pub enum Command {
+    PeerId(PeerId),
+    Export(Export),
+    Import(Import),
+    ChangePassphrase(ChangePassphrase),
+}
+
+/// 🪪 Prints the peer id of your key. Your passphrase is not required.
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "peer-id")]
+pub struct PeerId {}
+
+/// 📦 Exports your key as an encrypted backup. You will be asked for your
+/// passphrase, and for a passphrase to protect the backup with.
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "export")]
+pub struct Export {
+    /// the file to write the backup to, which must not already exist
+    #[argh(option)]
+    pub output: PathBuf,
+}
+
+/// 📥 Imports a key from an encrypted backup into your profile, which must not
+/// hold a key yet. You will be asked for the passphrase of the backup, and for
+/// a passphrase to protect the key in your profile with.
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "import")]
+pub struct Import {
+    /// the file to read the backup from
+    #[argh(option)]
+    pub input: PathBuf,
+}
+
+/// 🔐 Changes the passphrase protecting your key. You will be asked for the
+/// current passphrase, and for the new one.
+#[derive(Debug, FromArgs)]
+#[argh(subcommand, name = "change-passphrase")]
+pub struct ChangePassphrase {}
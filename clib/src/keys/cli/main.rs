@@ -0,0 +1,96 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::fs;
+
+use librad::profile::Profile;
+use radicle_keystore::{
+    crypto::{KdfParams, Pwhash},
+    pinentry::{Pinentry as _, Prompt},
+};
+
+use super::args::{Args, Command, Export, Import};
+use crate::keys::{
+    backup::{self, Backup},
+    prompt,
+    Passphrase,
+};
+
+pub fn main() -> anyhow::Result<()> {
+    let args: Args = argh::from_env();
+    let profile = Profile::load()?;
+    match args.command {
+        Command::PeerId(_) => {
+            let peer_id = backup::peer_id(&profile)?;
+            backup::validate(&profile, peer_id)?;
+            println!("{}", peer_id);
+        },
+        Command::Export(Export { output }) => {
+            let file = create_private(&output)?;
+            let backup = backup::export(
+                &profile,
+                prompt(),
+                new_passphrase("please enter a passphrase for the backup: ")?,
+            )?;
+            backup.to_writer(file)?;
+            println!(
+                "The key of `{}` was exported to `{}`",
+                backup.peer_id,
+                output.display()
+            );
+        },
+        Command::Import(Import { input }) => {
+            let backup = Backup::from_reader(fs::File::open(&input)?)?;
+            let peer_id = backup::import(
+                &profile,
+                backup,
+                passphrase("please enter the passphrase of the backup: "),
+                new_passphrase("please enter a new passphrase: ")?,
+            )?;
+            println!("The key of `{}` was imported", peer_id);
+        },
+        Command::ChangePassphrase(_) => {
+            let peer_id = backup::change_passphrase(
+                &profile,
+                prompt(),
+                new_passphrase("please enter a new passphrase: ")?,
+            )?;
+            println!("The passphrase of the key of `{}` was changed", peer_id);
+        },
+    }
+
+    Ok(())
+}
+
+fn passphrase(message: &'static str) -> Pwhash<Prompt<'static>> {
+    Pwhash::new(Prompt::new(message), KdfParams::recommended())
+}
+
+/// Prompt for a new passphrase twice, failing if the two don't match.
+fn new_passphrase(message: &'static str) -> anyhow::Result<Pwhash<Passphrase>> {
+    let passphrase = Prompt::new(message).get_passphrase()?;
+    let repeated = Prompt::new("please repeat the passphrase: ").get_passphrase()?;
+    if passphrase != repeated {
+        anyhow::bail!("passphrases do not match");
+    }
+
+    Ok(Pwhash::new(
+        Passphrase::from(passphrase),
+        KdfParams::recommended(),
+    ))
+}
+
+/// Create the file at `path`, which must not exist, readable only by the
+/// current user.
+fn create_private(path: &std::path::Path) -> std::io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt as _;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
@@ -20,6 +20,9 @@ pub enum Error {
     ReadWriteInit(#[from] error::Init),
     #[error(transparent)]
     Keys(#[from] super::keys::Error),
+    #[cfg(unix)]
+    #[error(transparent)]
+    SshAgent(#[from] super::keys::SshAgentError),
 }
 
 /// How to decrypt the secret key from the file store when initialising the
@@ -28,7 +31,10 @@ pub enum Crypto {
     /// The decryption will happen by prompting the person for their passphrase
     /// at the command line.
     Prompt,
-    // TODO(finto): SshAgent
+    /// The secret key is held by the `ssh-agent`, and is never decrypted by
+    /// us. See [`keys::signer_ssh_agent`].
+    #[cfg(unix)]
+    SshAgent,
 }
 
 /// Intialise a [`ReadOnly`] storage.
@@ -45,5 +51,10 @@ pub fn read_write(profile: &Profile, crypto: Crypto) -> Result<Storage, Error> {
             let signer = keys::signer_prompt(profile)?;
             Ok(Storage::open(paths, signer)?)
         },
+        #[cfg(unix)]
+        Crypto::SshAgent => {
+            let signer = keys::signer_ssh_agent(profile)?;
+            Ok(Storage::open(paths, signer)?)
+        },
     }
 }
@@ -25,6 +25,9 @@ pub enum Error {
     #[error(transparent)]
     Include(#[from] include::Error),
 
+    #[error(transparent)]
+    Local(#[from] identities::local::Error),
+
     #[error(transparent)]
     Ref(#[from] git_ext::name::Error),
 
@@ -37,22 +40,26 @@ pub enum Error {
 /// It looks at the tracked peers of the `project` and creates an entry for each
 /// one in an include file. The file can be located by using
 /// [`Paths::git_includes_dir`], and the name of the file will be the `Urn`.
+/// The large file storage endpoint is set to the one advertised by the local
+/// identity or a delegate of the `project`, see
+/// [`Include::set_largefiles_from`].
 pub fn update(storage: &Storage, paths: &Paths, project: &Project) -> Result<PathBuf, Error> {
     let urn = project.urn();
     let url = LocalUrl::from(urn.clone());
-    let tracked = identities::relations::tracked(storage, &urn)?;
-    let include = Include::from_tracked_persons(
+    let remotes = identities::relations::tracked(storage, &urn)?
+        .into_iter()
+        .filter_map(relations::Peer::replicated_remote)
+        .collect::<Vec<_>>();
+    let mut include = Include::from_tracked_persons(
         paths.git_includes_dir().to_path_buf(),
         url,
-        tracked
-            .into_iter()
-            .filter_map(|peer| {
-                relations::Peer::replicated_remote(peer).map(|(p, u)| {
-                    git_ext::RefLike::try_from(u.subject().name.to_string()).map(|r| (r, p))
-                })
-            })
+        remotes
+            .iter()
+            .map(|(p, u)| git_ext::RefLike::try_from(u.subject().name.to_string()).map(|r| (r, *p)))
             .collect::<Result<Vec<_>, _>>()?,
     );
+    let local = identities::local::default(storage)?;
+    include.set_largefiles_from(local.as_ref().map(|local| &***local), project);
     let path = include.file_path();
     include.save()?;
 
@@ -73,6 +73,7 @@ where
             replication: replication::Config::default(),
             fetch: net::protocol::config::Fetch::default(),
             rate_limits: net::protocol::Quota::default(),
+            relay: false,
         },
         storage: net::peer::config::Storage::default(),
     }
@@ -72,6 +72,10 @@ pub enum Error {
     #[error(transparent)]
     State(#[from] state::error::Error),
 
+    /// Syncing with a remote peer failed.
+    #[error(transparent)]
+    Sync(#[from] net::peer::error::Sync),
+
     /// Peer initialisation error.
     #[error(transparent)]
     Init(#[from] net::peer::error::Init),
@@ -14,7 +14,7 @@ use std::{
 use serde::Serialize;
 
 use librad::{
-    git::Urn,
+    git::{fetch, storage::gc, Urn},
     net::{
         self,
         peer::{PeerInfo, ProtocolEvent},
@@ -52,6 +52,8 @@ use running_waiting_room::{RunningWaitingRoom, WaitingRoomTransition};
 pub enum Event {
     /// Announcement subroutine completed and emitted the enclosed updates.
     Announced(announcement::Updates),
+    /// Storage garbage collection completed with the enclosed report.
+    GarbageCollected(gc::Report),
     /// A fetch originated by a gossip message succeeded
     GossipFetched {
         /// Provider of the fetched update.
@@ -66,6 +68,15 @@ pub enum Event {
     Protocol(ProtocolEvent),
     /// Sync with a peer completed.
     PeerSynced(PeerId),
+    /// A replication of `urn` from `remote_peer` made progress.
+    ReplicationProgress {
+        /// The URN being replicated.
+        urn: Urn,
+        /// The peer being replicated from.
+        remote_peer: PeerId,
+        /// Progress of the current fetch.
+        progress: fetch::Progress,
+    },
     /// Request fullfilled with a successful clone.
     RequestCloned(Urn, PeerId),
     /// Request is being cloned from a peer.
@@ -102,6 +113,7 @@ impl MaybeFrom<&Input> for Event {
             Input::Announce(input::Announce::Succeeded(updates)) => {
                 Some(Self::Announced(updates.clone()))
             },
+            Input::Gc(input::Gc::Succeeded(report)) => Some(Self::GarbageCollected(report.clone())),
             Input::PeerSync(input::Sync::Succeeded(peer_id)) => Some(Self::PeerSynced(*peer_id)),
             Input::Protocol(protocol_event) => match protocol_event {
                 ProtocolEvent::Gossip(gossip) => match &**gossip {
@@ -115,6 +127,15 @@ impl MaybeFrom<&Input> for Event {
                         result: result.clone(),
                     }),
                 },
+                ProtocolEvent::Replication(upstream::Replication::Progress {
+                    urn,
+                    remote_peer,
+                    progress,
+                }) => Some(Self::ReplicationProgress {
+                    urn: urn.clone(),
+                    remote_peer: *remote_peer,
+                    progress: *progress,
+                }),
                 event => Some(Self::Protocol(event.clone())),
             },
             Input::Request(input::Request::Cloned(urn, remote_peer)) => {
@@ -157,6 +178,8 @@ pub enum Status {
 pub struct RunState {
     /// Tracking remote peers that have an active connection.
     connected_peers: HashSet<PeerId>,
+    /// Whether a storage garbage collection is in progress.
+    gc_running: bool,
     listen_addrs: Vec<SocketAddr>,
     /// Current internal status.
     pub status: Status,
@@ -172,6 +195,7 @@ impl RunState {
     fn construct(connected_peers: HashSet<PeerId>, status: Status, syncs: HashSet<PeerId>) -> Self {
         Self {
             connected_peers,
+            gc_running: false,
             listen_addrs: vec![],
             stats: downstream::Stats::default(),
             status,
@@ -187,6 +211,7 @@ impl RunState {
     pub fn new(waiting_room: WaitingRoom<SystemTime, Duration>) -> Self {
         Self {
             connected_peers: HashSet::new(),
+            gc_running: false,
             listen_addrs: vec![],
             stats: downstream::Stats::default(),
             status: Status::Stopped,
@@ -204,6 +229,7 @@ impl RunState {
         let cmds = match input {
             Input::Announce(announce_input) => self.handle_announce(announce_input),
             Input::Control(control_input) => self.handle_control(control_input),
+            Input::Gc(gc_input) => self.handle_gc(&gc_input),
             Input::ListenAddrs(addrs) => self.handle_listen_addrs(addrs),
             Input::Protocol(protocol_event) => self.handle_protocol(protocol_event),
             Input::PeerSync(peer_sync_input) => self.handle_peer_sync(&peer_sync_input),
@@ -263,6 +289,22 @@ impl RunState {
         }
     }
 
+    /// Handle [`input::Gc`]s.
+    fn handle_gc(&mut self, input: &input::Gc) -> Vec<Command> {
+        match input {
+            input::Gc::Tick if !self.gc_running => vec![Command::Gc],
+            input::Gc::Tick => vec![],
+            input::Gc::Started => {
+                self.gc_running = true;
+                vec![]
+            },
+            input::Gc::Succeeded(_) | input::Gc::Failed => {
+                self.gc_running = false;
+                vec![]
+            },
+        }
+    }
+
     fn handle_listen_addrs(&mut self, addrs: Vec<SocketAddr>) -> Vec<Command> {
         self.listen_addrs = addrs;
         vec![]
@@ -643,6 +685,22 @@ mod test {
         assert_eq!(cmds.len(), num_peers);
     }
 
+    #[test]
+    fn issue_gc_once() {
+        let mut state = RunState::construct(HashSet::new(), Status::Stopped, HashSet::new());
+
+        let cmds = state.transition(Input::Gc(input::Gc::Tick));
+        assert_matches!(cmds.as_slice(), [Command::Gc]);
+
+        state.transition(Input::Gc(input::Gc::Started));
+        let cmds = state.transition(Input::Gc(input::Gc::Tick));
+        assert!(cmds.is_empty());
+
+        state.transition(Input::Gc(input::Gc::Failed));
+        let cmds = state.transition(Input::Gc(input::Gc::Tick));
+        assert_matches!(cmds.as_slice(), [Command::Gc]);
+    }
+
     fn one_connected_peer(peer_id: PeerId) -> HashMap<PeerId, Vec<SocketAddr>> {
         std::iter::once((peer_id, vec!["127.0.0.1:1234".parse().unwrap()])).collect()
     }
@@ -18,6 +18,8 @@ pub enum Command {
     Announce,
     /// Answer control requests.
     Control(Control),
+    /// Start the storage garbage collection subroutine.
+    Gc,
     /// Update the include file for the provided [`Urn`].
     Include(Urn),
     /// Tell the subroutine to persist the [`WaitingRoom`].
@@ -7,9 +7,14 @@
 
 use std::time::Duration;
 
+use librad::git::storage::gc;
+
 /// Default time to wait between announcement subroutine runs.
 const DEFAULT_ANNOUNCE_INTERVAL: Duration = std::time::Duration::from_secs(1);
 
+/// Default time to wait between storage garbage collection runs.
+const DEFAULT_GC_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
 const DEFAULT_STATS_INTERVAL: Duration = Duration::from_millis(1000);
 
 const DEFAULT_SYNC_INTERVAL: Duration = std::time::Duration::from_secs(30);
@@ -25,6 +30,8 @@ pub(crate) const DEFAULT_WAITING_ROOM_TIMEOUT: Duration = Duration::from_secs(10
 pub struct Config {
     /// Set of knobs to alter announce behaviour.
     pub announce: Announce,
+    /// Set of knobs to alter storage garbage collection.
+    pub gc: Gc,
     /// Set of knobs to alter stats polling.
     pub stats: Stats,
     /// Set of knobs to alter sync behaviour.
@@ -48,6 +55,25 @@ impl Default for Announce {
     }
 }
 
+/// Set of knobs to alter storage garbage collection.
+#[derive(Clone, Debug)]
+pub struct Gc {
+    /// Determines how often the garbage collection subroutine should be run.
+    /// A zero interval disables it.
+    pub interval: Duration,
+    /// Options passed to [`gc::collect`].
+    pub options: gc::Options,
+}
+
+impl Default for Gc {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_GC_INTERVAL,
+            options: gc::Options::default(),
+        }
+    }
+}
+
 /// Set of knobs to alter stats polling.
 #[derive(Clone, Debug)]
 pub struct Stats {
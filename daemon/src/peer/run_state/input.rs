@@ -7,7 +7,12 @@ use std::{net::SocketAddr, time::SystemTime};
 
 use tokio::sync::oneshot;
 
-use librad::{git::Urn, net, net::peer::ProtocolEvent, peer::PeerId};
+use librad::{
+    git::{storage::gc, Urn},
+    net,
+    net::peer::ProtocolEvent,
+    peer::PeerId,
+};
 
 use crate::{
     peer::announcement,
@@ -22,6 +27,8 @@ pub enum Input {
     Announce(Announce),
     /// Peer state change events.
     Control(Control),
+    /// Storage garbage collection lifecycle events.
+    Gc(Gc),
     ListenAddrs(Vec<SocketAddr>),
     /// Inputs from the underlying coco protocol.
     Protocol(ProtocolEvent),
@@ -69,6 +76,19 @@ pub enum Control {
     ListRequests(oneshot::Sender<Vec<SomeRequest<SystemTime>>>),
 }
 
+/// Storage garbage collection lifecycle events.
+#[derive(Debug)]
+pub enum Gc {
+    /// A collection has been initiated.
+    Started,
+    /// The collection failed.
+    Failed,
+    /// The collection succeeded, producing the enclosed report.
+    Succeeded(gc::Report),
+    /// The ticker duration has elapsed.
+    Tick,
+}
+
 /// Request event for projects requested from the network.
 #[derive(Debug)]
 pub enum Request {
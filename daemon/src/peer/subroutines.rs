@@ -20,7 +20,7 @@ use tokio::{
 };
 
 use librad::{
-    git::Urn,
+    git::{storage::gc, Urn},
     net::{self, peer::ProtocolEvent},
     peer::PeerId,
     signer::Signer,
@@ -55,6 +55,8 @@ pub struct Subroutines<S> {
     peer: net::peer::Peer<S>,
     /// [`kv::Store`] for suborutine task fulfillment.
     store: kv::Store,
+    /// Options for the storage garbage collection subroutine.
+    gc_options: gc::Options,
 
     /// Main peer state machine.
     run_state: RunState,
@@ -109,6 +111,12 @@ where
             Some(interval(run_config.sync.interval))
         };
 
+        let gc_timer = if run_config.gc.interval.is_zero() {
+            None
+        } else {
+            Some(interval(run_config.gc.interval))
+        };
+
         let run_state = RunState::new(waiting_room);
 
         let inputs = {
@@ -150,6 +158,17 @@ where
                     .boxed(),
                 );
             }
+            if let Some(mut timer) = gc_timer {
+                coalesced.push(
+                    stream! {
+                        loop {
+                            timer.tick().await;
+                            yield Input::Gc(input::Gc::Tick);
+                        }
+                    }
+                    .boxed(),
+                );
+            }
             if let Some(mut timer) = sync_timer {
                 coalesced.push(
                     stream! {
@@ -222,6 +241,7 @@ where
 
             peer,
             store,
+            gc_options: run_config.gc.options,
             run_state,
 
             subscriber,
@@ -242,6 +262,11 @@ where
                     tokio::spawn(control_respond(respond_command))
                 },
             },
+            Command::Gc => tokio::spawn(gc(
+                self.peer.clone(),
+                self.gc_options,
+                self.input_sender.clone(),
+            )),
             Command::Include(urn) => tokio::spawn(include::update(self.peer.clone(), urn)),
             Command::PersistWaitingRoom(waiting_room) => {
                 tokio::spawn(persist_waiting_room(waiting_room, self.store.clone()))
@@ -366,6 +391,39 @@ async fn control_respond(cmd: control::Response) {
     };
 }
 
+/// Run the storage garbage collection. On completion report back with the
+/// success or failure.
+async fn gc<S>(peer: net::peer::Peer<S>, options: gc::Options, sender: mpsc::Sender<Input>)
+where
+    S: Clone + Signer,
+{
+    sender.send(Input::Gc(input::Gc::Started)).await.ok();
+
+    let res = peer
+        .using_storage(move |storage| gc::collect(storage, options))
+        .await;
+    match res {
+        Ok(Ok(report)) => {
+            tracing::debug!(
+                reclaimed = report.reclaimed_bytes(),
+                "storage garbage collection succeeded"
+            );
+            sender
+                .send(Input::Gc(input::Gc::Succeeded(report)))
+                .await
+                .ok();
+        },
+        Ok(Err(err)) => {
+            tracing::error!(?err, "storage garbage collection error");
+            sender.send(Input::Gc(input::Gc::Failed)).await.ok();
+        },
+        Err(err) => {
+            tracing::error!(?err, "storage garbage collection error");
+            sender.send(Input::Gc(input::Gc::Failed)).await.ok();
+        },
+    }
+}
+
 async fn get_stats<S>(peer: net::peer::Peer<S>, sender: mpsc::Sender<Input>)
 where
     S: Clone + Signer,
@@ -5,15 +5,21 @@
 
 //! Perform full state syncs with remote peers.
 
-use librad::{identities::generic::Identity, net::peer::Peer, peer::PeerId, signer::Signer};
+use librad::{
+    identities::generic::Identity,
+    net::{peer::Peer, protocol::sync::Summary},
+    peer::PeerId,
+    signer::Signer,
+};
 
 use crate::state;
 
 use super::{include, Error};
 
-/// Initiaites a fetch for all locally tracked projects from the given
-/// [`PeerId`].
-pub async fn sync<S>(peer: &Peer<S>, remote_peer: PeerId) -> Result<(), Error>
+/// Syncs all locally tracked projects with the given [`PeerId`], fetching only
+/// those which diverge from the remote peer, or all of them if the remote peer
+/// doesn't answer the sync request.
+pub async fn sync<S>(peer: &Peer<S>, remote_peer: PeerId) -> Result<Summary, Error>
 where
     S: Clone + Signer,
 {
@@ -25,21 +31,18 @@ where
         .map(Identity::urn)
         .collect::<Vec<_>>();
 
-    for urn in urns {
-        tracing::debug!(%urn, %remote_peer, "starting fetch");
-        match state::fetch(peer, urn.clone(), remote_peer, vec![], None).await {
-            Ok(result) => {
-                tracing::debug!(
-                    %urn,
-                    %remote_peer,
-                    updated_tips = ?result.updated_tips,
-                    "finished fetch",
-                );
-                include::update(peer.clone(), urn).await;
-            },
-            Err(error) => tracing::error!(%urn, %remote_peer, ?error, "fetch error"),
-        }
+    let summary = peer.sync((remote_peer, vec![]), urns).await?;
+    tracing::debug!(
+        %remote_peer,
+        in_sync = summary.in_sync.len(),
+        unknown = summary.unknown.len(),
+        replicated = summary.replicated.len(),
+        failed = summary.failed.len(),
+        "finished sync",
+    );
+    for urn in &summary.replicated {
+        include::update(peer.clone(), urn.clone()).await;
     }
 
-    Ok(())
+    Ok(summary)
 }
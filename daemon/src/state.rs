@@ -26,10 +26,7 @@ use librad::{
         local::{transport, url::LocalUrl},
         refs::Refs,
         replication::{self, ReplicateResult},
-        storage::{
-            fetcher::{self, BuildFetcher},
-            ReadOnlyStorage as _,
-        },
+        storage::ReadOnlyStorage as _,
         tracking,
         types::{Namespace, Reference, Single},
         Urn,
@@ -181,26 +178,15 @@ where
     C: Into<Option<replication::Config>> + Send,
     Addrs: IntoIterator<Item = SocketAddr> + Send + 'static,
 {
-    let config = config
-        .into()
-        .unwrap_or_else(|| peer.protocol_config().replication);
     let owner = default_owner(peer).await?.ok_or(Error::MissingOwner)?;
     Ok(peer
-        .using_storage(move |store| {
-            // FIXME(finto): we could configure retry logic
-            let fetcher =
-                fetcher::PeerToPeer::new(urn, remote_peer, addr_hints).build_fetcher(store)?;
-            match fetcher {
-                Ok(fetcher) => {
-                    replication::replicate(store, fetcher, config, Some(owner)).map_err(Error::from)
-                },
-                Err(info) => Err(Error::FetchLocked {
-                    urn: info.urn,
-                    remote_peer: info.remote_peer,
-                }),
-            }
-        })
-        .await??)
+        .replicate(
+            (remote_peer, addr_hints.into_iter().collect()),
+            urn,
+            config,
+            Some(owner),
+        )
+        .await?)
 }
 
 /// Get the project found at `urn`.
@@ -217,6 +203,57 @@ where
         .map_err(Error::from)
 }
 
+/// Remove the project found at `urn` from the monorepo.
+///
+/// Unless `force` is `true`, the project is not removed if it is in use by
+/// local identities, ie. if the local peer is a maintainer of the project, it
+/// is the `rad/self` of any namespace, or a person the local peer is a
+/// delegate of links to it. Returns `true` if the project existed.
+///
+/// # Errors
+///
+///   * The project is in use by local identities, and `force` is `false`.
+///   * Removing the namespace from the storage fails.
+pub async fn remove_project<S>(peer: &Peer<S>, urn: Urn, force: bool) -> Result<bool, Error>
+where
+    S: Clone + Signer,
+{
+    let local_peer = peer.peer_id();
+    peer.using_storage(move |store| {
+        if !force && in_use_locally(store, &urn, local_peer)? {
+            return Err(Error::InUse(urn));
+        }
+
+        Ok(store.remove_namespace(&urn)?)
+    })
+    .await?
+}
+
+/// Determine if `urn` is in use by local identities, see [`remove_project`].
+fn in_use_locally(
+    store: &librad::git::storage::Storage,
+    urn: &Urn,
+    local_peer: PeerId,
+) -> Result<bool, Error> {
+    let local_key = local_peer.as_public_key();
+    let maintained = identities::project::get(store, urn)?.map_or(false, |project| {
+        project.delegations().owner(local_key).is_some()
+    });
+    if maintained || !store.self_linked_from(urn)?.is_empty() {
+        return Ok(true);
+    }
+
+    for linking in store.linked_from(urn)? {
+        if let Some(person) = person::get(store, &linking)? {
+            if person.delegations().contains(local_key) {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
 /// Returns the list of [`Project`]s for the local peer.
 ///
 /// # Errors
@@ -334,25 +371,14 @@ where
     C: Into<Option<replication::Config>> + Send,
     Addrs: IntoIterator<Item = SocketAddr> + Send + 'static,
 {
-    let config = config
-        .into()
-        .unwrap_or_else(|| peer.protocol_config().replication);
-    peer.using_storage(move |store| {
-        // FIXME(finto): we could configure retry logic
-        let fetcher =
-            fetcher::PeerToPeer::new(urn, remote_peer, addr_hints).build_fetcher(store)?;
-        match fetcher {
-            Ok(fetcher) => {
-                replication::replicate(store, fetcher, config, None).map_err(Error::from)
-            },
-            Err(info) => Err(Error::FetchLocked {
-                urn: info.urn,
-                remote_peer: info.remote_peer,
-            }),
-        }
-    })
-    .await?
-    .map_err(Error::from)
+    Ok(peer
+        .replicate(
+            (remote_peer, addr_hints.into_iter().collect()),
+            urn,
+            config,
+            None,
+        )
+        .await?)
 }
 
 /// Get the user found at `urn`.
@@ -407,26 +433,14 @@ where
     C: Into<Option<replication::Config>> + Send,
     Addrs: IntoIterator<Item = SocketAddr> + Send + 'static,
 {
-    let config = config
-        .into()
-        .unwrap_or_else(|| peer.protocol_config().replication);
-
     Ok(peer
-        .using_storage(move |store| -> Result<_, Error> {
-            // FIXME(finto): we could configure retry logic
-            let fetcher =
-                fetcher::PeerToPeer::new(urn, remote_peer, addr_hints).build_fetcher(store)?;
-            match fetcher {
-                Ok(fetcher) => {
-                    replication::replicate(store, fetcher, config, None).map_err(Error::from)
-                },
-                Err(info) => Err(Error::FetchLocked {
-                    urn: info.urn,
-                    remote_peer: info.remote_peer,
-                }),
-            }
-        })
-        .await??)
+        .replicate(
+            (remote_peer, addr_hints.into_iter().collect()),
+            urn,
+            config,
+            None,
+        )
+        .await?)
 }
 
 /// Initialize a [`Project`] that is owned by the `owner`.
@@ -765,22 +779,27 @@ where
     S: Clone + Signer,
 {
     let local_url = LocalUrl::from(urn.clone());
-    let tracked = tracked(peer, urn).await?;
+    let tracked = tracked(peer, urn.clone()).await?;
+    let owner = default_owner(peer).await?;
+    let project = get_project(peer, urn).await?;
     let include = spawn_blocking({
         let path = paths(peer).git_includes_dir().to_path_buf();
         move || {
-            let inc = Include::from_tracked_persons(
+            let remotes = tracked
+                .into_iter()
+                .filter_map(crate::project::Peer::replicated_remote)
+                .collect::<Vec<_>>();
+            let mut inc = Include::from_tracked_persons(
                 path,
                 local_url,
-                tracked
-                    .into_iter()
-                    .filter_map(|peer| {
-                        crate::project::Peer::replicated_remote(peer).map(|(p, u)| {
-                            RefLike::try_from(u.subject().name.to_string()).map(|r| (r, p))
-                        })
-                    })
+                remotes
+                    .iter()
+                    .map(|(p, u)| RefLike::try_from(u.subject().name.to_string()).map(|r| (r, *p)))
                     .collect::<Result<Vec<_>, _>>()?,
             );
+            if let Some(project) = &project {
+                inc.set_largefiles_from(owner.as_ref().map(|owner| &***owner), project);
+            }
             Ok::<_, Error>(inc)
         }
     })
@@ -1196,6 +1215,36 @@ pub mod test {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn can_remove_project() -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_dir = tempfile::tempdir().expect("failed to create temdir");
+        let repo_path = tmp_dir.path().join("radicle");
+        let key = SecretKey::new();
+        let signer = signer::BoxedSigner::from(key.clone());
+        let config = config::default(signer.clone(), tmp_dir.path())?;
+        let peer = net::peer::Peer::new(config)?;
+
+        let user = super::init_owner(
+            &peer,
+            Person {
+                name: "cloudhead".into(),
+            },
+        )
+        .await?;
+        let project = super::init_project(&peer, &user, radicle_project(repo_path)).await?;
+        let urn = project.urn();
+
+        let refused = super::remove_project(&peer, urn.clone(), false).await;
+        assert!(matches!(refused, Err(super::Error::InUse(_))));
+        assert!(super::get_project(&peer, urn.clone()).await?.is_some());
+
+        assert!(super::remove_project(&peer, urn.clone(), true).await?);
+        assert!(super::get_project(&peer, urn.clone()).await?.is_none());
+        assert!(super::default_owner(&peer).await?.is_some());
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn list_projects() -> Result<(), Box<dyn std::error::Error>> {
         let tmp_dir = tempfile::tempdir().expect("failed to create temdir");
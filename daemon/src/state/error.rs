@@ -74,6 +74,10 @@ pub enum Error {
     #[error("project not found for '{0}'")]
     ProjectNotFound(Urn),
 
+    /// Refused to remove an identity which is in use by local identities.
+    #[error("'{0}' is in use by local identities")]
+    InUse(Urn),
+
     /// Failed to parse a reference.
     #[error(transparent)]
     ReferenceName(#[from] librad::git_ext::reference::name::Error),
@@ -94,6 +98,10 @@ pub enum Error {
     #[error(transparent)]
     Storage(#[from] storage::Error),
 
+    /// Removing a namespace from the storage failed.
+    #[error(transparent)]
+    RemoveNamespace(#[from] librad::git::storage::error::RemoveNamespace),
+
     /// An interaction with the config file for the storage failed.
     #[error(transparent)]
     StorageConfig(#[from] librad::git::storage::config::Error),
@@ -143,6 +151,22 @@ impl From<librad::git::identities::Error> for Error {
     }
 }
 
+impl From<net::peer::error::Replicate> for Error {
+    fn from(err: net::peer::error::Replicate) -> Self {
+        use net::peer::error::Replicate;
+
+        match err {
+            Replicate::Concurrent(info) => Self::FetchLocked {
+                urn: info.urn,
+                remote_peer: info.remote_peer,
+            },
+            Replicate::MkFetcher(e) => Self::Git(e),
+            Replicate::Replication(e) => Self::Replication(e),
+            Replicate::Storage(e) => Self::PeerStorage(e),
+        }
+    }
+}
+
 impl From<tokio::task::JoinError> for Error {
     fn from(err: tokio::task::JoinError) -> Self {
         if err.is_cancelled() {
@@ -112,6 +112,7 @@ async fn main() {
                 replication: Default::default(),
                 fetch: Default::default(),
                 rate_limits: Default::default(),
+                relay: false,
             },
             storage: Default::default(),
         })
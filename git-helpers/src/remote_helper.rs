@@ -16,9 +16,15 @@ use librad::{
         url::LocalUrl,
     },
     keys::{PublicKey, SecretKey},
+    paths::Paths,
     profile::Profile,
     signer::{BoxedSigner, SomeSigner},
 };
+#[cfg(unix)]
+use librad::{
+    git::storage::ReadOnly,
+    signer::agent::{self, SshAgent},
+};
 use radicle_keystore::{
     crypto::{self, Pwhash},
     FileStorage,
@@ -63,7 +69,7 @@ See https://git-scm.com/docs/git-remote-ext for more detail."#
         let paths = profile.paths().to_owned();
         let signer = match config.signer {
             Some(signer) => signer,
-            None => get_signer(&git_dir, paths.keys_dir(), &url)?,
+            None => get_signer(&git_dir, &paths, &url)?,
         };
         let settings: Box<dyn CanOpenStorage> = Box::new(Settings { paths, signer });
         Ok::<_, anyhow::Error>(LocalTransport::from(settings))
@@ -101,10 +107,19 @@ See https://git-scm.com/docs/git-remote-ext for more detail."#
     Ok(())
 }
 
-fn get_signer(git_dir: &Path, keys_dir: &Path, url: &LocalUrl) -> anyhow::Result<BoxedSigner> {
+/// Obtain the signer for `url`.
+///
+/// If an `ssh-agent` is running and holds the key of the local peer, it is used
+/// for signing. Otherwise, the secret key is decrypted from the file store,
+/// using the passphrase from the git credential helper.
+fn get_signer(git_dir: &Path, paths: &Paths, url: &LocalUrl) -> anyhow::Result<BoxedSigner> {
+    if let Some(signer) = agent_signer(paths) {
+        return Ok(signer);
+    }
+
     let mut cred = credential::Git::new(git_dir);
     let pass = cred.get(url)?;
-    let file = keys_dir.join(SECRET_KEY_FILE);
+    let file = paths.keys_dir().join(SECRET_KEY_FILE);
     let keystore = FileStorage::<_, PublicKey, _, _>::new(
         &file,
         Pwhash::new(pass.clone(), *crypto::KDF_PARAMS_PROD),
@@ -114,3 +129,25 @@ fn get_signer(git_dir: &Path, keys_dir: &Path, url: &LocalUrl) -> anyhow::Result
 
     Ok(SomeSigner { signer: key }.into())
 }
+
+/// The `ssh-agent` signer for the local peer, if the agent holds its key.
+///
+/// `None` is also returned if the local peer can't be determined, in which
+/// case we fall back to the file store.
+#[cfg(unix)]
+fn agent_signer(paths: &Paths) -> Option<BoxedSigner> {
+    if env::var_os(agent::SSH_AUTH_SOCK).is_none() {
+        return None;
+    }
+
+    let peer_id = *ReadOnly::open(paths).ok()?.peer_id();
+    SshAgent::from_env(*peer_id.as_public_key())
+        .and_then(SshAgent::check)
+        .ok()
+        .map(BoxedSigner::from)
+}
+
+#[cfg(not(unix))]
+fn agent_signer(_: &Paths) -> Option<BoxedSigner> {
+    None
+}
@@ -10,6 +10,7 @@ pub mod local;
 pub mod p2p;
 pub mod refs;
 pub mod replication;
+pub mod rotation;
 
 pub mod storage;
 pub use storage::Storage;
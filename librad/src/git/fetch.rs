@@ -3,9 +3,17 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{collections::BTreeMap, iter::FromIterator, ops::Deref};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    iter::FromIterator,
+    num::NonZeroU32,
+    ops::Deref,
+    str::FromStr,
+};
 
 use git_ext as ext;
+use thiserror::Error;
 
 use crate::identities::Urn;
 
@@ -44,6 +52,59 @@ impl Default for Limit {
     }
 }
 
+/// The shape of the history fetched using [`Fetchspecs::Replicate`].
+///
+/// The default is to fetch the full history. The identity branches (`rad/*`)
+/// are always fetched in full.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Partial {
+    /// Only fetch the given number of commits from the tip of each head, like
+    /// `git fetch --depth`.
+    ///
+    /// If `None`, and the local history is shallow, it is deepened to the
+    /// full history.
+    pub depth: Option<NonZeroU32>,
+    /// Omit objects from the fetched packfile, like `git fetch --filter`.
+    pub filter: Option<Filter>,
+}
+
+impl Partial {
+    /// `true` if the full history is fetched.
+    pub fn is_full(&self) -> bool {
+        self.depth.is_none() && self.filter.is_none()
+    }
+}
+
+/// A filter for [`Partial`] fetches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// Omit all blobs, ie. `filter=blob:none`.
+    BlobNone,
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BlobNone => f.write_str("blob:none"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("unsupported filter: {0}")]
+pub struct UnsupportedFilter(String);
+
+impl FromStr for Filter {
+    type Err = UnsupportedFilter;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blob:none" => Ok(Self::BlobNone),
+            other => Err(UnsupportedFilter(other.to_owned())),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct RemoteHeads(BTreeMap<ext::RefLike, ext::Oid>);
 
@@ -74,6 +135,41 @@ pub struct FetchResult {
     pub updated_tips: BTreeMap<ext::RefLike, ext::Oid>,
 }
 
+/// The phase of [`crate::git::replication::replicate`] a fetch belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Discovery of the identity and the remotes of a new [`Urn`], see
+    /// [`Fetchspecs::PeekAll`].
+    Peek,
+    /// The `rad/*` branches (including `rad/signed_refs`) of known remotes,
+    /// see [`Fetchspecs::Peek`].
+    SignedRefs,
+    /// The heads advertised in the signed refs, see
+    /// [`Fetchspecs::Replicate`].
+    Data,
+}
+
+/// The progress of a fetch, as reported by the transport.
+///
+/// The final progress of every successful fetch is reported, even if no
+/// objects were transferred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    pub phase: Phase,
+    /// Number of objects the remote end is sending. Zero until it is known.
+    pub total_objects: usize,
+    pub received_objects: usize,
+    pub indexed_objects: usize,
+    pub received_bytes: usize,
+}
+
+impl Progress {
+    /// `true` if all objects have been received and indexed.
+    pub fn is_done(&self) -> bool {
+        self.total_objects > 0 && self.indexed_objects == self.total_objects
+    }
+}
+
 /// Types which can process [`Fetchspecs`], and update the local storage
 /// accordingly.
 pub trait Fetcher {
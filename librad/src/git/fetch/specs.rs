@@ -3,15 +3,19 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
 
 use git_ext as ext;
 use multihash::Multihash;
 
-use super::{Limit, RemoteHeads};
+use super::{Limit, Partial, Phase, RemoteHeads};
 use crate::{
     git::{
         refs::Refs,
+        tracking::Policy,
         types::{
             reference::{Reference, RefsCategory},
             AsRemote,
@@ -38,10 +42,19 @@ pub enum Fetchspecs<P, R> {
     /// Request the remote heads matching the signed refs of the respective
     /// tracked peers, as well as top-level delegates found in the identity
     /// document.
+    ///
+    /// Only refs allowed by the tracking [`Policy`] of the respective peer
+    /// are requested, see [`Policy::filter`]. Peers without an entry in
+    /// `policies` are replicated in full.
+    ///
+    /// The history of the heads may be limited by `partial`, the identity
+    /// branches are always requested in full.
     Replicate {
         tracked_sigrefs: BTreeMap<P, Refs>,
+        policies: BTreeMap<P, Policy>,
         delegates: BTreeSet<Urn<R>>,
         limit: Limit,
+        partial: Partial,
     },
 }
 
@@ -70,9 +83,25 @@ where
             Self::Peek { remotes, .. } => refspecs::peek(urn, &remote_peer, remotes),
             Self::Replicate {
                 tracked_sigrefs,
+                policies,
                 delegates,
                 ..
-            } => refspecs::replicate(urn, &remote_peer, remote_heads, tracked_sigrefs, delegates),
+            } => refspecs::replicate(
+                urn,
+                &remote_peer,
+                remote_heads,
+                tracked_sigrefs,
+                policies,
+                delegates,
+            ),
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        match self {
+            Fetchspecs::PeekAll { .. } => Phase::Peek,
+            Fetchspecs::Peek { .. } => Phase::SignedRefs,
+            Fetchspecs::Replicate { .. } => Phase::Data,
         }
     }
 
@@ -216,6 +245,7 @@ pub mod refspecs {
         remote_peer: &P,
         remote_heads: &RemoteHeads,
         tracked_sigrefs: &BTreeMap<P, Refs>,
+        policies: &BTreeMap<P, Policy>,
         delegates: &BTreeSet<Urn<R>>,
     ) -> Vec<Fetchspec>
     where
@@ -229,13 +259,18 @@ pub mod refspecs {
         let mut signed = tracked_sigrefs
             .iter()
             .flat_map(|(tracked_peer, refs)| {
+                let refs = match policies.get(tracked_peer) {
+                    Some(policy) => Cow::Owned(policy.filter(refs.clone())),
+                    None => Cow::Borrowed(refs),
+                };
                 sigrefs(
                     namespace.clone(),
                     remote_peer,
                     remote_heads,
                     tracked_peer,
-                    refs,
+                    &refs,
                 )
+                .collect::<Vec<_>>()
             })
             .collect::<Vec<_>>();
 
@@ -4,6 +4,8 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 pub mod any;
+pub mod audit;
+pub mod enrollment;
 pub mod error;
 pub mod local;
 pub mod person;
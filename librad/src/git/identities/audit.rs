@@ -0,0 +1,362 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Inspection of the revision history of identities.
+//!
+//! An [`Audit`] lists every revision of a [`Person`] or [`Project`] from the
+//! root to the tip of its `rad/id`, along with who signed it, whether it
+//! reached a quorum, and how it changed the delegations of its parent. Each
+//! revision is classified following the same rules as verification, so the
+//! [`Audit`] explains why [`super::person::verify`] or
+//! [`super::project::verify`] yields the head it does.
+
+use std::{collections::BTreeSet, convert::TryFrom};
+
+use git_ext::is_not_found_err;
+use serde::Serialize;
+
+use super::{
+    super::{
+        storage::{self, ReadOnlyStorage as _},
+        types::Reference,
+    },
+    error::Error,
+};
+use crate::{
+    identities::{
+        delegation::{self, Delegations},
+        git::{
+            ContentId,
+            Doc,
+            Identity,
+            IndirectDelegation,
+            Person,
+            Project,
+            Revision,
+            Threshold,
+            Urn,
+            VerifiedIdentity,
+            Verifying,
+        },
+    },
+    keys::PublicKey,
+    peer::PeerId,
+};
+
+/// The revision history of an identity, oldest first.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Audit {
+    pub urn: Urn,
+    pub revisions: Vec<Entry>,
+}
+
+impl Audit {
+    /// The most recent [`Status::Verified`] entry, if any.
+    pub fn verified(&self) -> Option<&Entry> {
+        self.revisions
+            .iter()
+            .rev()
+            .find(|entry| entry.status == Status::Verified)
+    }
+}
+
+/// A single revision in an [`Audit`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub content_id: ContentId,
+    pub revision: Revision,
+    pub replaces: Option<Revision>,
+    pub signatures: Vec<Signature>,
+    pub threshold: Option<Threshold>,
+    /// Whether the signatures form a quorum of the delegations of this
+    /// revision.
+    pub quorum: bool,
+    pub status: Status,
+    /// Why the revision was [`Status::Rejected`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The change to the delegations of the revision this one replaces.
+    pub delegations: Diff,
+}
+
+impl Entry {
+    fn reject<E: std::error::Error>(&mut self, e: E) {
+        self.status = Status::Rejected;
+        self.reason = Some(e.to_string());
+    }
+}
+
+/// A signature on an [`Entry`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Signature {
+    pub key: PublicKey,
+    pub peer: PeerId,
+    /// Whether the key is delegated to by the revision.
+    pub eligible: bool,
+    /// The [`Person`] owning the key, if it is an indirect delegation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub person: Option<Urn>,
+}
+
+/// A delegate of an identity.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Delegate {
+    Key(PublicKey),
+    Person(Urn),
+}
+
+/// The delegates added and removed by an [`Entry`].
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Diff {
+    pub added: BTreeSet<Delegate>,
+    pub removed: BTreeSet<Delegate>,
+}
+
+impl Diff {
+    pub(super) fn between(prev: &BTreeSet<Delegate>, next: &BTreeSet<Delegate>) -> Self {
+        Self {
+            added: next.difference(prev).cloned().collect(),
+            removed: prev.difference(next).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Status {
+    /// The revision passed verification.
+    Verified,
+    /// The revision is not (yet) signed by enough delegates.
+    Proposal,
+    /// The revision failed verification.
+    Rejected,
+    /// A previous revision was rejected in a way which invalidates the rest of
+    /// the history, so this revision was not considered.
+    Unverified,
+}
+
+/// Audit the history of the [`Person`] at `urn`.
+///
+/// If the ref pointed to by [`Urn::path`] is not found, `None` is returned.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn person<S>(storage: &S, urn: &Urn) -> Result<Option<Audit>, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let storage = storage.as_ref();
+    match tip(storage, urn)? {
+        None => Ok(None),
+        Some(tip) => {
+            let history = storage
+                .identities::<Person>()
+                .iter(tip)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Some(audit(urn, history)))
+        },
+    }
+}
+
+/// Audit the history of the [`Project`] at `urn`.
+///
+/// The indirect delegations of each revision are as they were inlined when
+/// the revision was made, ie. updates of the delegating [`Person`]s are not
+/// taken into account (unlike in [`super::project::verify`]).
+///
+/// If the ref pointed to by [`Urn::path`] is not found, `None` is returned.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn project<S>(storage: &S, urn: &Urn) -> Result<Option<Audit>, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let storage = storage.as_ref();
+    match tip(storage, urn)? {
+        None => Ok(None),
+        Some(tip) => {
+            let history = storage
+                .identities::<Project>()
+                .iter(tip)?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Some(audit(urn, history)))
+        },
+    }
+}
+
+fn tip(storage: &storage::ReadOnly, urn: &Urn) -> Result<Option<git2::Oid>, Error> {
+    match storage.reference(&Reference::try_from(urn)?) {
+        Ok(Some(reference)) => Ok(Some(reference.peel_to_commit()?.id())),
+        Ok(None) => Ok(None),
+        Err(storage::Error::Git(e)) if is_not_found_err(&e) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Delegations which can be listed in an [`Audit`].
+pub(super) trait Delegates {
+    fn members(&self) -> BTreeSet<Delegate>;
+    fn owner(&self, key: &PublicKey) -> Option<Urn>;
+}
+
+impl Delegates for delegation::Direct {
+    fn members(&self) -> BTreeSet<Delegate> {
+        self.iter().copied().map(Delegate::Key).collect()
+    }
+
+    fn owner(&self, _: &PublicKey) -> Option<Urn> {
+        None
+    }
+}
+
+impl Delegates for IndirectDelegation {
+    fn members(&self) -> BTreeSet<Delegate> {
+        self.iter()
+            .map(|d| d.either(|key| Delegate::Key(*key), |id| Delegate::Person(id.urn())))
+            .collect()
+    }
+
+    fn owner(&self, key: &PublicKey) -> Option<Urn> {
+        delegation::Indirect::owner(self, key).map(|id| id.urn())
+    }
+}
+
+/// Classify the revisions in `history` like [`Verifying::verify`] does.
+fn audit<P, D>(urn: &Urn, history: Vec<Identity<Doc<P, D>>>) -> Audit
+where
+    D: Delegates + Delegations,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    let mut revisions: Vec<Entry> = Vec::with_capacity(history.len());
+    let mut members_of: Vec<(Revision, BTreeSet<Delegate>)> = Vec::with_capacity(history.len());
+    let mut head: Option<VerifiedIdentity<Doc<P, D>>> = None;
+    let mut parent: Option<VerifiedIdentity<Doc<P, D>>> = None;
+    let mut broken = false;
+
+    for id in history {
+        let members = id.delegations().members();
+        let delegations = {
+            let empty = BTreeSet::new();
+            let prev = id
+                .doc
+                .replaces
+                .and_then(|replaces| {
+                    members_of
+                        .iter()
+                        .rev()
+                        .find(|(revision, _)| *revision == replaces)
+                })
+                .map_or(&empty, |(_, members)| members);
+            Diff::between(prev, &members)
+        };
+        let signatures = id
+            .signatures
+            .keys()
+            .map(|key| {
+                let eligible = id
+                    .delegations()
+                    .eligible(Some(key).into_iter().collect())
+                    .map(|eligible| !eligible.is_empty())
+                    .unwrap_or(false);
+                Signature {
+                    key: *key,
+                    peer: PeerId::from(*key),
+                    eligible,
+                    person: id.delegations().owner(key),
+                }
+            })
+            .collect();
+        let mut entry = Entry {
+            content_id: id.content_id,
+            revision: id.revision,
+            replaces: id.doc.replaces,
+            signatures,
+            threshold: id.doc.threshold,
+            quorum: false,
+            status: Status::Unverified,
+            reason: None,
+            delegations,
+        };
+
+        let quorum = Verifying::from(id).signed().map(|signed| signed.quorum());
+        match quorum {
+            Err(e) => {
+                entry.reject(e);
+                broken = true;
+            },
+            Ok(Err(_)) => entry.status = Status::Proposal,
+            Ok(Ok(_)) if broken => entry.quorum = true,
+            Ok(Ok(quorum)) => {
+                entry.quorum = true;
+                match head.take() {
+                    None => match quorum.verified(None) {
+                        Ok(verified) => {
+                            entry.status = Status::Verified;
+                            head = Some(verified);
+                        },
+                        Err(e) => {
+                            entry.reject(e);
+                            broken = true;
+                        },
+                    },
+
+                    // A confirmation of the current head
+                    Some(cur)
+                        if quorum.revision == cur.revision
+                            && quorum.doc.replaces == cur.doc.replaces =>
+                    {
+                        match quorum.verified(parent.as_ref()) {
+                            Ok(verified) => {
+                                entry.status = Status::Verified;
+                                head = Some(verified);
+                            },
+                            Err(e) => {
+                                entry.reject(e);
+                                head = Some(cur);
+                            },
+                        }
+                    }
+
+                    Some(cur) => match quorum.is_pending_succession(&cur) {
+                        Ok(true) => {
+                            entry.status = Status::Proposal;
+                            head = Some(cur);
+                        },
+                        Ok(false) => match quorum.verified(Some(&cur)) {
+                            Ok(verified) => {
+                                entry.status = Status::Verified;
+                                head = Some(verified);
+                                parent = Some(cur);
+                            },
+                            Err(e) => {
+                                entry.reject(e);
+                                head = Some(cur);
+                                broken = true;
+                            },
+                        },
+                        Err(e) => {
+                            entry.reject(e);
+                            head = Some(cur);
+                            broken = true;
+                        },
+                    },
+                }
+            },
+        }
+
+        members_of.push((entry.revision, members));
+        revisions.push(entry);
+    }
+
+    Audit {
+        urn: urn.clone().with_path(None),
+        revisions,
+    }
+}
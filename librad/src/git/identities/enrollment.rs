@@ -0,0 +1,273 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Enrollment of additional devices into a [`Person`].
+//!
+//! Enrolling a device proceeds as follows:
+//!
+//! 1. The new device generates its key, replicates the [`Person`], and makes a
+//!    [`Request`] (which is transferred to an existing device out-of-band)
+//! 2. An existing device [`enroll`]s the new device, making a new revision of
+//!    the [`Person`] which delegates to the key of the new device. Since this
+//!    revision is only signed by the existing device, it is a proposal until
+//!    the new device signs it, too.
+//! 3. The new device fetches the proposal from the existing device, and
+//!    [`complete`]s the enrollment by signing it, and making the [`Person`]
+//!    its [`LocalIdentity`].
+//!
+//! Once the existing device replicates the [`Person`] from the new device, it
+//! fast-forwards its `rad/id` to the co-signed revision (see
+//! [`crate::git::replication`]), so both devices converge without needing to
+//! [`super::person::merge`].
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    super::{
+        refs::{self, Refs},
+        storage::{self, config, ReadOnlyStorage as _, Storage},
+        tracking,
+        types::{Namespace, Reference},
+    },
+    common::IdRef,
+    local::{self, LocalIdentity},
+    person,
+};
+use crate::{
+    identities::{
+        delegation,
+        git::{error, Person, Urn, VerificationError, Verifying},
+    },
+    internal::canonical::{Cjson, CjsonError},
+    keys::{PublicKey, Signature},
+    peer::PeerId,
+    signer::Signer,
+};
+
+/// Value of the `type` field of the signed contents of a [`Request`], so the
+/// signature can not be mistaken for one over a different kind of data.
+const REQUEST_TYPE: &str = "enrollment";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("the URN {0} does not exist")]
+    NotFound(Urn),
+
+    #[error("invalid signature on enrollment request of {0}")]
+    InvalidSignature(PeerId),
+
+    #[error("{from} has not proposed to enroll the local peer in {urn}")]
+    NotProposed { urn: Urn, from: PeerId },
+
+    #[error("signer error")]
+    Sign(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error(transparent)]
+    Cjson(#[from] CjsonError),
+
+    #[error(transparent)]
+    Identities(#[from] Box<super::Error>),
+
+    #[error(transparent)]
+    LocalId(#[from] local::ValidationError),
+
+    #[error(transparent)]
+    Verification(#[from] VerificationError),
+
+    #[error(transparent)]
+    Verify(#[from] error::Verify),
+
+    #[error(transparent)]
+    Load(#[from] error::Load),
+
+    #[error(transparent)]
+    Store(#[from] error::Store),
+
+    #[error(transparent)]
+    Refs(#[from] refs::stored::Error),
+
+    #[error(transparent)]
+    Track(#[from] tracking::Error),
+
+    #[error(transparent)]
+    Storage(#[from] storage::Error),
+
+    #[error(transparent)]
+    Config(#[from] config::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+impl From<super::Error> for Error {
+    fn from(e: super::Error) -> Self {
+        Self::Identities(Box::new(e))
+    }
+}
+
+/// A request of a device to be enrolled into the [`Person`] at `urn`.
+///
+/// The request is signed by the key of the device, proving that it holds the
+/// corresponding secret key.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub urn: Urn,
+    pub key: PublicKey,
+    pub signature: Signature,
+}
+
+/// The signed contents of a [`Request`].
+#[derive(Serialize)]
+struct Enrollee<'a> {
+    #[serde(rename = "type")]
+    typ: &'static str,
+    urn: &'a Urn,
+    key: &'a PublicKey,
+}
+
+impl Request {
+    /// Create a [`Request`] to enroll the key of `signer` into the [`Person`]
+    /// at `urn`.
+    pub fn new<S>(urn: &Urn, signer: &S) -> Result<Self, Error>
+    where
+        S: Signer,
+    {
+        let urn = urn.clone().with_path(None);
+        let key = PublicKey::from(signer.public_key());
+        let signature = signer
+            .sign_blocking(&canonical_form(&urn, &key)?)
+            .map_err(|e| Error::Sign(Box::new(e)))?
+            .into();
+
+        Ok(Self {
+            urn,
+            key,
+            signature,
+        })
+    }
+
+    /// The [`PeerId`] of the device to enroll.
+    pub fn peer_id(&self) -> PeerId {
+        PeerId::from(self.key)
+    }
+
+    /// Verify that the [`Request`] was signed by [`Request::key`].
+    pub fn verify(&self) -> Result<(), Error> {
+        if self
+            .signature
+            .verify(&canonical_form(&self.urn, &self.key)?, &self.key)
+        {
+            Ok(())
+        } else {
+            Err(Error::InvalidSignature(self.peer_id()))
+        }
+    }
+}
+
+fn canonical_form(urn: &Urn, key: &PublicKey) -> Result<Vec<u8>, CjsonError> {
+    Cjson(Enrollee {
+        typ: REQUEST_TYPE,
+        urn,
+        key,
+    })
+    .canonical_form()
+}
+
+/// Enroll the device which made `request`, by adding its key to the
+/// delegations of the [`Person`].
+///
+/// The enrolled device is tracked, so the revision it signs in turn (see
+/// [`complete`]) is fetched when replicating the [`Person`].
+///
+/// If the key is already delegated to, only the tracking is ensured.
+#[tracing::instrument(
+    level = "debug",
+    skip(storage, request),
+    fields(urn = %request.urn, peer = %request.peer_id()),
+)]
+pub fn enroll(storage: &Storage, request: &Request) -> Result<Person, Error> {
+    request.verify()?;
+
+    let urn = &request.urn;
+    let peer = request.peer_id();
+    let prev = person::get(storage, urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let next = if prev.delegations().contains(&request.key) {
+        prev
+    } else {
+        let prev = Verifying::from(prev).signed()?;
+        let delegations = prev
+            .delegations()
+            .iter()
+            .copied()
+            .chain(Some(request.key))
+            .collect::<delegation::Direct>();
+        let next = storage.read_only().identities::<Person>().update(
+            prev,
+            None,
+            delegations,
+            storage.signer(),
+        )?;
+        IdRef::from(urn).update(storage, next.content_id, &format!("enroll {}", peer))?;
+        next
+    };
+
+    if &peer != storage.peer_id() {
+        tracking::track(storage, urn, peer)?;
+    }
+    Refs::update(storage, urn)?;
+
+    Ok(next)
+}
+
+/// Complete the enrollment of the local device into the [`Person`] at `urn`,
+/// which was proposed by the device `from` (see [`enroll`]).
+///
+/// The proposal is expected to have been replicated from `from`. It is signed
+/// by the local key, and becomes the `rad/id` of `urn`, which is in turn
+/// configured as the default [`LocalIdentity`] (see
+/// [`config::Config::set_user`]).
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn complete(storage: &Storage, urn: &Urn, from: PeerId) -> Result<LocalIdentity, Error> {
+    let urn = urn.clone().with_path(None);
+    let local_peer = *storage.peer_id();
+    let not_proposed = || Error::NotProposed {
+        urn: urn.clone(),
+        from,
+    };
+
+    let ids = storage.read_only().identities::<Person>();
+    let theirs = {
+        let proposal = Reference::rad_id(Namespace::from(&urn)).with_remote(from);
+        let tip = storage
+            .reference(&proposal)?
+            .ok_or_else(not_proposed)?
+            .peel_to_commit()?
+            .id();
+        ids.get(tip)?
+    };
+    if !theirs.delegations().contains(&local_peer) || !theirs.signatures.contains_key(&from) {
+        return Err(not_proposed());
+    }
+
+    let next = if theirs.signatures.contains_key(&local_peer) {
+        theirs
+    } else {
+        ids.create_from(Verifying::from(theirs).signed()?, storage.signer())?
+    };
+    let verified = ids
+        .verify(*next.content_id)
+        .map_err(|e| Error::Verify(e.into()))?;
+    let local_id = LocalIdentity::valid(verified, storage.signer())?;
+
+    IdRef::from(&urn).update(storage, next.content_id, &format!("enrolled by {}", from))?;
+    local_id.link(storage, &urn)?;
+    storage.config()?.set_user(local_id.clone())?;
+    tracking::track(storage, &urn, from)?;
+    Refs::update(storage, &urn)?;
+
+    Ok(local_id)
+}
@@ -10,10 +10,13 @@ use super::{
     super::{refs, storage, types::reference},
     local,
 };
-use crate::identities::{
-    self,
-    git::{Urn, VerificationError},
-    urn,
+use crate::{
+    identities::{
+        self,
+        git::{Urn, VerificationError},
+        urn,
+    },
+    peer::PeerId,
 };
 
 #[derive(Debug, Error)]
@@ -22,6 +25,9 @@ pub enum Error {
     #[error("the URN {0} does not exist")]
     NotFound(Urn),
 
+    #[error("{0} is not a delegation of the identity")]
+    NotDelegate(PeerId),
+
     #[error("failed to build ref from URN")]
     RefFromUrn(#[from] reference::FromUrnError),
 
@@ -61,6 +67,9 @@ pub enum Error {
     #[error(transparent)]
     ProjHist(#[from] identities::git::error::History<identities::git::ProjectDoc>),
 
+    #[error(transparent)]
+    Ext(#[from] identities::payload::ExtError),
+
     #[error(transparent)]
     Git(#[from] git2::Error),
 }
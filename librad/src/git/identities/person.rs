@@ -3,7 +3,7 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{convert::TryFrom, fmt::Debug};
+use std::{collections::BTreeSet, convert::TryFrom, fmt::Debug};
 
 use radicle_git_ext::{self as ext, is_not_found_err, OneLevel};
 
@@ -22,9 +22,12 @@ use crate::{
         self,
         delegation,
         git::{Identities, Verifying},
+        payload::{ExtError, Revocations},
         urn,
     },
+    keys::PublicKey,
     peer::PeerId,
+    signer::Signer,
 };
 
 pub use identities::{
@@ -156,6 +159,62 @@ where
     Ok(next)
 }
 
+/// Rotate the key of `storage`'s signer in the [`Person`] at `urn` to the key
+/// of `signer`.
+///
+/// A new revision is made, in which the old key is replaced by the new one in
+/// the delegations, and is added to the [`Revocations`] of the payload. The
+/// revision is signed by both keys, so it reaches a quorum of both the previous
+/// and the new delegations. Note that this requires the old key to reach a
+/// quorum of the previous delegations on its own.
+///
+/// `rad/signed_refs` is still signed by the old key -- see
+/// [`crate::git::rotation::rotate`] for migrating the storage to the new key.
+#[tracing::instrument(level = "debug", skip(storage, signer))]
+pub fn rotate<S>(storage: &Storage, urn: &Urn, signer: &S) -> Result<Person, Error>
+where
+    S: Signer,
+{
+    let prev = get(storage, urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let prev = Verifying::from(prev).signed()?;
+
+    let old = PublicKey::from(storage.signer().public_key());
+    let new = PublicKey::from(signer.public_key());
+    let delegations = {
+        let mut keys = BTreeSet::from(prev.delegations().clone());
+        if !keys.remove(&old) {
+            return Err(Error::NotDelegate(PeerId::from(old)));
+        }
+        keys.insert(new);
+        delegation::Direct::from(keys)
+    };
+    let payload = {
+        let mut payload = prev.payload().clone();
+        let mut revocations = payload
+            .get_ext::<Revocations>()
+            .map_err(ExtError::from)?
+            .unwrap_or_default();
+        revocations.keys.insert(old);
+        payload.set_ext(revocations)?;
+        payload
+    };
+
+    let proposal = identities(storage).update(prev, payload, delegations, storage.signer())?;
+    let next = identities(storage).create_from(Verifying::from(proposal).signed()?, signer)?;
+    identities(storage)
+        .verify(*next.content_id)
+        .map_err(|e| Error::Verify(e.into()))?;
+
+    common::IdRef::from(urn).update(
+        storage,
+        next.content_id,
+        &format!("rotate to {}", PeerId::from(new)),
+    )?;
+    Refs::update(storage, urn)?;
+
+    Ok(next)
+}
+
 /// Merge and sign the [`Person`] state as seen by `from`.
 #[tracing::instrument(level = "debug", skip(storage))]
 pub fn merge(storage: &Storage, urn: &Urn, from: PeerId) -> Result<Person, Error> {
@@ -29,6 +29,8 @@ use crate::{
 
 pub use identities::{git::Urn, payload::ProjectPayload};
 
+pub mod proposal;
+
 type Namespace = namespace::Namespace<Revision>;
 
 /// Read a [`Project`] from the tip of the ref [`Urn::path`] points to.
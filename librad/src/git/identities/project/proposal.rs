@@ -0,0 +1,252 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Pending updates of a [`Project`] identity.
+//!
+//! A delegate proposes an update of a [`Project`] by making a new revision
+//! which replaces the currently verified one (see [`super::update`]). Until
+//! enough delegates have signed it, the revision does not pass verification,
+//! and is a [`Proposal`]. Other delegates discover proposals after replicating
+//! the `rad/id`s of their peers via [`list`], and co-sign them via [`cosign`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use either::Either;
+use thiserror::Error;
+
+use super::{
+    super::{
+        audit::{Delegates as _, Diff},
+        error::Error as IdentitiesError,
+    },
+    get,
+    identities,
+    verify,
+    ProjectRefs,
+};
+use crate::{
+    git::{
+        refs::{self, Refs},
+        storage::{self, ReadOnlyStorage as _, Storage},
+        types::{Namespace, Reference},
+    },
+    identities::{
+        git::{error, Project, Revision, Urn, VerificationError, VerifiedProject, Verifying},
+        payload::ProjectPayload,
+        sign::Signatures,
+    },
+    peer::PeerId,
+};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("the URN {0} does not exist")]
+    NotFound(Urn),
+
+    #[error("no pending proposal of revision {revision} for {urn}")]
+    NoProposal { urn: Urn, revision: Revision },
+
+    #[error("{0} is not a delegation of the project")]
+    NotDelegate(PeerId),
+
+    #[error(transparent)]
+    Identities(#[from] Box<IdentitiesError>),
+
+    #[error(transparent)]
+    Verification(#[from] VerificationError),
+
+    #[error(transparent)]
+    Load(#[from] error::Load),
+
+    #[error(transparent)]
+    Store(#[from] error::Store),
+
+    #[error(transparent)]
+    Merge(#[from] error::Merge),
+
+    #[error(transparent)]
+    Refs(#[from] refs::stored::Error),
+
+    #[error(transparent)]
+    Storage(#[from] storage::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+impl From<IdentitiesError> for Error {
+    fn from(e: IdentitiesError) -> Self {
+        Self::Identities(Box::new(e))
+    }
+}
+
+/// A revision of a [`Project`] which replaces the currently verified one, but
+/// is not (yet) signed by enough delegates to pass verification.
+#[derive(Clone, Debug)]
+pub struct Proposal {
+    /// The proposed [`Project`], as found in the `rad/id` of one of the
+    /// `proposers`.
+    pub project: Project,
+    /// The peers whose `rad/id` points to the proposed revision.
+    pub proposers: BTreeSet<PeerId>,
+    /// The union of the signatures over the proposed revision found in the
+    /// `rad/id`s of the `proposers`.
+    pub signatures: Signatures,
+    /// The proposed payload, if it differs from the verified one.
+    pub payload: Option<ProjectPayload>,
+    /// The proposed change to the delegations.
+    pub delegations: Diff,
+}
+
+impl Proposal {
+    pub fn revision(&self) -> Revision {
+        self.project.revision
+    }
+
+    /// The peers which signed the proposed revision.
+    pub fn signed_by(&self) -> BTreeSet<PeerId> {
+        self.signatures.keys().copied().map(PeerId::from).collect()
+    }
+}
+
+/// List the pending [`Proposal`]s for the [`Project`] at `urn`.
+///
+/// The `rad/id`s of the local peer and of all delegates of the verified
+/// [`Project`] are considered.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn list<S>(storage: &S, urn: &Urn) -> Result<Vec<Proposal>, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let storage = storage.as_ref();
+    let urn = urn.clone().with_path(None);
+    let head = verify(storage, &urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let members = head.delegations().members();
+
+    let mut proposals: BTreeMap<Revision, Proposal> = BTreeMap::new();
+    for remote in peers(&head, *storage.peer_id()) {
+        let rad_id = Reference::rad_id(Namespace::from(&urn)).with_remote(remote);
+        let tip = match storage.reference(&rad_id)? {
+            None => continue,
+            Some(reference) => reference.peel_to_commit()?.id(),
+        };
+        let project = identities(storage).get(tip)?;
+        if !is_pending(&head, &project) {
+            continue;
+        }
+
+        let proposer = remote.unwrap_or(*storage.peer_id());
+        match proposals.get_mut(&project.revision) {
+            Some(proposal) => {
+                proposal.proposers.insert(proposer);
+                proposal.signatures.extend(project.signatures.clone());
+            },
+            None => {
+                let payload = if project.payload() != head.payload() {
+                    Some(project.payload().clone())
+                } else {
+                    None
+                };
+                let delegations = Diff::between(&members, &project.delegations().members());
+                proposals.insert(
+                    project.revision,
+                    Proposal {
+                        proposers: Some(proposer).into_iter().collect(),
+                        signatures: project.signatures.clone(),
+                        payload,
+                        delegations,
+                        project,
+                    },
+                );
+            },
+        }
+    }
+
+    Ok(proposals.into_values().collect())
+}
+
+/// Co-sign the pending [`Proposal`] of `revision` for the [`Project`] at `urn`
+/// with the local key, and publish the result as the local `rad/id`.
+///
+/// The proposal is merged into the local `rad/id` like [`super::merge`] does,
+/// retaining the signatures of all proposers (see [`Proposal::signatures`]).
+/// If the local `rad/id` is not signed by the local key, the proposal replaces
+/// it instead. The result passes verification once enough delegates have
+/// co-signed it.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn cosign(storage: &Storage, urn: &Urn, revision: Revision) -> Result<Project, Error> {
+    let urn = urn.clone().with_path(None);
+    let local_peer = *storage.peer_id();
+    let proposal = list(storage, &urn)?
+        .into_iter()
+        .find(|proposal| proposal.revision() == revision)
+        .ok_or_else(|| Error::NoProposal {
+            urn: urn.clone(),
+            revision,
+        })?;
+
+    let head = verify(storage, &urn)?.ok_or_else(|| Error::NotFound(urn.clone()))?;
+    let is_delegate = |project: &Project| {
+        project
+            .delegations()
+            .eligible(Some(local_peer.as_public_key()).into_iter().collect())
+            .map_or(false, |eligible| !eligible.is_empty())
+    };
+    if !is_delegate(&head) && !is_delegate(&proposal.project) {
+        return Err(Error::NotDelegate(local_peer));
+    }
+
+    let theirs = Verifying::from(Project {
+        signatures: proposal.signatures,
+        ..proposal.project
+    })
+    .signed()?;
+    let next = match get(storage, &urn)? {
+        Some(ours) if ours.signatures.contains_key(local_peer.as_public_key()) => {
+            let ours = Verifying::from(ours).signed()?;
+            identities(storage).update_from(ours, theirs, storage.signer())?
+        },
+        // We have not signed any revision yet, eg. because the proposal adds
+        // us as a delegate
+        _ => identities(storage).create_from(theirs, storage.signer())?,
+    };
+
+    ProjectRefs::Update(&next, &format!("co-sign revision {}", revision)).apply(storage)?;
+    Refs::update(storage, &urn)?;
+
+    Ok(next)
+}
+
+/// The peers whose `rad/id` may carry proposals, where `None` denotes the local
+/// peer.
+fn peers(head: &VerifiedProject, local_peer: PeerId) -> BTreeSet<Option<PeerId>> {
+    head.delegations()
+        .iter()
+        .flat_map(|d| match d {
+            Either::Left(key) => vec![*key],
+            Either::Right(person) => person.delegations().iter().copied().collect(),
+        })
+        .map(PeerId::from)
+        .map(|peer| Some(peer).filter(|peer| *peer != local_peer))
+        .chain(Some(None))
+        .collect()
+}
+
+/// Whether `project` is a successor of `head` which does not pass
+/// verification.
+fn is_pending(head: &VerifiedProject, project: &Project) -> bool {
+    if project.root != head.root || project.doc.replaces != Some(head.revision) {
+        return false;
+    }
+
+    match Verifying::from(project.clone()).signed() {
+        Err(_) => false,
+        Ok(signed) => signed
+            .quorum()
+            .and_then(|quorum| quorum.verified(Some(head)))
+            .is_err(),
+    }
+}
@@ -12,16 +12,29 @@ use std::{
 
 use git_ext as ext;
 use tempfile::NamedTempFile;
+use url::Url;
 
 use super::{
     local::url::LocalUrl,
     types::{Flat, Force, GenericRef, Reference, Refspec, Remote},
 };
-use crate::peer::PeerId;
+use crate::{
+    identities::{
+        git::{Person, Project},
+        payload::Contributor,
+    },
+    peer::PeerId,
+};
 
 /// Config key to reference generated include files in working copies.
 pub const GIT_CONFIG_PATH_KEY: &str = "include.path";
 
+/// Config key for the large file storage endpoint of `git-lfs`.
+pub const GIT_CONFIG_LFS_URL_KEY: &str = "lfs.url";
+
+/// The path `git-lfs` appends to `lfs.url` to obtain the batch API endpoint.
+const LFS_BATCH_PATH: &str = "/objects/batch";
+
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum Error {
@@ -51,6 +64,12 @@ pub enum Error {
 ///     fetch = refs/remotes/<peer_id>/heads/*:refs/remotes/<handle>@<peer_id>/*
 /// ```
 ///
+/// If a large file storage endpoint is set, it is generated as:
+/// ```text
+/// [lfs]
+///     url = <url>
+/// ```
+///
 /// This file can then be added to the working copy's `config` file as:
 /// ```text
 /// [include]
@@ -68,6 +87,8 @@ pub struct Include<Path> {
     /// Note that the final file name will be named after
     /// the namespace.
     pub local_url: LocalUrl,
+    /// The large file storage endpoint, see [`Include::set_largefiles`].
+    lfs_url: Option<Url>,
 }
 
 impl<Path> Include<Path> {
@@ -77,6 +98,7 @@ impl<Path> Include<Path> {
             remotes: vec![],
             path,
             local_url,
+            lfs_url: None,
         }
     }
 
@@ -85,6 +107,68 @@ impl<Path> Include<Path> {
         self.remotes.push(remote);
     }
 
+    /// Set the [`GIT_CONFIG_LFS_URL_KEY`] to the large file storage endpoint
+    /// advertised by `contributor`, returning the endpoint.
+    ///
+    /// `git-lfs` can only use literal `http(s)` endpoints, so `None` is
+    /// returned (and the endpoint is left unset) if the
+    /// [`Contributor::largefiles`] is a template with expressions, or uses
+    /// another scheme. The batch API path `/objects/batch` is stripped, as
+    /// `git-lfs` appends it itself.
+    pub fn set_largefiles(&mut self, contributor: &Contributor) -> Option<&Url> {
+        self.lfs_url = contributor
+            .largefiles
+            .as_ref()
+            .and_then(|template| template.as_url())
+            .filter(|url| matches!(url.scheme(), "http" | "https"))
+            .map(|mut url| {
+                if let Some(path) = url.path().strip_suffix(LFS_BATCH_PATH) {
+                    let path = path.to_owned();
+                    url.set_path(&path);
+                }
+                url
+            });
+        self.lfs_url.as_ref()
+    }
+
+    /// Set the [`GIT_CONFIG_LFS_URL_KEY`] to the first usable large file
+    /// storage endpoint advertised by the [`Contributor`] extension of either
+    /// `local`, or one of the delegates of `project`, returning the endpoint.
+    ///
+    /// `local` takes precedence, the delegates are considered in the order of
+    /// their [`Urn`]s. Endpoints advertised by other tracked peers are not
+    /// considered, as they could direct the working copy to arbitrary
+    /// servers.
+    ///
+    /// See [`Include::set_largefiles`] for which endpoints are usable. Persons
+    /// with a malformed [`Contributor`] extension are skipped.
+    ///
+    /// [`Urn`]: crate::identities::Urn
+    pub fn set_largefiles_from(
+        &mut self,
+        local: Option<&Person>,
+        project: &Project,
+    ) -> Option<&Url> {
+        let mut delegates = project.delegations().iter().indirect().collect::<Vec<_>>();
+        delegates.sort_by_key(|person| person.urn());
+
+        self.lfs_url = None;
+        for person in local.into_iter().chain(delegates) {
+            match person.payload().get_ext::<Contributor>() {
+                Ok(Some(contributor)) => {
+                    if self.set_largefiles(&contributor).is_some() {
+                        break;
+                    }
+                },
+                Ok(None) => {},
+                Err(e) => {
+                    tracing::warn!(urn = %person.urn(), err = %e, "malformed contributor")
+                },
+            }
+        }
+        self.lfs_url.as_ref()
+    }
+
     /// Writes the contents of the [`git2::Config`] of the include file to disk.
     #[allow(clippy::unit_arg)]
     #[tracing::instrument(level = "debug", skip(self))]
@@ -118,6 +202,12 @@ impl<Path> Include<Path> {
                     writeln!(tmp, "\tpush = {}", spec)?;
                 }
             }
+
+            if let Some(url) = &self.lfs_url {
+                tracing::debug!("{} = {}", GIT_CONFIG_LFS_URL_KEY, url);
+                writeln!(tmp, "[lfs]")?;
+                writeln!(tmp, "\turl = {}", url)?;
+            }
         }
         tmp.as_file().sync_data()?;
         tmp.persist(self.file_path())?;
@@ -160,6 +250,7 @@ impl<Path> Include<Path> {
             remotes,
             path,
             local_url,
+            lfs_url: None,
         }
     }
 
@@ -10,9 +10,13 @@ use std::{
 };
 
 use git2::transport::Service as GitService;
+use git_ext as ext;
 use thiserror::Error;
 
-use crate::peer::{self, PeerId};
+use crate::{
+    git::fetch::{Partial, UnsupportedFilter},
+    peer::{self, PeerId},
+};
 
 #[derive(Debug, PartialEq)]
 pub struct Header<Urn> {
@@ -20,6 +24,11 @@ pub struct Header<Urn> {
     pub repo: Urn,
     pub peer: PeerId,
     pub nonce: Option<u32>,
+    /// The shape of the history requested by an upload-pack.
+    pub partial: Partial,
+    /// The commits the requesting side has no parents of, ie. the contents of
+    /// its `shallow` file.
+    pub shallow: Vec<ext::Oid>,
 }
 
 impl<Urn> Header<Urn> {
@@ -29,8 +38,24 @@ impl<Urn> Header<Urn> {
             repo,
             peer,
             nonce,
+            partial: Partial::default(),
+            shallow: Vec::new(),
         }
     }
+
+    pub fn with_partial(self, partial: Partial, shallow: Vec<ext::Oid>) -> Self {
+        Self {
+            partial,
+            shallow,
+            ..self
+        }
+    }
+
+    /// `true` if the upload-pack needs to be amended with `shallow`, `deepen`,
+    /// and / or `filter` lines.
+    pub fn is_partial(&self) -> bool {
+        !self.partial.is_full() || !self.shallow.is_empty()
+    }
 }
 
 impl<Urn: Display> Display for Header<Urn> {
@@ -53,6 +78,22 @@ impl<Urn: Display> Display for Header<Urn> {
         if let Some(n) = self.nonce {
             write!(f, "\0n={}", n)?;
         }
+        if let GitService::UploadPack = self.service.0 {
+            if let Some(depth) = self.partial.depth {
+                write!(f, "\0depth={}", depth)?;
+            }
+            if let Some(filter) = self.partial.filter {
+                write!(f, "\0filter={}", filter)?;
+            }
+            if !self.shallow.is_empty() {
+                let shallow = self
+                    .shallow
+                    .iter()
+                    .map(|oid| oid.to_string())
+                    .collect::<Vec<_>>();
+                write!(f, "\0shallow={}", shallow.join(","))?;
+            }
+        }
         writeln!(f, "\0")
     }
 }
@@ -80,6 +121,12 @@ pub enum ParseError {
 
     #[error("invalid mode: `{0}`. Must be `ls`, or absent")]
     InvalidMode(String),
+
+    #[error(transparent)]
+    Filter(#[from] UnsupportedFilter),
+
+    #[error("malformed shallow commit")]
+    Shallow(#[source] git2::Error),
 }
 
 impl<Urn> FromStr for Header<Urn>
@@ -104,6 +151,8 @@ where
         let mut peer = None;
         let mut ls = false;
         let mut nonce = None;
+        let mut partial = Partial::default();
+        let mut shallow = Vec::new();
 
         for part in parts {
             if part == "ls" {
@@ -112,6 +161,14 @@ where
                 match k {
                     "host" => peer = Some(v.parse::<PeerId>()?),
                     "n" => nonce = v.parse().ok(),
+                    "depth" => partial.depth = v.parse().ok(),
+                    "filter" => partial.filter = Some(v.parse().map_err(ParseError::from)?),
+                    "shallow" => {
+                        shallow = v
+                            .split(',')
+                            .map(|oid| oid.parse().map_err(ParseError::Shallow))
+                            .collect::<Result<_, _>>()?
+                    },
                     _ => {},
                 }
             }
@@ -138,7 +195,7 @@ where
             unknown => Err(ParseError::InvalidService(unknown.to_owned())),
         }?;
 
-        Ok(Self::new(service, repo, peer, nonce))
+        Ok(Self::new(service, repo, peer, nonce).with_partial(partial, shallow))
     }
 }
 
@@ -11,6 +11,19 @@
 //! a null-terminated string "advertise" to decide whether we should wait for
 //! data to be fed into `stdin` of `git upload-pack` or not.
 //!
+//! # Shallow and partial fetches
+//!
+//! `libgit2` can neither request a shallow nor a partial (ie. filtered) fetch,
+//! so the parameters of such fetches are sent in the header line instead (see
+//! [`Header::partial`]). The corresponding `shallow`, `deepen`, and `filter`
+//! lines are added to the request before it is passed on to `git upload-pack`,
+//! and the shallow info `git upload-pack` responds with is removed from the
+//! response, as `libgit2` wouldn't be able to parse it. The requesting side is
+//! thus responsible for determining the new shallow commits itself.
+//!
+//! Note that serving a namespace which was itself fetched partially yields
+//! the history as it is present locally.
+//!
 //! [`git-daemon`]: https://git-scm.com/docs/git-daemon
 
 use std::{
@@ -25,7 +38,7 @@ use futures::{
     io::{AsyncBufReadExt, AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt, BufReader},
 };
 use git2::transport::Service;
-use git_ext::{into_io_err, RefLike, References, UPLOAD_PACK_HEADER};
+use git_ext::{self as ext, into_io_err, RefLike, References, UPLOAD_PACK_HEADER};
 use tokio::process::{self, Command};
 use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
 
@@ -36,7 +49,11 @@ use super::{
     },
     header::{self, Header},
 };
-use crate::paths::Paths;
+use crate::{git::fetch::Partial, paths::Paths};
+
+/// The depth git uses to denote "all of the history", when deepening a shallow
+/// repository.
+const INFINITE_DEPTH: u32 = 0x7fff_ffff;
 
 #[derive(Clone)]
 pub struct GitServer {
@@ -99,11 +116,12 @@ where
     #[allow(clippy::unit_arg)]
     #[tracing::instrument(skip(self))]
     pub async fn run(mut self) -> io::Result<()> {
+        let amend = Amend::from_header(&self.header);
         let Header { service, repo, .. } = self.header;
         match *service {
             Service::UploadPack => {
                 tracing::info!("upload pack");
-                UploadPack::upload_pack(&self.repo_path)?
+                UploadPack::upload_pack(&self.repo_path, amend)?
                     .run(self.recv, self.send)
                     .await?;
             },
@@ -126,7 +144,7 @@ where
 
 enum UploadPack {
     AdvertiseRefs(process::Child),
-    UploadPack(process::Child),
+    UploadPack(process::Child, Option<Amend>),
 }
 
 impl UploadPack {
@@ -182,9 +200,12 @@ impl UploadPack {
     }
 
     #[tracing::instrument(level = "debug")]
-    fn upload_pack(repo_path: &Path) -> io::Result<Self> {
+    fn upload_pack(repo_path: &Path, amend: Option<Amend>) -> io::Result<Self> {
         let mut git = Command::new("git");
         git.arg("-c").arg("uploadpack.allowanysha1inwant=true");
+        if amend.as_ref().map_or(false, |a| a.partial.filter.is_some()) {
+            git.arg("-c").arg("uploadpack.allowfilter=true");
+        }
 
         git_tracing(&mut git);
         git.args(&[
@@ -200,7 +221,7 @@ impl UploadPack {
         .stderr(Stdio::inherit())
         .kill_on_drop(true)
         .spawn()
-        .map(Self::UploadPack)
+        .map(|child| Self::UploadPack(child, amend))
     }
 
     #[allow(clippy::unit_arg)]
@@ -241,16 +262,23 @@ impl UploadPack {
                 }
             },
 
-            Self::UploadPack(mut child) => {
+            Self::UploadPack(mut child, amend) => {
                 let mut stdin = child.stdin.take().unwrap().compat_write();
                 let mut stdout = child.stdout.take().unwrap().compat();
 
-                futures::try_join!(
-                    futures::io::copy(&mut recv, &mut stdin),
-                    futures::io::copy(&mut stdout, &mut send),
-                    child.wait(),
-                )
-                .and_then(|(_, _, status)| {
+                let res = match amend {
+                    None => futures::try_join!(
+                        futures::io::copy(&mut recv, &mut stdin),
+                        futures::io::copy(&mut stdout, &mut send),
+                        child.wait(),
+                    ),
+                    Some(amend) => futures::try_join!(
+                        amend.request(&mut recv, &mut stdin),
+                        amend.response(&mut stdout, &mut send),
+                        child.wait(),
+                    ),
+                };
+                res.and_then(|(_, _, status)| {
                     if !status.success() {
                         Err(io::Error::new(
                             io::ErrorKind::Other,
@@ -265,6 +293,125 @@ impl UploadPack {
     }
 }
 
+/// Amendments to an upload-pack request for a shallow and / or partial fetch.
+#[derive(Debug)]
+struct Amend {
+    partial: Partial,
+    shallow: Vec<ext::Oid>,
+}
+
+impl Amend {
+    fn from_header(header: &Header<Urn>) -> Option<Self> {
+        header.is_partial().then(|| Self {
+            partial: header.partial,
+            shallow: header.shallow.clone(),
+        })
+    }
+
+    /// The depth to request, if any.
+    ///
+    /// If the requesting side has shallow commits, but didn't request a
+    /// depth, its history is deepened to the full history.
+    fn deepen(&self) -> Option<u32> {
+        match self.partial.depth {
+            Some(depth) => Some(depth.get()),
+            None if !self.shallow.is_empty() => Some(INFINITE_DEPTH),
+            None => None,
+        }
+    }
+
+    /// Read the wants from `recv`, add the capabilities and lines of the
+    /// shallow / partial fetch, and pass everything on to `stdin`.
+    async fn request<R, W>(&self, recv: &mut R, stdin: &mut W) -> io::Result<u64>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut first = true;
+        while let Some(mut line) = read_pkt_line(recv).await? {
+            if first {
+                first = false;
+                if line.last() == Some(&b'\n') {
+                    line.pop();
+                }
+                if self.deepen().is_some() {
+                    line.extend_from_slice(b" shallow");
+                }
+                if self.partial.filter.is_some() {
+                    line.extend_from_slice(b" filter");
+                }
+                line.push(b'\n');
+            }
+            stdin.write_all(&pkt_line_bytes(&line)).await?;
+        }
+
+        for oid in &self.shallow {
+            let line = format!("shallow {}\n", oid);
+            stdin.write_all(pkt_line(&line).as_bytes()).await?;
+        }
+        if let Some(depth) = self.deepen() {
+            let line = format!("deepen {}\n", depth);
+            stdin.write_all(pkt_line(&line).as_bytes()).await?;
+        }
+        if let Some(filter) = self.partial.filter {
+            let line = format!("filter {}\n", filter);
+            stdin.write_all(pkt_line(&line).as_bytes()).await?;
+        }
+        stdin.write_all(FLUSH_PKT).await?;
+
+        futures::io::copy(recv, stdin).await
+    }
+
+    /// Strip the shallow info from the response of `git upload-pack`, and pass
+    /// the rest on to `send`.
+    async fn response<R, W>(&self, stdout: &mut R, send: &mut W) -> io::Result<u64>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        if self.deepen().is_some() {
+            while let Some(line) = read_pkt_line(stdout).await? {
+                tracing::trace!("dropping shallow info: {}", String::from_utf8_lossy(&line));
+            }
+        }
+
+        futures::io::copy(stdout, send).await
+    }
+}
+
+const FLUSH_PKT: &[u8] = b"0000";
+
+/// Read a pkt-line, returning `None` if it is a flush-pkt.
+async fn read_pkt_line<R>(r: &mut R) -> io::Result<Option<Vec<u8>>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len = [0; 4];
+    r.read_exact(&mut len).await?;
+    let len = std::str::from_utf8(&len)
+        .ok()
+        .and_then(|len| usize::from_str_radix(len, 16).ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed pkt-line"))?;
+    match len {
+        0 => Ok(None),
+        1..=4 => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "unexpected special pkt-line",
+        )),
+        len => {
+            let mut buf = vec![0; len - 4];
+            r.read_exact(&mut buf).await?;
+            Ok(Some(buf))
+        },
+    }
+}
+
+fn pkt_line_bytes(data: &[u8]) -> Vec<u8> {
+    let mut buf = format!("{:04x}", 4 + data.len()).into_bytes();
+    buf.extend_from_slice(data);
+    buf
+}
+
 fn git_tracing(git: &mut Command) {
     git.envs(::std::env::vars().filter(|(key, _)| key.starts_with("GIT_TRACE")));
 }
@@ -10,6 +10,11 @@
 //!
 //! `rad-p2p://LOCAL_PEER_ID@REMOTE_PEER_ID/PROJECT_ID`
 //!
+//! The query parameters `depth`, `filter`, and `shallow` request a shallow
+//! and / or partial upload-pack (see [`crate::git::fetch::Partial`]). Since
+//! `libgit2` does not support either, they are passed on in the header line,
+//! and applied by the remote [`GitServer`].
+//!
 //! The local peer id is needed to support testing with multiple peers:
 //! `libgit2` stores custom transports in a `static` variable, so we can
 //! register ours only once per program.
@@ -161,6 +166,8 @@ impl SmartSubtransport for RadTransport {
             repo,
             addr_hints,
             nonce,
+            partial,
+            shallow,
         } = url.parse().map_err(into_git_err)?;
         let stream = self
             .open_stream(&local_peer, &remote_peer, &addr_hints)
@@ -170,7 +177,8 @@ impl SmartSubtransport for RadTransport {
                     remote_peer
                 ))
             })?;
-        let header = Header::new(service, Urn::new(repo), remote_peer, nonce);
+        let header =
+            Header::new(service, Urn::new(repo), remote_peer, nonce).with_partial(partial, shallow);
 
         Ok(Box::new(RadSubTransport {
             header: Some(header),
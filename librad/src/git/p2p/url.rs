@@ -10,11 +10,13 @@ use std::{
     str::FromStr,
 };
 
+use git_ext as ext;
 use multihash::Multihash;
 use thiserror::Error;
 use url::Url;
 
 use crate::{
+    git::fetch::Partial,
     identities::urn::Urn,
     peer::{self, PeerId},
 };
@@ -26,6 +28,10 @@ pub struct GitUrl<R> {
     pub addr_hints: Vec<SocketAddr>,
     pub repo: R,
     pub nonce: Option<u32>,
+    /// The shape of the history to fetch, see [`super::header::Header`].
+    pub partial: Partial,
+    /// The commits the local side has no parents of.
+    pub shallow: Vec<ext::Oid>,
 }
 
 impl<R> GitUrl<R> {
@@ -36,6 +42,8 @@ impl<R> GitUrl<R> {
             addr_hints: &self.addr_hints,
             repo: &self.repo,
             nonce: self.nonce.as_ref(),
+            partial: self.partial,
+            shallow: &self.shallow,
         }
     }
 }
@@ -113,21 +121,29 @@ where
                 let mhash = Multihash::from_bytes(bytes)?;
                 R::try_from(mhash).map_err(|e| Self::Err::Repo(Box::new(e)))
             })?;
-        let (addr_hints, nonce) = url
-            .query_pairs()
-            .fold((Vec::new(), None), |mut acc, (k, v)| {
-                match k.as_ref() {
-                    "addr" => {
-                        if let Ok(addr) = v.parse() {
-                            acc.0.push(addr)
-                        }
-                    },
-                    "n" => acc.1 = v.parse().ok(),
-
-                    _ => {},
-                }
-                acc
-            });
+        let mut addr_hints = Vec::new();
+        let mut nonce = None;
+        let mut partial = Partial::default();
+        let mut shallow = Vec::new();
+        for (k, v) in url.query_pairs() {
+            match k.as_ref() {
+                "addr" => {
+                    if let Ok(addr) = v.parse() {
+                        addr_hints.push(addr)
+                    }
+                },
+                "n" => nonce = v.parse().ok(),
+                "depth" => partial.depth = v.parse().ok(),
+                "filter" => partial.filter = v.parse().ok(),
+                "shallow" => {
+                    if let Ok(oid) = v.parse() {
+                        shallow.push(oid)
+                    }
+                },
+
+                _ => {},
+            }
+        }
 
         Ok(Self {
             local_peer,
@@ -135,6 +151,8 @@ where
             addr_hints,
             repo,
             nonce,
+            partial,
+            shallow,
         })
     }
 }
@@ -146,6 +164,8 @@ pub struct GitUrlRef<'a, R> {
     pub addr_hints: &'a [SocketAddr],
     pub repo: &'a R,
     pub nonce: Option<&'a u32>,
+    pub partial: Partial,
+    pub shallow: &'a [ext::Oid],
 }
 
 impl<'a, R> GitUrlRef<'a, R>
@@ -167,6 +187,8 @@ where
             addr_hints: addr_hints.as_ref(),
             repo: &urn.id,
             nonce: None,
+            partial: Partial::default(),
+            shallow: &[],
         }
     }
 }
@@ -182,6 +204,8 @@ impl<R> GitUrlRef<'_, R> {
             addr_hints: self.addr_hints.to_vec(),
             repo: self.repo.clone(),
             nonce: self.nonce.copied(),
+            partial: self.partial,
+            shallow: self.shallow.to_vec(),
         }
     }
 }
@@ -195,6 +219,8 @@ impl<'a, R> Clone for GitUrlRef<'a, R> {
             addr_hints: self.addr_hints,
             repo: self.repo,
             nonce: self.nonce,
+            partial: self.partial,
+            shallow: self.shallow,
         }
     }
 }
@@ -229,6 +255,13 @@ where
             if let Some(n) = git.nonce {
                 query.append_pair("n", &n.to_string());
             }
+            if let Some(depth) = git.partial.depth {
+                query.append_pair("depth", &depth.to_string());
+            }
+            if let Some(filter) = git.partial.filter {
+                query.append_pair("filter", &filter.to_string());
+            }
+            query.extend_pairs(git.shallow.iter().map(|oid| ("shallow", oid.to_string())));
         }
         let repo: Multihash = git.repo.into();
         url.set_path(&format!(
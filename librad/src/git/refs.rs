@@ -37,10 +37,29 @@ use crate::{
 pub use crate::identities::git::Urn;
 pub use git_ext::Oid;
 
-/// The depth of the tracking graph (ie. [`Remotes`]) to retain per peer.
-// TODO(kim): bubble up as parameter
+/// The default depth of the tracking graph (ie. [`Remotes`]) to retain per
+/// peer.
+///
+/// The depth can be configured per [`Urn`] via
+/// [`storage::Config::set_tracking_graph_depth`].
 pub const TRACKING_GRAPH_DEPTH: usize = 3;
 
+/// Determine the depth of the tracking graph to retain per peer in the context
+/// of `urn`.
+///
+/// This is the depth recorded in the storage config for `urn`, if any, or
+/// [`TRACKING_GRAPH_DEPTH`] otherwise.
+pub fn tracking_graph_depth<S>(storage: &S, urn: &Urn) -> Result<usize, stored::Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    Ok(storage
+        .as_ref()
+        .config()?
+        .tracking_graph_depth(urn)?
+        .unwrap_or(TRACKING_GRAPH_DEPTH))
+}
+
 /// The transitive tracking graph.
 // **NOTE**: A recursion limit of 128 is imposed by `serde_json` when deserialising.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -196,6 +215,9 @@ pub mod stored {
         #[error(transparent)]
         Store(#[from] storage::Error),
 
+        #[error(transparent)]
+        Config(#[from] storage::config::Error),
+
         #[error(transparent)]
         Git(#[from] git2::Error),
     }
@@ -216,6 +238,13 @@ pub struct Refs {
     /// `refs/notes/*`
     pub notes: BTreeMap<reference::OneLevel, Oid>,
 
+    /// `refs/cobs/*`
+    ///
+    /// Omitted from the signed form if empty, so signatures made before cobs
+    /// existed remain valid.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub cobs: BTreeMap<reference::OneLevel, Oid>,
+
     /// The [`Remotes`], ie. tracking graph.
     ///
     /// Note that this does does not include the oids, as they can be determined
@@ -225,8 +254,10 @@ pub struct Refs {
 
 impl Refs {
     /// Compute the [`Refs`] from the current storage state at [`Urn`].
+    ///
+    /// The [`Remotes`] of each tracked peer are retained up to `depth` levels.
     #[tracing::instrument(level = "debug", skip(storage, urn), fields(urn = %urn))]
-    pub fn compute<S>(storage: &S, urn: &Urn) -> Result<Self, stored::Error>
+    pub fn compute<S>(storage: &S, urn: &Urn, depth: usize) -> Result<Self, stored::Error>
     where
         S: AsRef<storage::ReadOnly>,
     {
@@ -267,7 +298,12 @@ impl Refs {
             .map(refined)
             .collect::<Result<_, _>>()?;
         let notes = storage
-            .references(&Reference::notes(namespace, None))?
+            .references(&Reference::notes(namespace.clone(), None))?
+            .filter_map(peeled)
+            .map(refined)
+            .collect::<Result<_, _>>()?;
+        let cobs = storage
+            .references(&Reference::cobs(namespace, None))?
             .filter_map(peeled)
             .map(refined)
             .collect::<Result<_, _>>()?;
@@ -275,7 +311,7 @@ impl Refs {
         let mut remotes = tracking::tracked(storage, urn)?.collect::<Remotes<PeerId>>();
         for (peer, tracked) in remotes.iter_mut() {
             if let Some(refs) = Self::load(storage, urn, *peer)? {
-                *tracked = Box::new(refs.remotes.cutoff(TRACKING_GRAPH_DEPTH));
+                *tracked = Box::new(refs.remotes.cutoff(depth));
             }
         }
 
@@ -284,6 +320,7 @@ impl Refs {
             rad,
             tags,
             notes,
+            cobs,
             remotes,
         })
     }
@@ -325,15 +362,46 @@ impl Refs {
     /// Compute the current [`Refs`], sign them, and store them at the
     /// `rad/signed_refs` branch of [`Urn`].
     ///
+    /// The depth of the tracking graph is determined by
+    /// [`tracking_graph_depth`].
+    ///
     /// If the result of [`Self::compute`] is the same as the alread-stored
     /// [`Refs`], no commit is made and `None` is returned. Otherwise, the
     /// new and persisted [`Refs`] are returned in a `Some`.
+    ///
+    /// The `rad/signed_refs` branch, as well as the refs being signed, are
+    /// locked in a [`storage::Transaction`] for the duration of the update,
+    /// so the signed refs always correspond to the state of the namespace at
+    /// the time `rad/signed_refs` was written.
     #[tracing::instrument(skip(storage, urn), fields(urn = %urn))]
     pub fn update(storage: &Storage, urn: &Urn) -> Result<Option<Self>, stored::Error> {
         let branch = Reference::rad_signed_refs(Namespace::from(urn), None);
         tracing::debug!("updating signed refs for {}", branch);
 
-        let signed_refs = Self::compute(storage, urn)?.sign(storage.signer())?;
+        let mut tx = storage.transaction()?;
+        let branch_name = reference::RefLike::from(&branch);
+        tx.lock(&branch_name)?;
+
+        // Lock the refs to sign, and recompute, as they may have moved in the
+        // meantime. Refs created in the meantime are not locked yet, so repeat
+        // until all of the computed refs are.
+        let depth = tracking_graph_depth(storage, urn)?;
+        let namespace = reflike!("refs/namespaces").join(Namespace::from(urn));
+        let refs = loop {
+            let refs = Self::compute(storage, urn, depth)?;
+            let mut all_locked = true;
+            for ((name, _), category) in refs.iter_categorised() {
+                let name = namespace.join(name.clone().into_qualified(category.into()));
+                if !tx.is_locked(&name) {
+                    tx.lock(&name)?;
+                    all_locked = false;
+                }
+            }
+            if all_locked {
+                break refs;
+            }
+        };
+        let signed_refs = refs.sign(storage.signer())?;
 
         let raw_git = storage.as_raw();
 
@@ -363,14 +431,19 @@ impl Refs {
 
         let commit_id = {
             let author = raw_git.signature()?;
-            raw_git.commit(
-                Some(reference::RefLike::from(&branch).as_str()),
+            let msg = format!("Update rad/signed_refs for {}", urn);
+            let commit_id = raw_git.commit(
+                None,
                 &author,
                 &author,
-                &format!("Update rad/signed_refs for {}", urn),
+                &msg,
                 &tree,
                 &parent.iter().collect::<Vec<&git2::Commit>>(),
-            )?
+            )?;
+            tx.set_target(&branch_name, commit_id, &msg)?;
+            tx.commit()?;
+
+            commit_id
         };
         tracing::trace!(
             "updated signed refs at {} to {}: {:?}",
@@ -405,6 +478,7 @@ impl Refs {
             rad,
             tags,
             notes,
+            cobs,
             remotes: _,
         } = self;
         heads
@@ -413,6 +487,7 @@ impl Refs {
             .chain(rad.iter().map(|x| (x, RefsCategory::Rad)))
             .chain(tags.iter().map(|x| (x, RefsCategory::Tags)))
             .chain(notes.iter().map(|x| (x, RefsCategory::Notes)))
+            .chain(cobs.iter().map(|x| (x, RefsCategory::Cobs)))
     }
 
     fn canonical_form(&self) -> Result<Vec<u8>, CjsonError> {
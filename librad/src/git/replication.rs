@@ -6,6 +6,7 @@
 use std::{
     collections::{BTreeMap, BTreeSet},
     convert::{TryFrom, TryInto},
+    io,
     iter,
 };
 
@@ -18,7 +19,8 @@ use super::{
     fetch,
     identities::{self, local::LocalIdentity},
     refs::{self, Refs},
-    storage::{self, ReadOnlyStorage, Storage},
+    rotation,
+    storage::{self, fetcher::Redirect, quarantine, Quarantine, ReadOnlyStorage, Storage},
     tracking,
     types::{reference, Force, Namespace, One, Reference},
 };
@@ -29,6 +31,9 @@ use crate::{
 
 pub use crate::identities::git::Urn;
 
+pub mod dry_run;
+pub use dry_run::{dry_run, DryRun};
+
 #[derive(Debug, Error)]
 #[non_exhaustive]
 pub enum Error {
@@ -73,6 +78,18 @@ pub enum Error {
 
     #[error(transparent)]
     Store(#[from] storage::Error),
+
+    #[error(transparent)]
+    Config(#[from] storage::config::Error),
+
+    #[error(transparent)]
+    Quarantine(#[from] quarantine::Error),
+
+    #[error(transparent)]
+    Shallow(#[from] storage::shallow::Error),
+
+    #[error(transparent)]
+    Rotation(#[from] rotation::Error),
 }
 
 impl From<identities::error::Error> for Error {
@@ -81,9 +98,41 @@ impl From<identities::error::Error> for Error {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug)]
 pub struct Config {
     pub fetch_limit: fetch::Limit,
+    /// The depth of the tracking graph to retain per peer for newly cloned
+    /// [`Urn`]s.
+    ///
+    /// The depth is recorded in the storage config when cloning, and the
+    /// recorded value takes precedence for subsequent fetches (see
+    /// [`refs::tracking_graph_depth`]).
+    pub tracking_graph_depth: usize,
+    /// The number of times a replication interrupted by a transport error
+    /// (such as a dropped connection) is retried, see [`replicate`].
+    pub fetch_retries: usize,
+    /// The shape of the history to fetch for newly cloned [`Urn`]s.
+    ///
+    /// Like [`Config::tracking_graph_depth`], this is recorded in the storage
+    /// config when cloning, and the recorded value takes precedence for
+    /// subsequent fetches. To deepen the history of an existing [`Urn`],
+    /// change the recorded value using [`storage::Config::set_partial`] and
+    /// replicate again.
+    ///
+    /// Note that blobs omitted due to a [`fetch::Filter`] are not fetched
+    /// later on, even if the filter is removed.
+    pub partial: fetch::Partial,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fetch_limit: fetch::Limit::default(),
+            tracking_graph_depth: refs::TRACKING_GRAPH_DEPTH,
+            fetch_retries: 2,
+            partial: fetch::Partial::default(),
+        }
+    }
 }
 
 /// The success outcome of [`self::replicate`].
@@ -102,6 +151,12 @@ pub struct ReplicateResult {
     /// Whether the replicated [`Urn`] was previously present in local storage
     /// or not.
     pub mode: Mode,
+
+    /// The shape of the local history of the replicated [`Urn`].
+    ///
+    /// Unless [`fetch::Partial::is_full`], the namespace is partial: commits
+    /// or blobs of the heads may be missing.
+    pub partial: fetch::Partial,
 }
 
 /// The "freshness" of the local view of a repo identity wrt the delegates.
@@ -110,7 +165,7 @@ pub enum IdStatus {
     /// Up-to-date, no further action is required.
     Even,
     /// Delegate tips are either behind or ahead. Interactive review is
-    /// recommended.
+    /// recommended, see [`crate::git::identities::project::proposal`].
     Uneven,
 }
 
@@ -167,11 +222,118 @@ enum ModeInternal {
 /// Note, however, that pushing local modifications requires a `rad/self` to be
 /// set, which is enforced by the
 /// [`crate::git::local::transport::LocalTransport`].
-#[allow(clippy::unit_arg)]
+///
+/// If any peer is tracked in the context of `urn` (see
+/// [`tracking::track_any`]), `remote_peer` is tracked before fetching, and all
+/// peers fetched from it while cloning are retained and tracked.
+///
+/// # Quarantine
+///
+/// All of the above takes place in a [`Quarantine`]: fetched objects and
+/// updated refs only become visible in `storage` after the identities and
+/// signed refs of all peers have been verified. If replication fails, nothing
+/// is left behind (see also [`Quarantine::migrate`]).
+///
+/// # Retries
+///
+/// If a fetch fails due to a transport error, replication is retried (up to
+/// [`Config::fetch_retries`] times) within the same [`Quarantine`], using a
+/// new connection. Objects received by the fetches which completed before the
+/// interruption are retained, and thus not requested again. The interrupted
+/// fetch itself is started over: resuming a partially received packfile is not
+/// supported, as the git protocol provides no means to request the remainder
+/// of a pack, and an incomplete pack can't be indexed to negotiate from.
+///
+/// If migrating the [`Quarantine`] fails because a ref was modified
+/// concurrently, replication is retried in a new [`Quarantine`], counting
+/// towards the same [`Config::fetch_retries`].
 #[tracing::instrument(skip(storage, fetcher, whoami))]
-pub fn replicate<'a, F>(
-    storage: &'a Storage,
-    mut fetcher: F,
+pub fn replicate<F>(
+    storage: &Storage,
+    fetcher: F,
+    config: Config,
+    whoami: Option<LocalIdentity>,
+) -> Result<ReplicateResult, Error>
+where
+    F: fetch::Fetcher<PeerId = PeerId, UrnId = Revision> + Redirect,
+{
+    let urn = Urn::new(fetcher.urn().id);
+    let cloning = !storage.has_urn(&urn)?;
+
+    let mut attempt = 0;
+    loop {
+        let quarantine = Quarantine::new(storage, &urn)?;
+        let mut result = loop {
+            let redirected = fetcher
+                .redirect(quarantine.storage())
+                .map_err(quarantine::Error::from)?;
+            match replicate_in_place(quarantine.storage(), redirected, config, whoami.clone()) {
+                Err(Error::Fetch(e)) if attempt < config.fetch_retries && is_transient(&*e) => {
+                    attempt += 1;
+                    tracing::warn!(err = %e, attempt, "fetch interrupted, retrying");
+                },
+                res => break res?,
+            }
+        };
+        // A retried clone may have found the namespace in the quarantine
+        if cloning {
+            result.mode = Mode::Clone;
+        }
+        match quarantine.migrate(storage) {
+            Err(e) if attempt < config.fetch_retries && is_transient(&e) => {
+                attempt += 1;
+                tracing::warn!(err = %e, attempt, "concurrent modification, retrying");
+            },
+            res => {
+                res?;
+                return Ok(result);
+            },
+        }
+    }
+}
+
+/// Determine if a replication error is transient, ie. replication may succeed
+/// if it is retried.
+///
+/// Errors raised by the transport (including I/O timeouts) are considered
+/// transient, as are concurrent updates of the refs a [`Quarantine`] migrates
+/// -- other git errors, such as a corrupt pack or a rejected ref update, would
+/// just fail again.
+fn is_transient(e: &(dyn std::error::Error + 'static)) -> bool {
+    use storage::fetcher::error::FetchError;
+
+    let mut source = Some(e);
+    while let Some(e) = source {
+        if let Some(quarantine::Error::Concurrent { .. }) = e.downcast_ref::<quarantine::Error>() {
+            return true;
+        }
+        if let Some(FetchError::Git(e)) = e.downcast_ref::<FetchError>() {
+            if matches!(
+                e.class(),
+                git2::ErrorClass::Net | git2::ErrorClass::Ssh | git2::ErrorClass::Http
+            ) {
+                return true;
+            }
+        }
+        if let Some(e) = e.downcast_ref::<io::Error>() {
+            if e.kind() == io::ErrorKind::TimedOut {
+                return true;
+            }
+        }
+        source = e.source();
+    }
+
+    false
+}
+
+/// [`replicate`] directly into `storage`, ie. without a [`Quarantine`].
+///
+/// If any peer is tracked (see [`tracking::track_any`]), the remote peer and
+/// the peers fetched from it are tracked. If replication fails, the peers
+/// which were not tracked before are untracked again.
+fn replicate_in_place<F>(
+    storage: &Storage,
+    fetcher: F,
     config: Config,
     whoami: Option<LocalIdentity>,
 ) -> Result<ReplicateResult, Error>
@@ -180,11 +342,54 @@ where
     F::Error: std::error::Error + Send + Sync + 'static,
 {
     let remote_peer = *fetcher.remote_peer();
-    let local_peer_id = storage.peer_id();
-    if local_peer_id == &remote_peer {
+    if storage.peer_id() == &remote_peer {
         return Err(Error::SelfReplication);
     }
     let urn = Urn::new(fetcher.urn().id);
+    let track_any = tracking::is_tracked_any(storage, &urn)?;
+    if !track_any {
+        return replicate_tracked(storage, fetcher, config, whoami, false);
+    }
+
+    let tracked = tracking::tracked(storage, &urn)?.collect::<BTreeSet<_>>();
+    tracking::track(storage, &urn, remote_peer)?;
+    let res = replicate_tracked(storage, fetcher, config, whoami, true);
+    if res.is_err() {
+        if let Err(e) = untrack_new(storage, &urn, &tracked) {
+            tracing::warn!(err = ?e, "failed to roll back tracking");
+        }
+    }
+    res
+}
+
+/// Untrack all peers tracked in the context of `urn` which are not in
+/// `before`.
+fn untrack_new(storage: &Storage, urn: &Urn, before: &BTreeSet<PeerId>) -> Result<(), Error> {
+    let new = tracking::tracked(storage, urn)?
+        .filter(|peer| !before.contains(peer))
+        .collect::<Vec<_>>();
+    for peer in new {
+        tracking::untrack(storage, urn, peer)?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::unit_arg)]
+fn replicate_tracked<F>(
+    storage: &Storage,
+    mut fetcher: F,
+    config: Config,
+    whoami: Option<LocalIdentity>,
+    track_any: bool,
+) -> Result<ReplicateResult, Error>
+where
+    F: fetch::Fetcher<PeerId = PeerId, UrnId = Revision>,
+    F::Error: std::error::Error + Send + Sync + 'static,
+{
+    let remote_peer = *fetcher.remote_peer();
+    let local_peer_id = storage.peer_id();
+    let urn = Urn::new(fetcher.urn().id);
     let (mut updated_tips, next) = determine_mode(
         storage,
         &mut fetcher,
@@ -198,7 +403,25 @@ where
             identity,
             fetched_peers,
         } => {
-            let (allowed, id_status) = match identity {
+            let (depth, partial) = {
+                let mut cfg = storage.config()?;
+                let depth = match cfg.tracking_graph_depth(&urn)? {
+                    Some(depth) => depth,
+                    None => {
+                        cfg.set_tracking_graph_depth(&urn, Some(config.tracking_graph_depth))?;
+                        config.tracking_graph_depth
+                    },
+                };
+                let partial = match cfg.partial(&urn)? {
+                    partial if partial.is_full() => {
+                        cfg.set_partial(&urn, config.partial)?;
+                        config.partial
+                    },
+                    partial => partial,
+                };
+                (depth, partial)
+            };
+            let (mut allowed, id_status) = match identity {
                 SomeIdentity::Project(proj) => {
                     let delegates = project::delegate_views(storage, proj, Some(remote_peer))?;
                     let mut allowed = delegates.keys().copied().collect::<BTreeSet<_>>();
@@ -213,6 +436,7 @@ where
                         storage,
                         &mut fetcher,
                         config.fetch_limit,
+                        depth,
                         delegates,
                         &rad_id,
                         proj,
@@ -238,6 +462,14 @@ where
                 },
             };
 
+            // If we're tracking any peer, keep whatever we got
+            if track_any {
+                for peer in fetched_peers.iter().filter(|peer| *peer != local_peer_id) {
+                    tracking::track(storage, &urn, *peer)?;
+                }
+                allowed.extend(fetched_peers.iter().copied());
+            }
+
             // Symref `rad/self` if a `LocalIdentity` was given
             if let Some(local_id) = whoami {
                 local_id.link(storage, &urn)?;
@@ -248,6 +480,7 @@ where
                     updated_tips,
                     identity: id_status,
                     mode: Mode::Clone,
+                    partial,
                 },
                 fetched_peers.difference(&allowed).copied().collect(),
             ))
@@ -258,6 +491,8 @@ where
             identity,
             existing,
         } => {
+            let depth = refs::tracking_graph_depth(storage, &urn)?;
+            let partial = storage.config()?.partial(&urn)?;
             let (result, updated) = match identity {
                 SomeIdentity::Project(proj) => {
                     let delegate_views = project::delegate_views(storage, proj, None)?;
@@ -271,6 +506,7 @@ where
                         storage,
                         &mut fetcher,
                         config.fetch_limit,
+                        depth,
                         delegate_views,
                         &rad_id,
                         proj,
@@ -285,6 +521,7 @@ where
                             updated_tips,
                             identity: id_status,
                             mode: Mode::Fetch,
+                            partial,
                         },
                         updated_tracked,
                     )
@@ -297,6 +534,7 @@ where
                             updated_tips,
                             identity: id_status,
                             mode: Mode::Fetch,
+                            partial,
                         },
                         tracking::tracked(storage, &urn)?.collect::<BTreeSet<_>>(),
                     )
@@ -434,8 +672,7 @@ fn adopt_rad_self(storage: &Storage, urn: &Urn, peer: PeerId) -> Result<(), Erro
         {
             let rad_id = unsafe_into_urn(Reference::rad_id(Namespace::from(person.urn())));
             if !storage.has_urn(&person.urn())? {
-                ensure_rad_id(storage, &rad_id, person.content_id)?;
-                symref(storage, &rad_id, rad_self)?;
+                adopt_rad_id(storage, &rad_id, person.content_id, rad_self)?;
                 tracking::track(storage, &rad_id, peer)?;
             }
         }
@@ -444,6 +681,32 @@ fn adopt_rad_self(storage: &Storage, urn: &Urn, peer: PeerId) -> Result<(), Erro
     Ok(())
 }
 
+/// Create the `rad/id` ref of `urn` pointing to `tip`, along with the symbolic
+/// ref `symbolic` pointing to it, in a single [`storage::Transaction`].
+///
+/// Refs which already exist are left untouched.
+fn adopt_rad_id(
+    storage: &Storage,
+    urn: &Urn,
+    tip: ext::Oid,
+    symbolic: Reference<One>,
+) -> Result<(), Error> {
+    let rad_id = ext::RefLike::from(&Reference::rad_id(Namespace::from(urn)));
+    let symbolic = ext::RefLike::from(&symbolic);
+
+    let mut tx = storage.transaction()?;
+    let store_err = |e: git2::Error| Error::Store(e.into());
+    tx.create(&rad_id, tip.into(), &format!("Initial rad/id for {}", urn))
+        .map_err(store_err)?;
+    tx.create_symbolic(
+        &symbolic,
+        &rad_id,
+        &format!("creating symbolic ref {} -> {}", symbolic, rad_id),
+    )
+    .map_err(store_err)?;
+    tx.commit().map_err(store_err)
+}
+
 fn symref(storage: &Storage, top_level: &Urn, symbolic: Reference<One>) -> Result<(), Error> {
     // Now point our view to the top-level
     Reference::try_from(top_level)
@@ -551,6 +814,8 @@ mod person {
         let delegations = match identities::person::verify(storage, rad_id)? {
             None => Err(Error::MissingIdentity),
             Some(person) => {
+                let person = latest_tracked(storage, &urn, person)?;
+                rotation::adopt_revocations(storage, &person)?;
                 let delegations = person
                     .into_inner()
                     .doc
@@ -574,6 +839,43 @@ mod person {
         adopt_latest(storage, &person.urn(), delegations)
     }
 
+    /// Find the most recent view of the `Person` among the tracked peers
+    /// which builds on `ours`.
+    ///
+    /// The delegations may have changed in a revision we don't know about yet,
+    /// eg. when a device was enrolled (see `identities::enrollment`), in which
+    /// case the new delegations can only be found in the view of the newly
+    /// delegated peer.
+    fn latest_tracked(
+        storage: &Storage,
+        urn: &Urn,
+        ours: VerifiedPerson,
+    ) -> Result<VerifiedPerson, Error> {
+        let mut latest = ours;
+        for peer in tracking::tracked(storage, urn)? {
+            if latest.delegations().contains(&peer) {
+                continue;
+            }
+            let remote_urn =
+                unsafe_into_urn(Reference::rad_id(Namespace::from(urn)).with_remote(peer));
+            match identities::person::verify(storage, &remote_urn) {
+                Ok(Some(theirs)) => {
+                    let descendant = storage
+                        .as_raw()
+                        .graph_descendant_of(*theirs.content_id, *latest.content_id)
+                        .map_err(|e| Error::Store(e.into()))?;
+                    if descendant {
+                        latest = theirs;
+                    }
+                },
+                Ok(None) => {},
+                Err(e) => tracing::warn!(peer = %peer, err = %e, "skipping invalid view"),
+            }
+        }
+
+        Ok(latest)
+    }
+
     /// Adopt the `rad/id` that has the most up-to-date commit from the set of
     /// `Person` delegates.
     #[allow(clippy::unit_arg)]
@@ -588,13 +890,24 @@ mod person {
         let local_peer = storage.peer_id();
         let delegates: BTreeMap<PeerId, VerifiedPerson> = delegates
             .into_iter()
-            .map(|peer| {
+            .filter_map(|peer| {
+                // Our own view is the top-level `rad/id`, if we have one yet
+                if &peer == local_peer {
+                    return identities::person::verify(storage, urn)
+                        .map_err(Error::from)
+                        .transpose()
+                        .map(|verified| verified.map(|verified| (peer, verified)));
+                }
+
                 let remote_urn =
                     unsafe_into_urn(Reference::rad_id(Namespace::from(urn)).with_remote(peer));
-                let verified = identities::person::verify(storage, &remote_urn)?
-                    .ok_or_else(|| Error::MissingIdentities(remote_urn.clone()))?;
+                let verified = identities::person::verify(storage, &remote_urn)
+                    .map_err(Error::from)
+                    .and_then(|verified| {
+                        verified.ok_or_else(|| Error::MissingIdentities(remote_urn.clone()))
+                    });
 
-                Ok((peer, verified))
+                Some(verified.map(|verified| (peer, verified)))
             })
             .collect::<Result<_, Error>>()?;
         let latest = {
@@ -608,11 +921,15 @@ mod person {
                     },
                 }
             }
-            prev.expect("empty delegations")
+            prev.ok_or(Error::MissingIdentity)?
         };
 
         let expected = match delegates.get(local_peer) {
-            Some(ours) => ours.content_id,
+            // If another device of ours signed a revision based on ours (see
+            // `identities::enrollment`), adopt it
+            Some(ours) => {
+                identities::person::fast_forward(storage, &latest)?.unwrap_or(ours.content_id)
+            },
             None => latest.content_id,
         };
         let actual = ensure_rad_id(storage, urn, expected)?;
@@ -646,7 +963,7 @@ mod project {
     ///     already replicated identity.
     ///   * Tracking the delegates
     ///   * Replicating the `rad/signed_refs`
-    ///   * Tracking the remotes of the delegates
+    ///   * Tracking the remotes of the delegates, up to `depth` levels
     ///   * Ensuring we have a top-level `rad/id` that points to the latest
     ///     version
     #[allow(clippy::unit_arg)]
@@ -655,6 +972,7 @@ mod project {
         storage: &Storage,
         fetcher: &mut F,
         limit: fetch::Limit,
+        depth: usize,
         delegates: BTreeMap<PeerId, project::DelegateView>,
         rad_id: &Urn,
         proj: VerifiedProject,
@@ -672,6 +990,7 @@ mod project {
             storage,
             fetcher,
             limit,
+            depth,
             &urn,
             delegates
                 .values()
@@ -693,6 +1012,19 @@ mod project {
 
     /// Fetch `rad/signed_refs` and `refs/heads` of the delegates and our
     /// tracked graph, returning the set of tracked peers.
+    ///
+    /// The tracking graph of each tracked peer is considered up to `depth`
+    /// levels, consistent with [`Refs::compute`].
+    ///
+    /// The history of the heads is fetched as recorded by
+    /// [`storage::Config::set_partial`], and the [`storage::shallow`] commits
+    /// are updated accordingly.
+    ///
+    /// Peers which were revoked (see [`rotation`]) are untracked in favour of
+    /// the peers which replaced them (see [`rotation::replace`]), and are not
+    /// considered part of the tracking graph of other peers. Note that the
+    /// `rad/signed_refs` of a replacement are only fetched by the next
+    /// replication.
     #[tracing::instrument(
         level = "trace",
         skip(storage, fetcher, urn),
@@ -703,6 +1035,7 @@ mod project {
         storage: &Storage,
         fetcher: &mut F,
         limit: fetch::Limit,
+        depth: usize,
         urn: &Urn,
         delegates: BTreeSet<Urn>,
     ) -> Result<(fetch::FetchResult, BTreeSet<PeerId>), Error>
@@ -710,8 +1043,17 @@ mod project {
         F: fetch::Fetcher<PeerId = PeerId, UrnId = Revision>,
         F::Error: std::error::Error + Send + Sync + 'static,
     {
+        // Revoked peers are no longer trusted to sign refs, track their
+        // replacements instead
+        let revoked = rotation::revoked(storage)?;
+        let (revoked_tracked, tracked) = tracking::tracked(storage, urn)?
+            .partition::<BTreeSet<_>, _>(|peer| revoked.contains(peer));
+        for peer in revoked_tracked {
+            let replaced = rotation::replace(storage, urn, peer)?;
+            tracing::info!(peer = %peer, replaced = ?replaced, "revoked peer replaced");
+        }
+
         // Read `signed_refs` for all tracked
-        let tracked = tracking::tracked(storage, urn)?.collect::<BTreeSet<_>>();
         let tracked_sigrefs = tracked
             .into_iter()
             .filter_map(|peer| match Refs::load(storage, urn, peer) {
@@ -721,23 +1063,47 @@ mod project {
                 Err(e) => Some(Err(e)),
             })
             .collect::<Result<BTreeMap<_, _>, _>>()?;
+        let policies = tracked_sigrefs
+            .keys()
+            .map(|peer| Ok((*peer, tracking::policy(storage, urn, *peer)?)))
+            .collect::<Result<BTreeMap<_, _>, Error>>()?;
+        let limit = tracking::policy(storage, urn, *fetcher.remote_peer())?.limit(limit);
+        let partial = storage.config()?.partial(urn)?;
 
         // Fetch all the rest
-        tracing::debug!("fetching heads: {:?}, {:?}", tracked_sigrefs, delegates);
+        tracing::debug!(
+            "fetching heads: {:?}, {:?}, {:?}",
+            tracked_sigrefs,
+            policies,
+            delegates
+        );
         let res = fetcher
             .fetch(fetch::Fetchspecs::Replicate {
                 tracked_sigrefs: tracked_sigrefs.clone(),
+                policies,
                 delegates,
                 limit,
+                partial,
             })
             .map_err(|e| Error::Fetch(e.into()))?;
+        storage::shallow::update(storage, urn, partial.depth)?;
 
         Refs::update(storage, urn)?;
         Ok((
             res,
             tracked_sigrefs
                 .iter()
-                .flat_map(|(peer, refs)| iter::once(*peer).chain(refs.remotes.flatten().copied()))
+                .flat_map(|(peer, refs)| {
+                    iter::once(*peer).chain(
+                        refs.remotes
+                            .clone()
+                            .cutoff(depth)
+                            .flatten()
+                            .copied()
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .filter(|peer| !revoked.contains(peer))
                 .collect(),
         ))
     }
@@ -805,22 +1171,20 @@ mod project {
         project_urn: &Urn,
     ) -> Result<(), Error> {
         let delegate_urn = person.urn();
+        rotation::adopt_revocations(storage, person)?;
 
-        // if the identity is known we see if we can fast-forward it
+        let rad_delegate = Reference::rad_delegate(Namespace::from(project_urn), &delegate_urn);
+
+        // if the identity is known we see if we can fast-forward it, and point
+        // our view to the top-level
         if storage.has_urn(&delegate_urn)? {
             identities::person::fast_forward(storage, person)?;
+            symref(storage, &delegate_urn, rad_delegate)
         } else {
-            ensure_rad_id(storage, &delegate_urn, person.content_id)?;
             tracking::track(storage, &delegate_urn, peer)?;
             tracking::track(storage, project_urn, peer)?;
+            adopt_rad_id(storage, &delegate_urn, person.content_id, rad_delegate)
         }
-
-        // Now point our view to the top-level
-        symref(
-            storage,
-            &delegate_urn,
-            Reference::rad_delegate(Namespace::from(project_urn), &delegate_urn),
-        )
     }
 
     /// Track all direct delegations of a `Project`.
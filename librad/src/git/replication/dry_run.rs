@@ -0,0 +1,217 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use git_ext as ext;
+use thiserror::Error;
+
+use super::{replicate_in_place, Config, ReplicateResult, Urn};
+use crate::{
+    git::{
+        fetch,
+        identities,
+        storage::{
+            fetcher::Redirect,
+            quarantine::{self, Change, Quarantine, Target},
+            Storage,
+        },
+    },
+    identities::git::{error::History, Revision},
+    peer::PeerId,
+};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Replication(#[from] super::Error),
+
+    #[error(transparent)]
+    Quarantine(#[from] quarantine::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+/// The outcome of a [`dry_run`].
+#[derive(Debug)]
+pub struct DryRun {
+    /// What [`super::replicate`] would have returned, or the fork which
+    /// prevented it from completing.
+    pub result: Result<ReplicateResult, Fork>,
+    /// The refs which would have been changed, by namespace and remote.
+    ///
+    /// The refs owned by the local peer are keyed by `None`. Ref names are
+    /// relative to the namespace (and remote, if any), eg. `heads/main`.
+    pub refs: BTreeMap<Urn, BTreeMap<Option<PeerId>, RefChanges>>,
+    /// The `rad/id` refs which would have been changed, by namespace and
+    /// remote.
+    pub identities: BTreeMap<Urn, BTreeMap<Option<PeerId>, IdentityChange>>,
+}
+
+impl DryRun {
+    /// `true` if replication would not have changed anything.
+    pub fn is_noop(&self) -> bool {
+        self.refs.is_empty()
+    }
+}
+
+/// The identity histories found to be unrelated, see
+/// [`History::Fork`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fork {
+    pub urn: Urn,
+    pub left: Revision,
+    pub right: Revision,
+}
+
+/// The changes to a set of refs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RefChanges {
+    pub new: BTreeMap<ext::RefLike, Target>,
+    /// Updated refs, along with their old and new targets.
+    pub updated: BTreeMap<ext::RefLike, (Target, Target)>,
+    pub removed: BTreeMap<ext::RefLike, Target>,
+}
+
+/// A change to the identity document revision a `rad/id` ref points to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentityChange {
+    /// The revision before replication, `None` if the ref is new.
+    pub old: Option<Revision>,
+    /// The revision after replication, `None` if the ref was removed.
+    pub new: Option<Revision>,
+}
+
+/// Perform [`super::replicate`] without updating `storage`.
+///
+/// The fetcher is [`Redirect`]ed to a [`Quarantine`], in which replication
+/// proceeds as usual: objects are fetched and verified, and the refs are
+/// updated. The resulting ref changes are reported, after which the quarantine
+/// is discarded. Neither the refs nor the config of `storage` are modified,
+/// and no `rad/self` is set.
+///
+/// A fork in the identity history (as determined when adopting the latest
+/// revision of any identity) is reported as such, along with the refs which
+/// were updated before it was detected. Any other error is returned as-is.
+#[tracing::instrument(skip(storage, fetcher))]
+pub fn dry_run<F>(storage: &Storage, fetcher: F, config: Config) -> Result<DryRun, Error>
+where
+    F: fetch::Fetcher<PeerId = PeerId, UrnId = Revision> + Redirect,
+{
+    let urn = Urn::new(fetcher.urn().id);
+    let quarantine = Quarantine::new(storage, &urn)?;
+    let redirected = fetcher.redirect(quarantine.storage())?;
+
+    let result = match replicate_in_place(quarantine.storage(), redirected, config, None) {
+        Ok(result) => Ok(result),
+        Err(super::Error::Identities(e)) => match fork(&e) {
+            Some(fork) => Err(fork),
+            None => return Err(super::Error::Identities(e).into()),
+        },
+        Err(e) => return Err(e.into()),
+    };
+
+    let repo = quarantine.storage().as_raw();
+    let mut refs: BTreeMap<_, BTreeMap<_, RefChanges>> = BTreeMap::new();
+    let mut identities: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+    for (name, change) in quarantine.changes()? {
+        let (urn, remote, name) = match split(&name) {
+            Some(split) => split,
+            None => {
+                tracing::warn!(name = %name, "skipping ref outside of a namespace");
+                continue;
+            },
+        };
+
+        if name.as_str() == "rad/id" {
+            let (old, new) = match &change {
+                Change::Created { new } => (None, Some(new)),
+                Change::Updated { old, new } => (Some(old), Some(new)),
+                Change::Removed { old } => (Some(old), None),
+            };
+            identities.entry(urn.clone()).or_default().insert(
+                remote,
+                IdentityChange {
+                    old: old.map(|target| revision(repo, target)).transpose()?,
+                    new: new.map(|target| revision(repo, target)).transpose()?,
+                },
+            );
+        }
+
+        let changes = refs.entry(urn).or_default().entry(remote).or_default();
+        match change {
+            Change::Created { new } => {
+                changes.new.insert(name, new);
+            },
+            Change::Updated { old, new } => {
+                changes.updated.insert(name, (old, new));
+            },
+            Change::Removed { old } => {
+                changes.removed.insert(name, old);
+            },
+        }
+    }
+
+    Ok(DryRun {
+        result,
+        refs,
+        identities,
+    })
+}
+
+fn fork(e: &identities::Error) -> Option<Fork> {
+    match e {
+        identities::Error::PersHist(History::Fork { left, right }) => Some(Fork {
+            urn: left.urn(),
+            left: left.revision,
+            right: right.revision,
+        }),
+        identities::Error::ProjHist(History::Fork { left, right }) => Some(Fork {
+            urn: left.urn(),
+            left: left.revision,
+            right: right.revision,
+        }),
+        _ => None,
+    }
+}
+
+/// Split a fully-qualified ref into its namespace, remote (if any), and the
+/// name relative to those.
+fn split(name: &ext::RefLike) -> Option<(Urn, Option<PeerId>, ext::RefLike)> {
+    let mut components = name.as_str().splitn(5, '/');
+    match (
+        components.next(),
+        components.next(),
+        components.next(),
+        components.next(),
+        components.next(),
+    ) {
+        (Some("refs"), Some("namespaces"), Some(ns), Some("refs"), Some(rest)) => {
+            let urn = Urn::try_from_id(ns).ok()?;
+            match rest.strip_prefix("remotes/") {
+                Some(remote) => {
+                    let mut components = remote.splitn(2, '/');
+                    let peer = components.next()?.parse().ok()?;
+                    let name = ext::RefLike::try_from(components.next()?).ok()?;
+                    Some((urn, Some(peer), name))
+                },
+                None => Some((urn, None, ext::RefLike::try_from(rest).ok()?)),
+            }
+        },
+        _ => None,
+    }
+}
+
+/// The identity [`Revision`] a `rad/id` target points to.
+fn revision(repo: &git2::Repository, target: &Target) -> Result<Revision, git2::Error> {
+    let commit = match target {
+        Target::Direct(oid) => repo.find_commit((*oid).into())?,
+        Target::Symbolic(name) => repo.find_reference(name.as_str())?.peel_to_commit()?,
+    };
+
+    Ok(commit.tree_id().into())
+}
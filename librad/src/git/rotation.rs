@@ -0,0 +1,237 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Rotation of the key of the local peer.
+//!
+//! Rotating a key involves:
+//!
+//! 1. Making a new revision of the local [`Person`], in which the old key is
+//!    replaced by the new one, and recorded as revoked (see
+//!    [`identities::person::rotate`])
+//! 2. Re-keying the [`Storage`], such that the new key becomes the local
+//!    [`PeerId`], and the old one is recorded in [`storage::Config::revoked`]
+//! 3. Migrating the storage to the new key, see [`migrate`]
+//!
+//! Once a peer learns about a revocation (either locally, or by replicating a
+//! [`Person`] which revoked a key), the `rad/signed_refs` of the revoked peer
+//! are no longer accepted: it is untracked in the context of every [`Urn`]
+//! replicated subsequently, and the keys which replaced it are tracked instead
+//! (see [`replace`]).
+//!
+//! Note that the key file of the local peer needs to be replaced separately,
+//! which should be done only after [`rotate`] returned successfully.
+
+use std::collections::BTreeSet;
+
+use thiserror::Error;
+
+use super::{
+    identities,
+    refs::{self, Refs},
+    storage::{self, Storage},
+    tracking,
+};
+use crate::{
+    identities::{
+        git::{Person, Urn, VerifiedPerson, Verifying},
+        payload::Revocations,
+    },
+    peer::PeerId,
+    signer::Signer,
+};
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Identities(#[from] Box<identities::Error>),
+
+    #[error(transparent)]
+    Load(#[from] crate::identities::git::error::Load),
+
+    #[error("malformed revocations")]
+    Revocations(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Refs(#[from] refs::stored::Error),
+
+    #[error(transparent)]
+    Track(#[from] tracking::Error),
+
+    #[error(transparent)]
+    Config(#[from] storage::config::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+impl From<identities::Error> for Error {
+    fn from(e: identities::Error) -> Self {
+        Self::Identities(Box::new(e))
+    }
+}
+
+/// Rotate the key of the local peer to the key of `signer`.
+///
+/// `urn` is the [`Person`] delegating to the current key, typically the
+/// default identity of the local peer (see [`storage::Config::user`]).
+///
+/// The returned [`Storage`] uses `signer`, the `storage` passed in can no
+/// longer be used. If an error occurs after the storage was re-keyed,
+/// [`migrate`] can be retried on the storage opened with `signer`.
+#[tracing::instrument(skip(storage, signer))]
+pub fn rotate<S>(storage: Storage, urn: &Urn, signer: S) -> Result<(Storage, Person), Error>
+where
+    S: Signer + Clone,
+    S::Error: std::error::Error + Send + Sync + 'static,
+{
+    let person = identities::person::rotate(&storage, urn, &signer)?;
+    let storage = storage.rekey(signer)?;
+    migrate(&storage)?;
+
+    Ok((storage, person))
+}
+
+/// Migrate `storage` to its current [`PeerId`], after it was re-keyed.
+///
+/// The tracking remotes are updated (see [`tracking::rekey`]), and the
+/// `rad/signed_refs` of all namespaces owned by the local peer are signed
+/// with the current key.
+///
+/// This function is idempotent.
+#[tracing::instrument(skip(storage))]
+pub fn migrate(storage: &Storage) -> Result<(), Error> {
+    tracking::rekey(storage)?;
+    for urn in owned(storage)? {
+        Refs::update(storage, &urn)?;
+    }
+
+    Ok(())
+}
+
+/// The peers recorded as revoked in `storage`.
+pub fn revoked(storage: &Storage) -> Result<BTreeSet<PeerId>, Error> {
+    Ok(storage.config()?.revoked()?)
+}
+
+/// Record the [`Revocations`] of `person` in `storage`, returning the newly
+/// revoked peers.
+///
+/// Only keys which signed a revision of `person` reaching a quorum, while being
+/// delegated to by that revision, are considered. Listing a key in the
+/// delegations is not enough, as it doesn't require the consent of its owner,
+/// so a person can not revoke anybody else's keys. The key of the local peer is
+/// never revoked this way.
+#[tracing::instrument(skip(storage, person), fields(urn = %person.urn()))]
+pub fn adopt_revocations(
+    storage: &Storage,
+    person: &VerifiedPerson,
+) -> Result<BTreeSet<PeerId>, Error> {
+    let revocations = match person.payload().get_ext::<Revocations>()? {
+        Some(revocations) if !revocations.keys.is_empty() => revocations,
+        _ => return Ok(BTreeSet::new()),
+    };
+
+    let signers = past_signers(storage, person)?;
+    let mut config = storage.config()?;
+    let revoked = config.revoked()?;
+    let replacements = person
+        .delegations()
+        .iter()
+        .filter(|key| !revocations.keys.contains(key))
+        .map(|key| PeerId::from(*key))
+        .filter(|peer| peer != storage.peer_id() && !revoked.contains(peer))
+        .collect::<BTreeSet<_>>();
+    let mut adopted = BTreeSet::new();
+    for key in revocations.keys {
+        let peer = PeerId::from(key);
+        if &peer == storage.peer_id() || revoked.contains(&peer) {
+            continue;
+        }
+        if !signers.contains(&key) {
+            tracing::warn!(peer = %peer, "ignoring revocation of foreign key");
+            continue;
+        }
+        config.revoke(peer)?;
+        config.set_replaced_by(peer, replacements.iter().copied())?;
+        adopted.insert(peer);
+    }
+
+    Ok(adopted)
+}
+
+/// Stop tracking the revoked `peer` in the context of `urn`, and track the
+/// peers it was replaced by (see [`storage::Config::replaced_by`]) instead,
+/// returning the newly tracked peers.
+///
+/// The tracking [`tracking::Policy`] of `peer` is carried over to its
+/// replacements, unless a policy was set for them already.
+#[tracing::instrument(skip(storage))]
+pub fn replace(storage: &Storage, urn: &Urn, peer: PeerId) -> Result<BTreeSet<PeerId>, Error> {
+    let revoked = revoked(storage)?;
+    let policy = tracking::policy(storage, urn, peer)?;
+    let mut replaced = BTreeSet::new();
+    for replacement in storage.config()?.replaced_by(&peer)? {
+        if &replacement == storage.peer_id() || revoked.contains(&replacement) {
+            continue;
+        }
+        if tracking::track(storage, urn, replacement)? {
+            if tracking::policy(storage, urn, replacement)? == Default::default() {
+                tracking::set_policy(storage, urn, replacement, &policy)?;
+            }
+            replaced.insert(replacement);
+        }
+    }
+    tracking::untrack(storage, urn, peer)?;
+
+    Ok(replaced)
+}
+
+/// The keys which signed any revision in the history of `person` which reaches
+/// a quorum, and which were delegated to by that revision.
+fn past_signers(
+    storage: &Storage,
+    person: &VerifiedPerson,
+) -> Result<BTreeSet<crate::keys::PublicKey>, Error> {
+    let ids = storage.read_only().identities::<Person>();
+    let mut walk = storage.as_raw().revwalk()?;
+    walk.push(*person.content_id)?;
+
+    let mut signers = BTreeSet::new();
+    for oid in walk {
+        if let Ok(quorum) = Verifying::from(ids.get(oid?)?).quorum() {
+            signers.extend(
+                quorum
+                    .signatures
+                    .keys()
+                    .filter(|key| quorum.delegations().contains(key))
+                    .copied(),
+            );
+        }
+    }
+
+    Ok(signers)
+}
+
+/// The namespaces in which the local peer has published `rad/signed_refs`.
+fn owned(storage: &Storage) -> Result<Vec<Urn>, Error> {
+    let mut owned = Vec::new();
+    for reference in storage
+        .as_raw()
+        .references_glob("refs/namespaces/*/refs/rad/signed_refs")?
+    {
+        let reference = reference?;
+        let id = reference.name().and_then(|name| {
+            name.strip_prefix("refs/namespaces/")?
+                .strip_suffix("/refs/rad/signed_refs")
+        });
+        match id.map(Urn::try_from_id) {
+            Some(Ok(urn)) => owned.push(urn),
+            _ => tracing::trace!("skipping {:?}", reference.name()),
+        }
+    }
+
+    Ok(owned)
+}
@@ -5,8 +5,11 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 use std::{
+    collections::BTreeSet,
     convert::TryFrom,
     fmt::Debug,
+    fs,
+    io,
     marker::PhantomData,
     path::{Path, PathBuf},
 };
@@ -15,7 +18,11 @@ use git2::string_array::StringArray;
 use git_ext::{self as ext, is_not_found_err};
 
 use crate::{
-    git::types::{Many, One, Reference},
+    git::{
+        identities::local::LocalIdentity,
+        tracking,
+        types::{Many, Namespace, One, Reference},
+    },
     identities::git::Urn,
     paths::Paths,
     peer::PeerId,
@@ -24,15 +31,20 @@ use crate::{
 
 pub mod config;
 pub mod fetcher;
+pub mod gc;
 pub mod glob;
 pub mod pool;
+pub mod quarantine;
 pub mod read;
+pub mod shallow;
+pub mod transaction;
 pub mod watch;
 
 pub use config::Config;
-pub use fetcher::{Fetcher, Fetchers};
+pub use fetcher::{Fetcher, Fetchers, Redirect};
 pub use glob::Pattern;
 pub use pool::{Pool, PoolError, Pooled, PooledRef};
+pub use quarantine::Quarantine;
 pub use read::{
     Error,
     ReadOnly,
@@ -42,12 +54,16 @@ pub use read::{
     References,
     ReferencesGlob,
 };
+pub use transaction::Transaction;
 pub use watch::{NamespaceEvent, Watcher};
 
 pub mod error {
+    use std::io;
+
     use thiserror::Error;
 
     use super::config;
+    use crate::git::tracking;
 
     #[derive(Debug, Error)]
     #[non_exhaustive]
@@ -61,6 +77,28 @@ pub mod error {
         #[error("signer key does not match the key used at initialisation")]
         SignerKeyMismatch,
     }
+
+    #[derive(Debug, Error)]
+    #[non_exhaustive]
+    pub enum RemoveNamespace {
+        #[error(transparent)]
+        Config(#[from] config::Error),
+
+        #[error(transparent)]
+        Store(#[from] super::Error),
+
+        #[error(transparent)]
+        Tracking(#[from] tracking::Error),
+
+        #[error(transparent)]
+        Name(#[from] git_ext::reference::name::Error),
+
+        #[error(transparent)]
+        Io(#[from] io::Error),
+
+        #[error(transparent)]
+        Git(#[from] git2::Error),
+    }
 }
 
 /// Low-level operations on the link "monorepo".
@@ -179,6 +217,32 @@ impl Storage {
         })
     }
 
+    /// Re-key the [`Storage`] to `signer`, revoking the current key.
+    ///
+    /// The configured peer id is replaced by the one of `signer`, and the
+    /// current one is recorded as revoked (see [`Config::revoked`]). Note that
+    /// this does not update anything signed by the current key, use
+    /// [`crate::git::rotation::rotate`] instead.
+    pub(crate) fn rekey<S>(self, signer: S) -> Result<Self, config::Error>
+    where
+        S: Signer + Clone,
+        S::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let peer_id = PeerId::from_signer(&signer);
+        self.config()?.rekey(peer_id)?;
+
+        let Self {
+            inner: ReadOnly { backend, .. },
+            fetchers,
+            ..
+        } = self;
+        Ok(Self {
+            inner: ReadOnly { backend, peer_id },
+            signer: BoxedSigner::from(SomeSigner { signer }),
+            fetchers,
+        })
+    }
+
     pub fn read_only(&self) -> &ReadOnly {
         &self.inner
     }
@@ -203,6 +267,134 @@ impl Storage {
         config::path(self.as_raw())
     }
 
+    /// Start a [`Transaction`] to update several refs at once.
+    pub fn transaction(&self) -> Result<Transaction, Error> {
+        Ok(Transaction::new(self.as_raw())?)
+    }
+
+    /// Determine the namespaces which link to `urn` via `rad/ids/<urn>`, ie.
+    /// the identities which delegate to `urn`.
+    pub fn linked_from(&self, urn: &Urn) -> Result<BTreeSet<Urn>, Error> {
+        let mut linked_from = BTreeSet::new();
+        for name in self
+            .as_raw()
+            .references_glob(&format!(
+                "refs/namespaces/*/refs/rad/ids/{}",
+                urn.encode_id()
+            ))?
+            .names()
+        {
+            let linking = ext::RefLike::try_from(name?)
+                .ok()
+                .and_then(|name| Urn::try_from(name).ok())
+                .map(|linking| Urn::new(linking.id));
+            if let Some(linking) = linking.filter(|linking| linking != urn) {
+                linked_from.insert(linking);
+            }
+        }
+
+        Ok(linked_from)
+    }
+
+    /// Determine the namespaces whose `rad/self` points to `urn`, ie. in which
+    /// `urn` is the local identity.
+    pub fn self_linked_from(&self, urn: &Urn) -> Result<BTreeSet<Urn>, Error> {
+        let target = reflike!("refs/namespaces")
+            .join(Namespace::from(&Urn::new(urn.id)))
+            .join(reflike!("refs/rad/id"));
+        let mut linked_from = BTreeSet::new();
+        for reference in self
+            .as_raw()
+            .references_glob("refs/namespaces/*/refs/rad/self")?
+        {
+            let reference = reference?;
+            if reference.symbolic_target() != Some(target.as_str()) {
+                continue;
+            }
+            let linking = reference
+                .name()
+                .and_then(|name| ext::RefLike::try_from(name).ok())
+                .and_then(|name| Urn::try_from(name).ok())
+                .map(|linking| Urn::new(linking.id));
+            if let Some(linking) = linking {
+                linked_from.insert(linking);
+            }
+        }
+
+        Ok(linked_from)
+    }
+
+    /// Remove the namespace of `urn` from the storage.
+    ///
+    /// This removes all refs of the namespace, the tracking relationships (see
+    /// [`tracking::untrack`]), any `rad/ids/<urn>` links from other namespaces
+    /// (see [`Self::linked_from`]), and any configuration specific to `urn`.
+    /// If `urn` is the default identity, the setting is removed, too.
+    ///
+    /// This function does not check whether it is safe to remove the namespace.
+    /// Objects are not removed, use [`gc::collect`] to reclaim the space.
+    ///
+    /// `true` is returned if the namespace existed.
+    #[tracing::instrument(skip(self))]
+    pub fn remove_namespace(&self, urn: &Urn) -> Result<bool, error::RemoveNamespace> {
+        let urn = Urn::new(urn.id);
+
+        for peer in tracking::tracked(self, &urn)?.collect::<Vec<_>>() {
+            tracking::untrack(self, &urn, peer)?;
+        }
+        tracking::untrack_any(self, &urn)?;
+
+        {
+            let mut config = self.config()?;
+            config.set_tracking_graph_depth(&urn, None)?;
+            config.set_partial(&urn, Default::default())?;
+            if config.user()?.as_ref() == Some(&urn) {
+                config.set_user(Option::<LocalIdentity>::None)?;
+            }
+        }
+
+        let namespace = reflike!("refs/namespaces").join(Namespace::from(&urn));
+        let links = self
+            .linked_from(&urn)?
+            .into_iter()
+            .map(|linking| {
+                reflike!("refs/namespaces")
+                    .join(Namespace::from(&linking))
+                    .join(reflike!("refs/rad/ids"))
+                    .join(&urn)
+            })
+            .collect::<Vec<_>>();
+        let refs = self
+            .as_raw()
+            .references_glob(&format!("{}/*", namespace))?
+            .names()
+            .map(|name| Ok(ext::RefLike::try_from(name?)?))
+            .collect::<Result<Vec<_>, error::RemoveNamespace>>()?;
+        let existed = !refs.is_empty();
+
+        let mut tx = self.transaction()?;
+        for name in links.iter().chain(&refs) {
+            tx.remove(name)?;
+        }
+        tx.commit()?;
+
+        // Removing the reflogs directory notifies namespace watchers
+        for dir in &[
+            self.path().join(namespace.as_str()),
+            self.path().join("logs").join(namespace.as_str()),
+        ] {
+            fs::remove_dir_all(dir).or_else(|e| {
+                if e.kind() == io::ErrorKind::NotFound {
+                    Ok(())
+                } else {
+                    Err(e)
+                }
+            })?;
+        }
+
+        Ok(existed)
+    }
+
     pub fn watch(&self) -> watch::Watch {
         watch::Watch { storage: self }
     }
@@ -5,7 +5,14 @@
 
 #![allow(unused)]
 
-use std::{convert::TryFrom, io, marker::PhantomData, path::PathBuf};
+use std::{
+    collections::BTreeSet,
+    convert::TryFrom,
+    io,
+    marker::PhantomData,
+    num::NonZeroU32,
+    path::PathBuf,
+};
 
 use git_ext::{self as ext, is_not_found_err};
 use std_ext::result::ResultExt as _;
@@ -13,6 +20,7 @@ use thiserror::Error;
 
 use super::{super::identities::local::LocalIdentity, Storage};
 use crate::{
+    git::fetch::{Partial, UnsupportedFilter},
     identities::{
         git::{Identities, Urn, VerifiedPerson},
         urn,
@@ -26,6 +34,11 @@ const CONFIG_USER_NAME: &str = "user.name";
 const CONFIG_USER_EMAIL: &str = "user.email";
 const CONFIG_RAD_SELF: &str = "rad.self";
 const CONFIG_RAD_PEER_ID: &str = "rad.peerid";
+const CONFIG_RAD_REVOKED: &str = "rad.revoked";
+const CONFIG_REPLACED_BY: &str = "replacedBy";
+const CONFIG_TRACKING_GRAPH_DEPTH: &str = "trackingGraphDepth";
+const CONFIG_DEPTH: &str = "depth";
+const CONFIG_FILTER: &str = "filter";
 
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -39,6 +52,15 @@ pub enum Error {
     #[error(transparent)]
     Urn(#[from] urn::error::FromStr<ext::oid::FromMultihashError>),
 
+    #[error("invalid tracking graph depth: {0}")]
+    TrackingGraphDepth(i64),
+
+    #[error("invalid depth: {0}")]
+    Depth(i64),
+
+    #[error(transparent)]
+    Filter(#[from] UnsupportedFilter),
+
     #[error(transparent)]
     Git(#[from] git2::Error),
 }
@@ -147,6 +169,44 @@ where
             .map_err(Error::from)
     }
 
+    /// Replace the configured peer id with `peer_id`, revoking the current
+    /// one.
+    ///
+    /// Note that this renders `self` unusable, as the signer no longer
+    /// matches the configured peer id.
+    pub(super) fn rekey(mut self, peer_id: PeerId) -> Result<(), Error> {
+        let name = self.user_name()?;
+        self.revoke(self.peer_id()?)?;
+        self.set_peer_id(peer_id)?;
+        self.set_user_info(&name)
+    }
+
+    /// Record that `peer` was revoked, ie. is no longer trusted to sign
+    /// `rad/signed_refs`.
+    pub fn revoke(&mut self, peer: PeerId) -> Result<(), Error> {
+        let peer = peer.to_string();
+        self.inner
+            .set_multivar(CONFIG_RAD_REVOKED, &format!("^{}$", peer), &peer)
+            .map_err(Error::from)
+    }
+
+    /// Record that the revoked `peer` was replaced by `replacements`, ie. the
+    /// keys the revoking [`crate::identities::git::Person`] delegates to
+    /// instead.
+    pub fn set_replaced_by<I>(&mut self, peer: PeerId, replacements: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = PeerId>,
+    {
+        let key = replaced_by_key(&peer);
+        for replacement in replacements {
+            let replacement = replacement.to_string();
+            self.inner
+                .set_multivar(&key, &format!("^{}$", replacement), &replacement)?;
+        }
+
+        Ok(())
+    }
+
     /// Set the default identity.
     ///
     /// Passing [`Option::None`] removes the setting.
@@ -173,6 +233,52 @@ where
         }
     }
 
+    /// Set the depth of the tracking graph to retain per peer in the context
+    /// of `urn`.
+    ///
+    /// Passing [`Option::None`] removes the setting, reverting to the default
+    /// [`crate::git::refs::TRACKING_GRAPH_DEPTH`].
+    pub fn set_tracking_graph_depth(
+        &mut self,
+        urn: &Urn,
+        depth: Option<usize>,
+    ) -> Result<(), Error> {
+        let key = tracking_graph_depth_key(urn);
+        match depth {
+            None => self
+                .inner
+                .remove(&key)
+                .or_matches::<Error, _, _>(is_not_found_err, || Ok(())),
+            Some(depth) => self.inner.set_i64(&key, depth as i64).map_err(Error::from),
+        }
+    }
+
+    /// Set the shape of the history to fetch in the context of `urn`.
+    ///
+    /// Passing [`Partial::default`] removes the setting, reverting to fetching
+    /// the full history.
+    pub fn set_partial(&mut self, urn: &Urn, partial: Partial) -> Result<(), Error> {
+        let depth = urn_key(urn, CONFIG_DEPTH);
+        match partial.depth {
+            None => self
+                .inner
+                .remove(&depth)
+                .or_matches::<Error, _, _>(is_not_found_err, || Ok(()))?,
+            Some(d) => self.inner.set_i64(&depth, i64::from(d.get()))?,
+        }
+        let filter = urn_key(urn, CONFIG_FILTER);
+        match partial.filter {
+            None => self
+                .inner
+                .remove(&filter)
+                .or_matches::<Error, _, _>(is_not_found_err, || Ok(())),
+            Some(f) => self
+                .inner
+                .set_str(&filter, &f.to_string())
+                .map_err(Error::from),
+        }
+    }
+
     pub(crate) fn as_raw(&self) -> &git2::Config {
         &self.inner
     }
@@ -199,6 +305,71 @@ impl<S> Config<'_, S> {
             .and_then(|peer_id| peer_id.parse().map_err(Error::from))
     }
 
+    /// The depth of the tracking graph to retain per peer in the context of
+    /// `urn`, if it was set.
+    pub fn tracking_graph_depth(&self, urn: &Urn) -> Result<Option<usize>, Error> {
+        self.inner
+            .get_i64(&tracking_graph_depth_key(urn))
+            .map(Some)
+            .or_matches::<Error, _, _>(is_not_found_err, || Ok(None))?
+            .map(|depth| usize::try_from(depth).map_err(|_| Error::TrackingGraphDepth(depth)))
+            .transpose()
+    }
+
+    /// The shape of the history to fetch in the context of `urn`.
+    ///
+    /// Unless it was set, this is the full history.
+    pub fn partial(&self, urn: &Urn) -> Result<Partial, Error> {
+        let depth = self
+            .inner
+            .get_i64(&urn_key(urn, CONFIG_DEPTH))
+            .map(Some)
+            .or_matches::<Error, _, _>(is_not_found_err, || Ok(None))?
+            .map(|depth| {
+                u32::try_from(depth)
+                    .ok()
+                    .and_then(NonZeroU32::new)
+                    .ok_or(Error::Depth(depth))
+            })
+            .transpose()?;
+        let filter = self
+            .inner
+            .get_string(&urn_key(urn, CONFIG_FILTER))
+            .map(Some)
+            .or_matches::<Error, _, _>(is_not_found_err, || Ok(None))?
+            .map(|filter| filter.parse())
+            .transpose()?;
+
+        Ok(Partial { depth, filter })
+    }
+
+    /// The peers recorded as revoked, see [`Config::revoke`].
+    pub fn revoked(&self) -> Result<BTreeSet<PeerId>, Error> {
+        let mut revoked = BTreeSet::new();
+        let mut iter = self.inner.multivar(CONFIG_RAD_REVOKED, None)?;
+        while let Some(entry) = iter.next() {
+            if let Some(peer) = entry?.value() {
+                revoked.insert(peer.parse()?);
+            }
+        }
+
+        Ok(revoked)
+    }
+
+    /// The peers the revoked `peer` was replaced by, see
+    /// [`Config::set_replaced_by`].
+    pub fn replaced_by(&self, peer: &PeerId) -> Result<BTreeSet<PeerId>, Error> {
+        let mut replacements = BTreeSet::new();
+        let mut iter = self.inner.multivar(&replaced_by_key(peer), None)?;
+        while let Some(entry) = iter.next() {
+            if let Some(replacement) = entry?.value() {
+                replacements.insert(replacement.parse()?);
+            }
+        }
+
+        Ok(replacements)
+    }
+
     pub fn user(&self) -> Result<Option<Urn>, Error> {
         self.inner
             .get_string(CONFIG_RAD_SELF)
@@ -209,6 +380,18 @@ impl<S> Config<'_, S> {
     }
 }
 
+fn tracking_graph_depth_key(urn: &Urn) -> String {
+    urn_key(urn, CONFIG_TRACKING_GRAPH_DEPTH)
+}
+
+fn replaced_by_key(peer: &PeerId) -> String {
+    format!("revoked.{}.{}", peer, CONFIG_REPLACED_BY)
+}
+
+fn urn_key(urn: &Urn, key: &str) -> String {
+    format!("urn.{}.{}", urn.encode_id(), key)
+}
+
 impl Config<'_, PhantomData<!>> {
     pub fn readonly(repo: &git2::Repository) -> Result<Self, git2::Error> {
         Self::try_from(repo)
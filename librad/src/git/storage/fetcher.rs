@@ -8,7 +8,8 @@ use std::{
     convert::TryFrom,
     hash::BuildHasherDefault,
     net::SocketAddr,
-    time::Duration,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use dashmap::DashMap;
@@ -17,12 +18,12 @@ use rustc_hash::FxHasher;
 use thiserror::Error;
 use url::Url;
 
-use super::{PoolError, Storage};
+use super::{shallow, PoolError, Storage};
 use crate::{
     executor,
     git::{
-        fetch::{self, FetchResult, Fetchspecs, RemoteHeads},
-        p2p::url::GitUrlRef,
+        fetch::{self, FetchResult, Fetchspecs, Progress, RemoteHeads},
+        p2p::url::{GitUrl, GitUrlRef},
         Urn,
     },
     identities::{self, git::Revision},
@@ -40,10 +41,24 @@ pub struct Info {
 ///
 /// Whenever multiple [`Storage`] instances are in use simultaneously (such as
 /// in a [`super::Pool`]), they MUST share a single instance of [`Fetchers`].
+/// Clones share the same state, so cloning is sufficient.
 ///
 /// Create via [`Default`].
 #[derive(Clone, Default)]
-pub struct Fetchers(DashMap<Urn, Info, BuildHasherDefault<FxHasher>>);
+pub struct Fetchers(Arc<DashMap<Urn, Info, BuildHasherDefault<FxHasher>>>);
+
+impl Fetchers {
+    /// Determine if a fetch of `urn` is currently in-flight.
+    pub(super) fn is_inflight(&self, urn: &Urn) -> bool {
+        self.0.contains_key(urn)
+    }
+}
+
+/// Receiver of [`Progress`] reports, see [`Fetcher::on_progress`].
+pub type OnProgress = Arc<dyn Fn(Progress) + Send + Sync + 'static>;
+
+/// The minimum interval between two [`Progress`] reports of the same fetch.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
 
 /// [`Storage`]-specific [`fetch::Fetcher`] impl.
 pub struct Fetcher<'a> {
@@ -51,6 +66,21 @@ pub struct Fetcher<'a> {
     inner: imp::Fetcher<'a>,
 }
 
+impl Fetcher<'_> {
+    /// Report the [`Progress`] of subsequent fetches to `f`.
+    ///
+    /// Reports are rate-limited, but the final report of each fetch is always
+    /// delivered. The callback is retained when the fetcher is
+    /// [`Redirect`]ed.
+    pub fn on_progress<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Progress) + Send + Sync + 'static,
+    {
+        self.inner.on_progress(Arc::new(f));
+        self
+    }
+}
+
 impl Drop for Fetcher<'_> {
     fn drop(&mut self) {
         self.reg.0.remove(&self.inner.info().urn);
@@ -82,6 +112,60 @@ impl<'a> fetch::Fetcher for Fetcher<'a> {
     }
 }
 
+/// A [`fetch::Fetcher`] which fetches into a different [`Storage`] than the
+/// one it was created for.
+///
+/// See [`Redirect`].
+pub struct Redirected<'a>(imp::Fetcher<'a>);
+
+impl<'a> fetch::Fetcher for Redirected<'a> {
+    type Error = <imp::Fetcher<'a> as fetch::Fetcher>::Error;
+    type PeerId = <imp::Fetcher<'a> as fetch::Fetcher>::PeerId;
+    type UrnId = <imp::Fetcher<'a> as fetch::Fetcher>::UrnId;
+
+    fn urn(&self) -> &identities::Urn<Self::UrnId> {
+        self.0.urn()
+    }
+
+    fn remote_peer(&self) -> &Self::PeerId {
+        self.0.remote_peer()
+    }
+
+    fn remote_heads(&self) -> &fetch::RemoteHeads {
+        self.0.remote_heads()
+    }
+
+    fn fetch(
+        &mut self,
+        specs: fetch::Fetchspecs<Self::PeerId, Self::UrnId>,
+    ) -> Result<fetch::FetchResult, Self::Error> {
+        self.0.fetch(specs)
+    }
+}
+
+/// Fetchers which can be redirected to fetch into a different [`Storage`],
+/// such as a [`super::Quarantine`].
+///
+/// The redirected fetcher reports the same remote heads as the original one.
+/// Note that it is not registered with [`Fetchers`]: the original fetcher
+/// should be kept alive for at least as long as the redirected one, so
+/// concurrent fetches of the same [`Urn`] are still prevented.
+pub trait Redirect {
+    fn redirect<'b>(&self, storage: &'b Storage) -> Result<Redirected<'b>, git2::Error>;
+}
+
+impl Redirect for Fetcher<'_> {
+    fn redirect<'b>(&self, storage: &'b Storage) -> Result<Redirected<'b>, git2::Error> {
+        self.inner.redirect(storage).map(Redirected)
+    }
+}
+
+impl Redirect for Redirected<'_> {
+    fn redirect<'b>(&self, storage: &'b Storage) -> Result<Redirected<'b>, git2::Error> {
+        self.0.redirect(storage).map(Redirected)
+    }
+}
+
 /// Types which can create a [`Fetcher`].
 pub trait BuildFetcher {
     type Error: std::error::Error + Send + Sync + 'static;
@@ -141,6 +225,8 @@ impl PeerToPeer {
             repo: &self.urn.id,
             addr_hints: &self.addr_hints,
             nonce: nonce.as_ref(),
+            partial: Default::default(),
+            shallow: &[],
         };
         AnyUrl {
             urn: self.urn.clone(),
@@ -274,6 +360,9 @@ pub mod error {
         },
         #[error(transparent)]
         Git(#[from] git2::Error),
+
+        #[error(transparent)]
+        Shallow(#[from] shallow::Error),
     }
 }
 
@@ -402,7 +491,9 @@ mod imp {
 
     pub struct Fetcher<'a> {
         info: Info,
+        repo: &'a git2::Repository,
         remote: git2::Remote<'a>,
+        progress: Option<OnProgress>,
     }
 
     impl<'a> Fetcher<'a> {
@@ -441,13 +532,84 @@ mod imp {
                 remote_heads,
             };
 
-            Ok(Self { info, remote })
+            Ok(Self {
+                info,
+                repo: storage.as_raw(),
+                remote,
+                progress: None,
+            })
         }
 
         pub fn info(&self) -> &Info {
             &self.info
         }
 
+        pub fn on_progress(&mut self, f: OnProgress) {
+            self.progress = Some(f)
+        }
+
+        pub fn redirect<'b>(&self, storage: &'b Storage) -> Result<Fetcher<'b>, git2::Error> {
+            let url = self
+                .remote
+                .url()
+                .ok_or_else(|| git2::Error::from_str("remote url is not valid UTF-8"))?;
+            let remote = storage.as_raw().remote_anonymous(url)?;
+            let info = Info {
+                urn: self.info.urn.clone(),
+                remote_peer: self.info.remote_peer,
+                remote_heads: self.info.remote_heads.clone(),
+            };
+
+            Ok(Fetcher {
+                info,
+                repo: storage.as_raw(),
+                remote,
+                progress: self.progress.clone(),
+            })
+        }
+
+        /// If `fetchspecs` requires a shallow or partial fetch, create a
+        /// remote which requests it.
+        ///
+        /// This is only supported by the peer-to-peer transport, other remotes
+        /// fall back to fetching the full history.
+        fn partial_remote(
+            &self,
+            fetchspecs: &Fetchspecs<PeerId, Revision>,
+        ) -> Result<Option<git2::Remote<'a>>, error::FetchError> {
+            let partial = match fetchspecs {
+                Fetchspecs::Replicate { partial, .. } => *partial,
+                _ => return Ok(None),
+            };
+            let shallow = shallow::read(self.repo)?;
+            if partial.is_full() && shallow.is_empty() {
+                return Ok(None);
+            }
+
+            let url = self
+                .remote
+                .url()
+                .ok_or_else(|| git2::Error::from_str("remote url is not valid UTF-8"))?;
+            match url.parse::<GitUrl<Revision>>() {
+                Ok(url) => {
+                    let url = GitUrl {
+                        partial,
+                        shallow: shallow.into_iter().collect(),
+                        ..url
+                    };
+                    Ok(Some(self.repo.remote_anonymous(&url.to_string())?))
+                },
+                Err(e) => {
+                    tracing::warn!(
+                        err = %e,
+                        ?partial,
+                        "partial fetch not supported, fetching full history"
+                    );
+                    Ok(None)
+                },
+            }
+        }
+
         #[tracing::instrument(skip(self))]
         pub fn fetch(
             &mut self,
@@ -467,11 +629,39 @@ mod imp {
                     .collect::<Vec<_>>();
                 tracing::trace!("{:?}", refspecs);
 
+                let mut partial_remote = self.partial_remote(&fetchspecs)?;
+                let remote = match partial_remote.as_mut() {
+                    Some(remote) => remote,
+                    None => &mut self.remote,
+                };
+
+                let phase = fetchspecs.phase();
+                let on_progress = self.progress.as_ref();
+                let mut last_progress: Option<Instant> = None;
+                let mut reported_done = false;
+
                 let mut callbacks = git2::RemoteCallbacks::new();
                 let mut excessive_transfer_bytes: Option<usize> = None;
                 callbacks.transfer_progress(|prog| {
                     let received_bytes = prog.received_bytes();
                     tracing::trace!("Fetch: received {} bytes", received_bytes);
+                    if let (Some(on_progress), false) = (on_progress, reported_done) {
+                        let progress = Progress {
+                            phase,
+                            total_objects: prog.total_objects(),
+                            received_objects: prog.received_objects(),
+                            indexed_objects: prog.indexed_objects(),
+                            received_bytes,
+                        };
+                        let due = last_progress
+                            .map(|last| last.elapsed() >= PROGRESS_INTERVAL)
+                            .unwrap_or(true);
+                        if due || progress.is_done() {
+                            on_progress(progress);
+                            last_progress = Some(Instant::now());
+                            reported_done = progress.is_done();
+                        }
+                    }
                     if received_bytes > limit {
                         tracing::error!("Fetch: exceeded {} bytes", limit);
                         excessive_transfer_bytes = Some(received_bytes);
@@ -499,7 +689,7 @@ mod imp {
                     true
                 });
 
-                let res = self.remote.fetch(
+                let res = remote.fetch(
                     &refspecs,
                     Some(
                         git2::FetchOptions::new()
@@ -522,6 +712,19 @@ mod imp {
                 } else {
                     res.map_err(|e| e.into())
                 }?;
+
+                // If there was nothing to fetch, the transport may not have reported
+                // any progress at all
+                if let (Some(on_progress), false) = (on_progress, reported_done) {
+                    let stats = remote.stats();
+                    on_progress(Progress {
+                        phase,
+                        total_objects: stats.total_objects(),
+                        received_objects: stats.received_objects(),
+                        indexed_objects: stats.indexed_objects(),
+                        received_bytes: stats.received_bytes(),
+                    });
+                }
             }
 
             Ok(FetchResult { updated_tips })
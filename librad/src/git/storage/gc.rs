@@ -0,0 +1,354 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Garbage collection of the monorepo.
+//!
+//! Objects are only ever added to the monorepo: after a
+//! [`crate::git::tracking::untrack`], or when replication fails midway, the
+//! objects which were fetched stay around. [`collect`] reclaims the space taken
+//! up by objects which are not reachable from any ref in any namespace (ie.
+//! `refs/namespaces/*`), and optionally removes namespaces which are not rooted
+//! in an identity. It also removes [`super::Quarantine`]s left behind by a
+//! process which crashed while replicating.
+//!
+//! # Concurrency
+//!
+//! It is safe to run [`collect`] while other [`Storage`]s (eg. from a
+//! [`super::Pool`]) are in use, provided they share the same
+//! [`super::Fetchers`]:
+//!
+//! * Namespaces for which a fetch is in-flight are never pruned.
+//! * Objects which are not (yet) reachable are only removed once they are older
+//!   than [`Options::expiry`]. This protects objects written by concurrent
+//!   fetches or commits before the refs pointing to them are updated.
+//! * Likewise, quarantines are only removed once nothing in them was modified
+//!   for [`Options::expiry`].
+//!
+//! Only one collection can run at a time, concurrent attempts fail with
+//! [`Error::Gc`].
+//!
+//! # Partial namespaces
+//!
+//! Objects are not collected while any namespace was fetched with a
+//! [`crate::git::fetch::Filter`]: `git gc` would fail on the missing objects.
+//! Shallow namespaces, on the other hand, are supported (see
+//! [`super::shallow`]).
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fs,
+    io,
+    path::Path,
+    process::{Command, ExitStatus, Stdio},
+    time::{Duration, SystemTime},
+};
+
+use git_ext as ext;
+use thiserror::Error;
+
+use super::{quarantine, Storage};
+use crate::{git::types::Namespace, identities::git::Urn};
+
+/// The default [`Options::expiry`], two weeks.
+pub const DEFAULT_EXPIRY: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("`git gc` failed with {status}: {stderr}")]
+    Gc { status: ExitStatus, stderr: String },
+
+    #[error(transparent)]
+    Store(#[from] super::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    /// Grace period after which unreachable objects, empty namespaces and
+    /// abandoned quarantines are removed.
+    ///
+    /// This should be considerably longer than a fetch can take.
+    pub expiry: Duration,
+    /// Whether to remove namespaces which don't have a `rad/id`, such as those
+    /// left behind by failed replication.
+    pub prune_namespaces: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            expiry: DEFAULT_EXPIRY,
+            prune_namespaces: true,
+        }
+    }
+}
+
+/// The outcome of a [`collect`] run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Namespaces which were removed because they didn't have a `rad/id`.
+    pub pruned_namespaces: BTreeSet<Urn>,
+    /// The number of abandoned quarantines which were removed.
+    pub pruned_quarantines: usize,
+    /// Size of the object database (in bytes) before the collection.
+    pub size_before: u64,
+    /// Size of the object database (in bytes) after the collection.
+    pub size_after: u64,
+    /// Whether unreachable objects were collected, see the [module
+    /// documentation][self] for when they are not.
+    pub objects_collected: bool,
+}
+
+impl Report {
+    /// The number of bytes reclaimed by the collection.
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
+
+/// Prune unreachable objects and namespaces from the monorepo.
+///
+/// This requires the `git` executable to be on the `PATH`.
+#[tracing::instrument(level = "debug", skip(storage))]
+pub fn collect(storage: &Storage, opts: Options) -> Result<Report, Error> {
+    let objects = storage.path().join("objects");
+    let size_before = disk_usage(&objects)?;
+
+    let pruned_namespaces = if opts.prune_namespaces {
+        prune_namespaces(storage)?
+    } else {
+        BTreeSet::new()
+    };
+    let cutoff = SystemTime::now()
+        .checked_sub(opts.expiry)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    remove_empty_dirs(&storage.path().join("refs/namespaces"), cutoff)?;
+    let pruned_quarantines = remove_stale_quarantines(storage.path(), cutoff)?;
+
+    let objects_collected = if has_filtered_namespaces(storage)? {
+        tracing::warn!("not collecting objects, as some namespaces are filtered");
+        false
+    } else {
+        gc(storage.path(), opts.expiry)?;
+        true
+    };
+
+    let report = Report {
+        pruned_namespaces,
+        pruned_quarantines,
+        size_before,
+        size_after: disk_usage(&objects)?,
+        objects_collected,
+    };
+    tracing::info!(
+        pruned = report.pruned_namespaces.len(),
+        quarantines = report.pruned_quarantines,
+        reclaimed = report.reclaimed_bytes(),
+        "garbage collection completed"
+    );
+
+    Ok(report)
+}
+
+/// Remove all refs of namespaces which don't have a `rad/id`.
+fn prune_namespaces(storage: &Storage) -> Result<BTreeSet<Urn>, Error> {
+    let mut namespaces: BTreeMap<Urn, Vec<ext::RefLike>> = BTreeMap::new();
+    for name in storage
+        .as_raw()
+        .references_glob("refs/namespaces/*")?
+        .names()
+    {
+        let name = name?;
+        let parsed = ext::RefLike::try_from(name)
+            .ok()
+            .and_then(|refl| Urn::try_from(refl.clone()).ok().map(|urn| (urn, refl)));
+        match parsed {
+            Some((urn, refl)) => namespaces.entry(Urn::new(urn.id)).or_default().push(refl),
+            None => tracing::warn!(name = %name, "skipping unparseable namespace ref"),
+        }
+    }
+
+    let mut pruned = BTreeSet::new();
+    for (urn, refs) in namespaces {
+        let rad_id = reflike!("refs/namespaces")
+            .join(Namespace::from(&urn))
+            .join(reflike!("refs/rad/id"));
+
+        // Lock `rad/id` first, so it can't be created from under us
+        let mut tx = storage.transaction()?;
+        if tx.exists(&rad_id)? || storage.fetchers().is_inflight(&urn) {
+            continue;
+        }
+        for name in &refs {
+            tx.remove(name)?;
+        }
+        tx.commit()?;
+
+        tracing::debug!(urn = %urn, "pruned namespace");
+        pruned.insert(urn);
+    }
+
+    Ok(pruned)
+}
+
+/// Determine if any namespace was fetched with a filter, ie. objects may be
+/// missing.
+fn has_filtered_namespaces(storage: &Storage) -> Result<bool, Error> {
+    let config = git2::Config::open(&storage.config_path())?;
+    let mut entries = config.entries(Some(r"^urn\..*\.filter$"))?;
+    let filtered = entries.next().transpose()?.is_some();
+    Ok(filtered)
+}
+
+/// Recursively remove empty directories below (but not including) `dir`,
+/// which were last modified before `cutoff`.
+fn remove_empty_dirs(dir: &Path, cutoff: SystemTime) -> io::Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        x => x?,
+    };
+    for entry in entries {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let path = entry.path();
+        remove_empty_dirs(&path, cutoff)?;
+
+        let is_empty = path.read_dir()?.next().is_none();
+        if is_empty && entry.metadata()?.modified()? <= cutoff {
+            if let Err(e) = fs::remove_dir(&path) {
+                // A concurrent writer may have populated (or removed) it in the
+                // meantime
+                if path
+                    .read_dir()
+                    .map(|mut d| d.next().is_none())
+                    .unwrap_or(false)
+                {
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the directories of [`super::Quarantine`]s in `git_dir` which were
+/// last modified before `cutoff`, returning how many were removed.
+///
+/// A quarantine is normally removed when it is dropped, so these are left
+/// behind by a process which crashed.
+fn remove_stale_quarantines(git_dir: &Path, cutoff: SystemTime) -> io::Result<usize> {
+    let mut removed = 0;
+    for entry in fs::read_dir(git_dir)? {
+        let entry = entry?;
+        let is_quarantine = entry
+            .file_name()
+            .to_str()
+            .map(|name| name.starts_with(quarantine::PREFIX))
+            .unwrap_or(false);
+        if !is_quarantine || !entry.file_type()?.is_dir() {
+            continue;
+        }
+
+        let path = entry.path();
+        let modified = match last_modified(&path) {
+            // Dropped concurrently
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            x => x?,
+        };
+        if modified > cutoff {
+            continue;
+        }
+        match fs::remove_dir_all(&path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            x => x?,
+        }
+        tracing::debug!(path = %path.display(), "removed stale quarantine");
+        removed += 1;
+    }
+
+    Ok(removed)
+}
+
+/// The most recent modification time of `path`, or of anything below it.
+fn last_modified(path: &Path) -> io::Result<SystemTime> {
+    let meta = fs::symlink_metadata(path)?;
+    let mut modified = meta.modified()?;
+    if meta.is_dir() {
+        for entry in fs::read_dir(path)? {
+            match last_modified(&entry?.path()) {
+                // Removed concurrently, eg. a lock file
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                x => modified = modified.max(x?),
+            }
+        }
+    }
+
+    Ok(modified)
+}
+
+/// Run `git gc`, which considers all refs, including those of all namespaces,
+/// when computing reachability.
+fn gc(git_dir: &Path, expiry: Duration) -> Result<(), Error> {
+    let prune = if expiry.as_secs() == 0 {
+        "now".to_owned()
+    } else {
+        format!("{}.seconds.ago", expiry.as_secs())
+    };
+
+    let out = Command::new("git")
+        .envs(std::env::vars().filter(|(key, _)| key.starts_with("GIT_TRACE")))
+        .arg("--git-dir")
+        .arg(git_dir)
+        .args(&["gc", "--quiet"])
+        .arg(format!("--prune={}", prune))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()?;
+
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(Error::Gc {
+            status: out.status,
+            stderr: String::from_utf8_lossy(&out.stderr).into_owned(),
+        })
+    }
+}
+
+/// The combined size of all files below `dir`.
+fn disk_usage(dir: &Path) -> io::Result<u64> {
+    let mut size = 0;
+    let entries = match fs::read_dir(dir) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        x => x?,
+    };
+    for entry in entries {
+        let entry = entry?;
+        let meta = match entry.metadata() {
+            // Removed concurrently
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            x => x?,
+        };
+        if meta.is_dir() {
+            size += disk_usage(&entry.path())?;
+        } else {
+            size += meta.len();
+        }
+    }
+
+    Ok(size)
+}
@@ -0,0 +1,458 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! A temporary [`Storage`] to stage updates in, before applying them to the
+//! monorepo.
+//!
+//! A [`Quarantine`] is a separate repository, which has the monorepo's object
+//! database configured as an alternate. Objects written to the quarantine
+//! (eg. by fetching into it) are thus not visible to the monorepo, while all
+//! objects of the monorepo are visible to the quarantine.
+//!
+//! When created, the refs of the namespace in question (and the namespaces it
+//! links to via `rad/ids/*`), as well as the storage config and the
+//! [`shallow`] commits, are copied into the quarantine. Other namespaces are
+//! not copied, so refs created in them may already exist in the monorepo --
+//! [`Quarantine::migrate`] reconciles those if their histories agree. The
+//! [`Changes`] made to the refs after that can be inspected via
+//! [`Quarantine::changes`].
+//!
+//! Once the quarantined updates are deemed acceptable, they can be applied to
+//! the monorepo using [`Quarantine::migrate`]. Otherwise, the quarantine is
+//! simply dropped, which removes it from disk.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fs,
+    io,
+    path::Path,
+};
+
+use git_ext as ext;
+use tempfile::TempDir;
+use thiserror::Error;
+
+use super::{read, shallow, ReadOnly, Storage};
+use crate::identities::git::Urn;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("{name} was modified concurrently")]
+    Concurrent { name: ext::RefLike },
+
+    #[error(transparent)]
+    Store(#[from] read::Error),
+
+    #[error(transparent)]
+    Name(#[from] ext::reference::name::Error),
+
+    #[error(transparent)]
+    Shallow(#[from] shallow::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+/// The prefix of the directory names of [`Quarantine`]s.
+pub(super) const PREFIX: &str = "quarantine-";
+
+/// The target of a ref.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    Direct(ext::Oid),
+    Symbolic(ext::RefLike),
+}
+
+impl Target {
+    fn of(reference: &git2::Reference) -> Result<Option<Self>, Error> {
+        if let Some(target) = reference.symbolic_target() {
+            Ok(Some(Self::Symbolic(ext::RefLike::try_from(target)?)))
+        } else {
+            Ok(reference.target().map(|oid| Self::Direct(oid.into())))
+        }
+    }
+}
+
+/// A change to a ref.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    Created { new: Target },
+    Updated { old: Target, new: Target },
+    Removed { old: Target },
+}
+
+/// The changes made to the refs of a [`Quarantine`] since it was created.
+pub type Changes = BTreeMap<ext::RefLike, Change>;
+
+/// A temporary [`Storage`] to stage updates in.
+///
+/// See the [module documentation][self] for details.
+pub struct Quarantine {
+    // NOTE: the storage must be dropped before the directory is removed
+    storage: Storage,
+    snapshot: BTreeMap<ext::RefLike, Target>,
+    namespaces: BTreeSet<String>,
+    config: BTreeMap<String, BTreeSet<String>>,
+    dir: TempDir,
+}
+
+impl Quarantine {
+    /// Create a new [`Quarantine`] for `urn` in the git directory of
+    /// `storage`.
+    #[tracing::instrument(skip(storage))]
+    pub fn new(storage: &Storage, urn: &Urn) -> Result<Self, Error> {
+        let dir = tempfile::Builder::new()
+            .prefix(PREFIX)
+            .tempdir_in(storage.path())?;
+
+        git2::Repository::init_opts(
+            dir.path(),
+            git2::RepositoryInitOptions::new()
+                .bare(true)
+                .no_reinit(true)
+                .external_template(false),
+        )?;
+        fs::copy(
+            super::config::path(storage.as_raw()),
+            dir.path().join("config"),
+        )?;
+        fs::write(
+            dir.path().join("objects/info/alternates"),
+            format!(
+                "{}\n",
+                storage.path().join("objects").canonicalize()?.display()
+            ),
+        )?;
+
+        let backend = git2::Repository::open_bare(dir.path())?;
+        let quarantine = Storage {
+            inner: ReadOnly {
+                backend,
+                peer_id: *storage.peer_id(),
+            },
+            signer: storage.signer().clone(),
+            fetchers: storage.fetchers().clone(),
+        };
+        shallow::migrate(storage.as_raw(), quarantine.as_raw())?;
+        let (snapshot, namespaces) = copy_refs(storage, &quarantine, urn)?;
+        let config = config_entries(&super::config::path(quarantine.as_raw()))?;
+
+        Ok(Self {
+            storage: quarantine,
+            snapshot,
+            namespaces,
+            config,
+            dir,
+        })
+    }
+
+    /// The quarantined [`Storage`].
+    pub fn storage(&self) -> &Storage {
+        &self.storage
+    }
+
+    /// Compute the [`Changes`] made to the refs of the quarantine since it was
+    /// created.
+    pub fn changes(&self) -> Result<Changes, Error> {
+        let mut current = BTreeMap::new();
+        for reference in self.storage.as_raw().references()? {
+            let reference = reference?;
+            if let (Some(name), Some(target)) = (reference.name(), Target::of(&reference)?) {
+                current.insert(ext::RefLike::try_from(name)?, target);
+            }
+        }
+
+        let mut changes = Changes::new();
+        for (name, old) in &self.snapshot {
+            match current.remove(name) {
+                None => {
+                    changes.insert(name.clone(), Change::Removed { old: old.clone() });
+                },
+                Some(new) if &new != old => {
+                    changes.insert(
+                        name.clone(),
+                        Change::Updated {
+                            old: old.clone(),
+                            new,
+                        },
+                    );
+                },
+                Some(_) => {},
+            }
+        }
+        for (name, new) in current {
+            changes.insert(name, Change::Created { new });
+        }
+
+        Ok(changes)
+    }
+
+    /// Apply the updates made to the quarantine to `storage`, which must be
+    /// the [`Storage`] the quarantine was created from.
+    ///
+    /// First, all objects are moved into the object database of `storage`,
+    /// and the [`shallow`] commits are updated accordingly. Then, the
+    /// [`Changes`] to the refs are applied in a single
+    /// [`super::Transaction`]. Changes whose outcome is already present in
+    /// `storage` are skipped. A ref created in a namespace which was not
+    /// copied into the quarantine, but which exists in `storage`, is
+    /// fast-forwarded, or skipped if `storage` is already ahead of it. If any
+    /// other changed ref was modified in `storage` since the quarantine was
+    /// created, no ref is updated and [`Error::Concurrent`] is returned.
+    /// Finally, the changes to the config are applied.
+    ///
+    /// The migrated [`Changes`] are returned.
+    #[tracing::instrument(level = "debug", skip(self, storage))]
+    pub fn migrate(self, storage: &Storage) -> Result<Changes, Error> {
+        move_objects(
+            &self.dir.path().join("objects"),
+            &storage.path().join("objects"),
+        )?;
+        shallow::migrate(self.storage.as_raw(), storage.as_raw())?;
+
+        let changes = self.changes()?;
+        let repo = storage.as_raw();
+        let mut tx = storage.transaction()?;
+        for (name, change) in &changes {
+            tx.lock(name)?;
+            let current = match repo.find_reference(name.as_str()) {
+                Ok(reference) => Target::of(&reference)?,
+                Err(e) if ext::is_not_found_err(&e) => None,
+                Err(e) => return Err(e.into()),
+            };
+            let (expected, new) = match change {
+                Change::Created { new } => (None, Some(new)),
+                Change::Updated { old, new } => (Some(old), Some(new)),
+                Change::Removed { old } => (Some(old), None),
+            };
+            // Already applied, eg. by a concurrent replication of the same
+            // identity
+            if current.as_ref() == new {
+                continue;
+            }
+            if current.as_ref() != expected {
+                match (current, new) {
+                    (Some(Target::Direct(cur)), Some(Target::Direct(new)))
+                        if expected.is_none() && !self.is_copied(name) =>
+                    {
+                        // Our view of the namespace is outdated
+                        if repo.graph_descendant_of(cur.into(), (*new).into())? {
+                            continue;
+                        }
+                        if !repo.graph_descendant_of((*new).into(), cur.into())? {
+                            return Err(Error::Concurrent { name: name.clone() });
+                        }
+                    }
+                    _ => return Err(Error::Concurrent { name: name.clone() }),
+                }
+            }
+            match new {
+                Some(Target::Direct(oid)) => tx.set_target(name, (*oid).into(), "migrate")?,
+                Some(Target::Symbolic(target)) => {
+                    tx.set_symbolic_target(name, target, "migrate")?
+                },
+                None => {
+                    tx.remove(name)?;
+                },
+            }
+        }
+        tx.commit()?;
+
+        migrate_config(
+            &self.config,
+            &config_entries(&super::config::path(self.storage.as_raw()))?,
+            &super::config::path(repo),
+        )?;
+
+        Ok(changes)
+    }
+
+    /// Whether the namespace of `name` was copied into the quarantine when it
+    /// was created.
+    fn is_copied(&self, name: &ext::RefLike) -> bool {
+        name.as_str()
+            .strip_prefix("refs/namespaces/")
+            .and_then(|suffix| suffix.split('/').next())
+            .map(|ns| self.namespaces.contains(ns))
+            .unwrap_or(true)
+    }
+}
+
+impl AsRef<Storage> for Quarantine {
+    fn as_ref(&self) -> &Storage {
+        &self.storage
+    }
+}
+
+impl AsRef<ReadOnly> for Quarantine {
+    fn as_ref(&self) -> &ReadOnly {
+        self.storage.read_only()
+    }
+}
+
+/// Copy the refs of the namespace `urn` and of the namespaces it links to from
+/// `from` into `to`, returning the refs and namespaces which were copied.
+fn copy_refs(
+    from: &Storage,
+    to: &Storage,
+    urn: &Urn,
+) -> Result<(BTreeMap<ext::RefLike, Target>, BTreeSet<String>), Error> {
+    let mut namespaces = BTreeSet::new();
+    namespaces.insert(urn.encode_id());
+
+    let mut copied = BTreeMap::new();
+    let mut visited = BTreeSet::new();
+    while let Some(ns) = namespaces.iter().find(|ns| !visited.contains(*ns)).cloned() {
+        visited.insert(ns.clone());
+        for reference in from
+            .as_raw()
+            .references_glob(&format!("refs/namespaces/{}/*", ns))?
+        {
+            let reference = reference?;
+            let (name, target) = match (reference.name(), Target::of(&reference)?) {
+                (Some(name), Some(target)) => (ext::RefLike::try_from(name)?, target),
+                _ => continue,
+            };
+
+            // Only follow links one level deep
+            if ns == urn.encode_id() {
+                if let Some(linked) = linked_namespace(&name, &target) {
+                    namespaces.insert(linked);
+                }
+            }
+            copied.insert(name, target);
+        }
+    }
+
+    let mut tx = to.transaction()?;
+    for (name, target) in &copied {
+        match target {
+            Target::Direct(oid) => tx.set_target(name, (*oid).into(), "quarantine")?,
+            Target::Symbolic(target) => {
+                tx.create_symbolic(name, target, "quarantine")?;
+            },
+        }
+    }
+    tx.commit()?;
+
+    Ok((copied, visited))
+}
+
+/// The namespace linked to by a `rad/ids/*` or `rad/self` ref, if any.
+fn linked_namespace(name: &ext::RefLike, target: &Target) -> Option<String> {
+    match target {
+        Target::Symbolic(target) => target
+            .as_str()
+            .strip_prefix("refs/namespaces/")
+            .and_then(|suffix| suffix.split('/').next())
+            .map(ToOwned::to_owned),
+        Target::Direct(_) => {
+            let mut components = name.as_str().rsplit('/');
+            let id = components.next()?;
+            match (components.next(), components.next()) {
+                (Some("ids"), Some("rad")) => Some(id.to_owned()),
+                _ => None,
+            }
+        },
+    }
+}
+
+/// Move all objects (loose and packed) from `from` to `to`.
+///
+/// Pack indices are moved after the packs they index, so a concurrent reader
+/// never sees a partial pack.
+fn move_objects(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.len() != 2 || !name.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+
+        let dir = to.join(&*name);
+        fs::create_dir_all(&dir)?;
+        for object in fs::read_dir(entry.path())? {
+            let object = object?;
+            let dest = dir.join(object.file_name());
+            if !dest.exists() {
+                fs::rename(object.path(), dest)?;
+            }
+        }
+    }
+
+    let mut indices = Vec::new();
+    for entry in fs::read_dir(from.join("pack"))? {
+        let entry = entry?;
+        let dest = to.join("pack").join(entry.file_name());
+        match entry.path().extension().and_then(|ext| ext.to_str()) {
+            Some("idx") => indices.push((entry.path(), dest)),
+            Some("pack") | Some("rev") if !dest.exists() => fs::rename(entry.path(), dest)?,
+            _ => {},
+        }
+    }
+    for (path, dest) in indices {
+        if !dest.exists() {
+            fs::rename(path, dest)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The entries of the config file at `path`, by name.
+fn config_entries(path: &Path) -> Result<BTreeMap<String, BTreeSet<String>>, Error> {
+    let config = git2::Config::open(path)?.snapshot()?;
+    let mut entries: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut iter = config.entries(None)?;
+    while let Some(entry) = iter.next() {
+        let entry = entry?;
+        if let (Some(name), Some(value)) = (entry.name(), entry.value()) {
+            entries
+                .entry(name.to_owned())
+                .or_default()
+                .insert(value.to_owned());
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Apply the difference between the config entries `before` and `after` to
+/// the config file at `path`.
+fn migrate_config(
+    before: &BTreeMap<String, BTreeSet<String>>,
+    after: &BTreeMap<String, BTreeSet<String>>,
+    path: &Path,
+) -> Result<(), Error> {
+    let mut config = git2::Config::open(path)?;
+    let empty = BTreeSet::new();
+    let names = before.keys().chain(after.keys()).collect::<BTreeSet<_>>();
+    for name in names {
+        let before = before.get(name).unwrap_or(&empty);
+        let after = after.get(name).unwrap_or(&empty);
+        for value in before.difference(after) {
+            match config.remove_multivar(name, &exact(value)) {
+                Err(e) if ext::is_not_found_err(&e) => {},
+                x => x?,
+            }
+        }
+        for value in after.difference(before) {
+            config.set_multivar(name, &exact(value), value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A regex matching exactly `value`.
+fn exact(value: &str) -> String {
+    format!("^{}$", regex::escape(value))
+}
@@ -0,0 +1,256 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! The commits of the monorepo whose parents are not present.
+//!
+//! Like in a shallow git clone, the history fetched with a limited
+//! [`crate::git::fetch::Partial::depth`] ends at commits whose parents were not
+//! fetched. These commits are recorded in `$GIT_DIR/shallow`, so that `git`
+//! (eg. when running [`super::gc::collect`]) treats them as roots, and so that
+//! subsequent fetches can tell the remote end where the local history ends.
+//!
+//! The file is shared by all namespaces, and is kept up-to-date by
+//! [`crate::git::replication::replicate`]: commits are removed from it once
+//! their parents have been fetched.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    io::{self, Write as _},
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use git_ext::{self as ext, is_not_found_err};
+use thiserror::Error;
+
+use super::Storage;
+use crate::{git::types::Namespace, identities::git::Urn};
+
+/// How long to wait for a concurrent writer to release the lock on the
+/// `shallow` file.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("timed out waiting for the lock on {0}")]
+    Locked(PathBuf),
+
+    #[error("malformed shallow file {path}")]
+    Malformed {
+        path: PathBuf,
+        #[source]
+        source: git2::Error,
+    },
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+/// The location of the `shallow` file of `repo`.
+pub fn path(repo: &git2::Repository) -> PathBuf {
+    repo.path().join("shallow")
+}
+
+/// Read the shallow commits of `repo`.
+///
+/// The result is empty if `repo` is not shallow.
+pub fn read(repo: &git2::Repository) -> Result<BTreeSet<ext::Oid>, Error> {
+    let path = path(repo);
+    let contents = match fs::read_to_string(&path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(BTreeSet::new()),
+        x => x?,
+    };
+    contents
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse().map_err(|source| Error::Malformed {
+                path: path.clone(),
+                source,
+            })
+        })
+        .collect()
+}
+
+/// Update the shallow commits after `urn` was fetched.
+///
+/// If `depth` is given, the commits at most `depth` levels from the tips of
+/// `urn` which are missing parents are added. Commits whose parents are now
+/// present (because they were deepened) are removed.
+pub fn update(storage: &Storage, urn: &Urn, depth: Option<NonZeroU32>) -> Result<(), Error> {
+    let repo = storage.as_raw();
+    modify(repo, |shallow| {
+        if let Some(depth) = depth {
+            let tips = tips(repo, urn)?;
+            shallow.append(&mut boundary(repo, tips, depth)?);
+        }
+        Ok(())
+    })
+}
+
+/// Add the shallow commits of `from` to `to`, which is assumed to contain the
+/// objects of `from`.
+pub(super) fn migrate(from: &git2::Repository, to: &git2::Repository) -> Result<(), Error> {
+    let mut theirs = read(from)?;
+    if theirs.is_empty() && !path(to).exists() {
+        return Ok(());
+    }
+    modify(to, |shallow| {
+        shallow.append(&mut theirs);
+        Ok(())
+    })
+}
+
+/// Modify the shallow commits of `repo` while holding its lock.
+///
+/// Afterwards, all commits which are either not present, or have all their
+/// parents present, are removed.
+fn modify<F>(repo: &git2::Repository, f: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut BTreeSet<ext::Oid>) -> Result<(), Error>,
+{
+    let path = path(repo);
+    let lock = Lock::acquire(&path)?;
+
+    let mut shallow = read(repo)?;
+    f(&mut shallow)?;
+
+    let odb = repo.odb()?;
+    let mut retained = BTreeSet::new();
+    for oid in shallow {
+        if is_shallow(repo, &odb, *oid)? {
+            retained.insert(oid);
+        }
+    }
+
+    lock.commit(&retained)
+}
+
+/// The commits the refs of `urn` point to.
+fn tips(repo: &git2::Repository, urn: &Urn) -> Result<Vec<git2::Oid>, Error> {
+    let namespace = reflike!("refs/namespaces").join(Namespace::from(urn));
+    let mut tips = Vec::new();
+    for reference in repo.references_glob(&format!("{}/refs/*", namespace))? {
+        match reference?.peel_to_commit() {
+            Ok(commit) => tips.push(commit.id()),
+            Err(e) => tracing::trace!(err = %e, "skipping non-commit ref"),
+        }
+    }
+
+    Ok(tips)
+}
+
+/// The commits at most `depth` levels from `tips` which are missing parents.
+fn boundary(
+    repo: &git2::Repository,
+    tips: Vec<git2::Oid>,
+    depth: NonZeroU32,
+) -> Result<BTreeSet<ext::Oid>, Error> {
+    let odb = repo.odb()?;
+    let mut seen = BTreeSet::new();
+    let mut boundary = BTreeSet::new();
+    let mut level = tips;
+    for _ in 0..depth.get() {
+        let mut next = Vec::new();
+        for oid in level {
+            if !seen.insert(oid) {
+                continue;
+            }
+            let commit = match repo.find_commit(oid) {
+                Err(e) if is_not_found_err(&e) => continue,
+                x => x?,
+            };
+            if commit.parent_ids().all(|parent| odb.exists(parent)) {
+                next.extend(commit.parent_ids());
+            } else {
+                boundary.insert(oid.into());
+            }
+        }
+        level = next;
+    }
+
+    Ok(boundary)
+}
+
+fn is_shallow(repo: &git2::Repository, odb: &git2::Odb, oid: git2::Oid) -> Result<bool, Error> {
+    match repo.find_commit(oid) {
+        Err(e) if is_not_found_err(&e) => Ok(false),
+        Err(e) => Err(e.into()),
+        Ok(commit) => Ok(commit.parent_ids().any(|parent| !odb.exists(parent))),
+    }
+}
+
+/// Lock on the `shallow` file, following the `git` convention of creating a
+/// `shallow.lock` file, which replaces the original when committed.
+struct Lock {
+    path: PathBuf,
+    lock: PathBuf,
+    file: Option<fs::File>,
+}
+
+impl Lock {
+    fn acquire(path: &Path) -> Result<Self, Error> {
+        let lock = path.with_extension("lock");
+        let start = Instant::now();
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock)
+            {
+                Ok(file) => {
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                        lock,
+                        file: Some(file),
+                    })
+                },
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    if start.elapsed() > LOCK_TIMEOUT {
+                        return Err(Error::Locked(path.to_path_buf()));
+                    }
+                    thread::sleep(Duration::from_millis(10))
+                },
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn commit(mut self, shallow: &BTreeSet<ext::Oid>) -> Result<(), Error> {
+        let mut file = self.file.take().expect("lock is only committed once. qed");
+        if shallow.is_empty() {
+            match fs::remove_file(&self.path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+                x => x?,
+            }
+            return Ok(());
+        }
+
+        for oid in shallow {
+            writeln!(file, "{}", oid)?;
+        }
+        file.sync_all()?;
+        fs::rename(&self.lock, &self.path)?;
+
+        Ok(())
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.lock) {
+            if e.kind() != io::ErrorKind::NotFound {
+                tracing::warn!(err = %e, "failed to remove {}", self.lock.display())
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::collections::BTreeSet;
+
+use git_ext as ext;
+
+/// A set of ref updates which are applied together.
+///
+/// All refs touched by the transaction are locked until it is either
+/// committed, or dropped (in which case none of the updates take effect).
+/// Refs which are only [`Transaction::lock`]ed are guaranteed to not be
+/// modified by other writers while the transaction is in progress.
+///
+/// Note that reads are not affected by the locks: a reader may observe the
+/// state before or after [`Transaction::commit`], but never a state where
+/// only some of the updates were applied, unless the process crashes in the
+/// middle of committing (in which case the lock files need to be removed by
+/// hand).
+pub struct Transaction<'a> {
+    repo: &'a git2::Repository,
+    inner: git2::Transaction<'a>,
+    locked: BTreeSet<ext::RefLike>,
+}
+
+impl<'a> Transaction<'a> {
+    pub(super) fn new(repo: &'a git2::Repository) -> Result<Self, git2::Error> {
+        Ok(Self {
+            repo,
+            inner: repo.transaction()?,
+            locked: BTreeSet::new(),
+        })
+    }
+
+    /// Lock the ref `name`.
+    ///
+    /// Locking a ref which is already locked by this transaction is a no-op.
+    pub fn lock(&mut self, name: &ext::RefLike) -> Result<(), git2::Error> {
+        if !self.locked.contains(name) {
+            self.inner.lock_ref(name.as_str())?;
+            self.locked.insert(name.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Determine if the ref `name` is locked by this transaction.
+    pub fn is_locked(&self, name: &ext::RefLike) -> bool {
+        self.locked.contains(name)
+    }
+
+    /// Determine if the ref `name` exists, locking it in the process.
+    pub fn exists(&mut self, name: &ext::RefLike) -> Result<bool, git2::Error> {
+        self.lock(name)?;
+        match self.repo.find_reference(name.as_str()) {
+            Ok(_) => Ok(true),
+            Err(e) if ext::is_not_found_err(&e) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Set the ref `name` to point to `target`, creating it if it doesn't
+    /// exist.
+    pub fn set_target(
+        &mut self,
+        name: &ext::RefLike,
+        target: git2::Oid,
+        msg: &str,
+    ) -> Result<(), git2::Error> {
+        self.lock(name)?;
+        self.repo.reference_ensure_log(name.as_str())?;
+        self.inner.set_target(name.as_str(), target, None, msg)
+    }
+
+    /// Create the ref `name` pointing to `target`, unless it already exists.
+    ///
+    /// `true` is returned if the ref will be created when the transaction is
+    /// committed.
+    pub fn create(
+        &mut self,
+        name: &ext::RefLike,
+        target: git2::Oid,
+        msg: &str,
+    ) -> Result<bool, git2::Error> {
+        if self.exists(name)? {
+            Ok(false)
+        } else {
+            self.set_target(name, target, msg).and(Ok(true))
+        }
+    }
+
+    /// Create the symbolic ref `name` pointing to `target`, unless it already
+    /// exists.
+    ///
+    /// `true` is returned if the ref will be created when the transaction is
+    /// committed.
+    pub fn create_symbolic(
+        &mut self,
+        name: &ext::RefLike,
+        target: &ext::RefLike,
+        msg: &str,
+    ) -> Result<bool, git2::Error> {
+        if self.exists(name)? {
+            Ok(false)
+        } else {
+            self.set_symbolic_target(name, target, msg).and(Ok(true))
+        }
+    }
+
+    /// Set the symbolic ref `name` to point to `target`, creating it if it
+    /// doesn't exist.
+    pub fn set_symbolic_target(
+        &mut self,
+        name: &ext::RefLike,
+        target: &ext::RefLike,
+        msg: &str,
+    ) -> Result<(), git2::Error> {
+        self.lock(name)?;
+        self.repo.reference_ensure_log(name.as_str())?;
+        self.inner
+            .set_symbolic_target(name.as_str(), target.as_str(), None, msg)
+    }
+
+    /// Remove the ref `name`, if it exists.
+    ///
+    /// `true` is returned if the ref will be removed when the transaction is
+    /// committed.
+    pub fn remove(&mut self, name: &ext::RefLike) -> Result<bool, git2::Error> {
+        if self.exists(name)? {
+            self.inner.remove(name.as_str()).and(Ok(true))
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Apply all updates.
+    pub fn commit(self) -> Result<(), git2::Error> {
+        self.inner.commit()
+    }
+}
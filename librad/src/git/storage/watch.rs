@@ -74,8 +74,9 @@ impl<'a> Watch<'a> {
     ///
     /// By default [`super::Config`] sets `core.logAllRefUpdates` to `true`
     /// (**not** "always"), and refs created by this library will have a
-    /// corresponding reflog created. It is currently unlikely that
-    /// [`EventKind`]s other than [`EventKind::Create`] will be emitted.
+    /// corresponding reflog created. [`EventKind::Remove`] is emitted when a
+    /// namespace is removed via [`Storage::remove_namespace`], other
+    /// [`EventKind`]s are currently unlikely to be emitted.
     pub fn namespaces(&self) -> Result<(Watcher, impl Iterator<Item = NamespaceEvent>), Error> {
         use notify::{Op, RawEvent, RecursiveMode::NonRecursive};
 
@@ -108,7 +109,7 @@ impl<'a> Watch<'a> {
                     path: Some(path),
                     op: Ok(op),
                     cookie: _,
-                } if path.is_dir() => {
+                } if path.is_dir() || op.contains(Op::REMOVE) => {
                     let path = path.strip_prefix(&reflogs_path).ok()?;
                     if is_namespace(path) {
                         let kind = if op.contains(Op::CREATE) {
@@ -15,6 +15,9 @@ use super::{
 };
 use crate::peer::PeerId;
 
+pub mod policy;
+pub use policy::Policy;
+
 pub use crate::identities::git::Urn;
 
 #[derive(Debug, Error)]
@@ -29,6 +32,9 @@ pub enum Error {
     #[error(transparent)]
     Config(#[from] storage::config::Error),
 
+    #[error(transparent)]
+    Policy(#[from] policy::Error),
+
     #[error(transparent)]
     Git(#[from] git2::Error),
 }
@@ -79,15 +85,31 @@ pub fn track(storage: &Storage, urn: &Urn, peer: PeerId) -> Result<bool, Error>
 /// `true` is returned if the tracking relationship existed and was removed as a
 /// side-effect of the function call. Otherwise, `false` is returned.
 ///
-/// # Caveats
+/// Any [`Policy`] set for `peer` in the context of `urn` is removed as well.
 ///
-/// Untracking will also attempt to prune any remote branches associated with
-/// `peer` (this mirrors the behaviour of `git`). Since refdb operations are not
-/// (yet) atomic, this may fail, leaving "dangling" refs in the storage. It is
-/// safe to call this function repeatedly, so as to ensure all remote tracking
-/// branches have been pruned.
+/// Untracking will also prune any remote branches associated with `peer`
+/// (this mirrors the behaviour of `git`). The branches are removed in a single
+/// [`storage::Transaction`] before the tracking relationship itself, so either
+/// all or none of them are removed. If the latter fails, it is safe to call
+/// this function again.
 #[tracing::instrument(skip(storage))]
 pub fn untrack(storage: &Storage, urn: &Urn, peer: PeerId) -> Result<bool, Error> {
+    // Prune all remote branches
+    let prune = storage
+        .reference_names_glob(glob::RefspecMatcher::from(
+            reflike!("refs/namespaces")
+                .join(urn)
+                .join(reflike!("refs/remotes"))
+                .join(peer)
+                .with_pattern_suffix(refspec_pattern!("*")),
+        ))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut tx = storage.transaction()?;
+    for branch in &prune {
+        tx.remove(branch)?;
+    }
+    tx.commit()?;
+
     let remote_name = tracking_remote_name(urn, &peer);
     let was_removed = storage
         .as_raw()
@@ -95,22 +117,41 @@ pub fn untrack(storage: &Storage, urn: &Urn, peer: PeerId) -> Result<bool, Error
         .map(|()| true)
         .or_matches::<Error, _, _>(is_not_found_err, || Ok(false))?;
 
-    // Prune all remote branches
-    let prune = storage.references_glob(glob::RefspecMatcher::from(
-        reflike!("refs/namespaces")
-            .join(urn)
-            .join(reflike!("refs/remotes"))
-            .join(peer)
-            .with_pattern_suffix(refspec_pattern!("*")),
-    ))?;
-
-    for branch in prune {
-        branch?.delete()?;
-    }
+    remove_policy(storage, urn, peer)?;
 
     Ok(was_removed)
 }
 
+/// Point the tracking remotes of all [`Urn`]s to the current
+/// [`Storage::peer_id`].
+///
+/// The remote urls record the local peer, so they need to be updated after the
+/// storage was re-keyed (see [`crate::git::rotation`]). If the new local peer
+/// was tracked itself, it is untracked, as it is not possible to track oneself.
+#[tracing::instrument(skip(storage))]
+pub fn rekey(storage: &Storage) -> Result<(), Error> {
+    let local_peer = storage.peer_id();
+    for name in storage.remotes()?.iter().flatten() {
+        let (urn, peer) = match parse_tracking_remote_name(name) {
+            Some(tracked) => tracked,
+            None => {
+                tracing::trace!(name = %name, "skipping non-tracking remote");
+                continue;
+            },
+        };
+
+        if &peer == local_peer {
+            untrack(storage, &urn, peer)?;
+        } else {
+            let url = GitUrlRef::from_urn(&urn, local_peer, &peer, &[]);
+            tracing::debug!("setting remote.{}.url = {}", name, url);
+            storage.as_raw().remote_set_url(name, &url.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Determine if `peer` is tracked in the context of `urn`.
 #[tracing::instrument(level = "trace", skip(storage))]
 pub fn is_tracked<S>(storage: &S, urn: &Urn, peer: PeerId) -> Result<bool, Error>
@@ -120,6 +161,127 @@ where
     storage.as_ref().has_remote(urn, peer).map_err(Error::from)
 }
 
+/// Track any peer in the context of `urn`.
+///
+/// This records a wildcard entry next to the per-peer tracking remotes, which
+/// causes [`crate::git::replication::replicate`] to track whichever peer `urn`
+/// is replicated from, and gossip about `urn` to be accepted from any peer.
+/// The peers tracked as a side-effect are regular tracking relationships, and
+/// thus not affected by [`untrack_any`].
+///
+/// `true` is returned if the wildcard entry didn't exist before and was
+/// created as a side-effect of the function call. Otherwise, `false` is
+/// returned.
+#[tracing::instrument(skip(storage))]
+pub fn track_any(storage: &Storage, urn: &Urn) -> Result<bool, Error> {
+    let was_created = !is_tracked_any(storage, urn)?;
+    if was_created {
+        let mut config = storage::Config::try_from(storage)?;
+        config.as_raw_mut().set_bool(&tracking_any_key(urn), true)?;
+    }
+
+    Ok(was_created)
+}
+
+/// Remove the wildcard entry created by [`track_any`].
+///
+/// `true` is returned if the entry existed and was removed as a side-effect of
+/// the function call. Otherwise, `false` is returned.
+#[tracing::instrument(skip(storage))]
+pub fn untrack_any(storage: &Storage, urn: &Urn) -> Result<bool, Error> {
+    let mut config = storage::Config::try_from(storage)?;
+    config
+        .as_raw_mut()
+        .remove(&tracking_any_key(urn))
+        .map(|()| true)
+        .or_matches(is_not_found_err, || Ok(false))
+}
+
+/// Determine if any peer is tracked in the context of `urn`, ie. if
+/// [`track_any`] was called for `urn`.
+#[tracing::instrument(level = "trace", skip(storage))]
+pub fn is_tracked_any<S>(storage: &S, urn: &Urn) -> Result<bool, Error>
+where
+    S: AsRef<storage::Storage>,
+{
+    storage
+        .as_ref()
+        .config_readonly()?
+        .as_raw()
+        .get_bool(&tracking_any_key(urn))
+        .or_matches(is_not_found_err, || Ok(false))
+}
+
+/// Set the tracking [`Policy`] of `peer` in the context of `urn`, replacing
+/// any previously set one.
+///
+/// The policy takes precedence over a default policy set via
+/// [`set_default_policy`]. Note that an empty policy is equivalent to not
+/// setting one at all, so in order to lift the restrictions of a default
+/// policy, allow `*` explicitly.
+#[tracing::instrument(skip(storage))]
+pub fn set_policy(
+    storage: &Storage,
+    urn: &Urn,
+    peer: PeerId,
+    policy: &Policy,
+) -> Result<(), Error> {
+    let mut config = storage::Config::try_from(storage)?;
+    policy.store(config.as_raw_mut(), &tracking_remote_name(urn, &peer))?;
+    Ok(())
+}
+
+/// Remove the tracking [`Policy`] of `peer` in the context of `urn`.
+///
+/// `true` is returned if a policy existed and was removed. Otherwise, `false`
+/// is returned.
+#[tracing::instrument(skip(storage))]
+pub fn remove_policy(storage: &Storage, urn: &Urn, peer: PeerId) -> Result<bool, Error> {
+    let mut config = storage::Config::try_from(storage)?;
+    Ok(Policy::remove(
+        config.as_raw_mut(),
+        &tracking_remote_name(urn, &peer),
+    )?)
+}
+
+/// Set the default tracking [`Policy`] of `peer`, which applies to all URNs
+/// for which no specific policy was set.
+#[tracing::instrument(skip(storage))]
+pub fn set_default_policy(storage: &Storage, peer: PeerId, policy: &Policy) -> Result<(), Error> {
+    let mut config = storage::Config::try_from(storage)?;
+    policy.store(config.as_raw_mut(), &peer.to_string())?;
+    Ok(())
+}
+
+/// Remove the default tracking [`Policy`] of `peer`.
+///
+/// `true` is returned if a policy existed and was removed. Otherwise, `false`
+/// is returned.
+#[tracing::instrument(skip(storage))]
+pub fn remove_default_policy(storage: &Storage, peer: PeerId) -> Result<bool, Error> {
+    let mut config = storage::Config::try_from(storage)?;
+    Ok(Policy::remove(config.as_raw_mut(), &peer.to_string())?)
+}
+
+/// Determine the effective tracking [`Policy`] of `peer` in the context of
+/// `urn`.
+///
+/// This is the policy set via [`set_policy`], or else the one set via
+/// [`set_default_policy`], or else the [`Default`] policy, which allows
+/// everything.
+#[tracing::instrument(level = "trace", skip(storage))]
+pub fn policy<S>(storage: &S, urn: &Urn, peer: PeerId) -> Result<Policy, Error>
+where
+    S: AsRef<storage::ReadOnly>,
+{
+    let config = storage.as_ref().config()?;
+    let config = config.as_raw();
+    match Policy::load(config, &tracking_remote_name(urn, &peer))? {
+        Some(policy) => Ok(policy),
+        None => Ok(Policy::load(config, &peer.to_string())?.unwrap_or_default()),
+    }
+}
+
 /// Obtain an iterator over the 1st degree tracked peers in the context of
 /// `urn`.
 pub fn tracked<S>(storage: &S, urn: &Urn) -> Result<Tracked, Error>
@@ -178,3 +340,12 @@ impl Iterator for Tracked {
 fn tracking_remote_name(urn: &Urn, peer: &PeerId) -> String {
     format!("{}/{}", urn.encode_id(), peer)
 }
+
+fn parse_tracking_remote_name(name: &str) -> Option<(Urn, PeerId)> {
+    let (id, peer) = name.split_once('/')?;
+    Some((Urn::try_from_id(id).ok()?, PeerId::from_str(peer).ok()?))
+}
+
+fn tracking_any_key(urn: &Urn) -> String {
+    format!("tracking.{}/*.any", urn.encode_id())
+}
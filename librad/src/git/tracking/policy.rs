@@ -0,0 +1,175 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{collections::BTreeSet, convert::TryFrom};
+
+use git_ext::{self as ext, is_not_found_err};
+use std_ext::result::ResultExt as _;
+use thiserror::Error;
+
+use crate::git::{
+    fetch,
+    refs::Refs,
+    storage::glob::{Pattern as _, RefspecMatcher},
+    types::RefsCategory,
+};
+
+const CONFIG_SECTION: &str = "tracking";
+const CONFIG_ALLOW: &str = "allow";
+const CONFIG_DENY: &str = "deny";
+const CONFIG_DATA_LIMIT: &str = "dataLimit";
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("invalid data limit: {0}")]
+    DataLimit(i64),
+
+    #[error(transparent)]
+    Pattern(#[from] ext::reference::name::Error),
+
+    #[error(transparent)]
+    Git(#[from] git2::Error),
+}
+
+/// A tracking policy, restricting which refs of a tracked peer are replicated.
+///
+/// Patterns are relative to `refs/`, e.g. `heads/main` or `tags/*`. To
+/// replicate everything except collaborative objects, deny `cobs/*`. Refs in
+/// the `rad` category are required for identity verification, and are thus
+/// never subject to filtering.
+///
+/// The [`Default`] policy allows everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Policy {
+    /// Refs to replicate. If empty, all refs are allowed.
+    pub allow: BTreeSet<ext::RefspecPattern>,
+    /// Refs to exclude from replication. Takes precedence over `allow`.
+    pub deny: BTreeSet<ext::RefspecPattern>,
+    /// Upper bound on the amount of data (in bytes) to fetch from the peer.
+    ///
+    /// Only takes effect if smaller than [`fetch::Limit::data`].
+    pub data_limit: Option<usize>,
+}
+
+impl Policy {
+    /// Determine if the ref `name` (relative to `refs/`) is allowed by this
+    /// policy.
+    pub fn allows(&self, name: &ext::RefLike) -> bool {
+        let matches =
+            |pat: &ext::RefspecPattern| RefspecMatcher::from(pat.clone()).matches(name.as_str());
+
+        !self.deny.iter().any(matches) && (self.allow.is_empty() || self.allow.iter().any(matches))
+    }
+
+    /// Like [`Policy::allows`], but for a ref `name` in the given `category`.
+    pub fn allows_categorised(&self, category: RefsCategory, name: &ext::OneLevel) -> bool {
+        category == RefsCategory::Rad
+            || self.allows(&ext::RefLike::from(category).join(name.clone()))
+    }
+
+    /// Retain only the [`Refs`] allowed by this policy.
+    pub fn filter(&self, mut refs: Refs) -> Refs {
+        refs.heads
+            .retain(|name, _| self.allows_categorised(RefsCategory::Heads, name));
+        refs.tags
+            .retain(|name, _| self.allows_categorised(RefsCategory::Tags, name));
+        refs.notes
+            .retain(|name, _| self.allows_categorised(RefsCategory::Notes, name));
+        refs.cobs
+            .retain(|name, _| self.allows_categorised(RefsCategory::Cobs, name));
+        refs
+    }
+
+    /// Restrict the [`fetch::Limit::data`] of `limit` to the
+    /// [`Policy::data_limit`], if any.
+    pub fn limit(&self, limit: fetch::Limit) -> fetch::Limit {
+        match self.data_limit {
+            Some(data) if data < limit.data => fetch::Limit { data, ..limit },
+            _ => limit,
+        }
+    }
+
+    /// Load the policy stored in the `tracking.<subsection>` section of
+    /// `config`.
+    ///
+    /// If no keys are set in that section, `None` is returned.
+    pub(super) fn load(config: &git2::Config, subsection: &str) -> Result<Option<Self>, Error> {
+        let allow = multivar(config, &key(subsection, CONFIG_ALLOW))?;
+        let deny = multivar(config, &key(subsection, CONFIG_DENY))?;
+        let data_limit = config
+            .get_i64(&key(subsection, CONFIG_DATA_LIMIT))
+            .map(Some)
+            .or_matches::<Error, _, _>(is_not_found_err, || Ok(None))?
+            .map(|limit| usize::try_from(limit).map_err(|_| Error::DataLimit(limit)))
+            .transpose()?;
+
+        if allow.is_empty() && deny.is_empty() && data_limit.is_none() {
+            Ok(None)
+        } else {
+            Ok(Some(Self {
+                allow,
+                deny,
+                data_limit,
+            }))
+        }
+    }
+
+    /// Store the policy in the `tracking.<subsection>` section of `config`,
+    /// replacing any previous one.
+    pub(super) fn store(&self, config: &mut git2::Config, subsection: &str) -> Result<(), Error> {
+        Self::remove(config, subsection)?;
+
+        for pat in &self.allow {
+            config.set_multivar(&key(subsection, CONFIG_ALLOW), "^$", pat.as_str())?;
+        }
+        for pat in &self.deny {
+            config.set_multivar(&key(subsection, CONFIG_DENY), "^$", pat.as_str())?;
+        }
+        if let Some(limit) = self.data_limit {
+            config.set_i64(&key(subsection, CONFIG_DATA_LIMIT), limit as i64)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the policy stored in the `tracking.<subsection>` section of
+    /// `config`.
+    ///
+    /// `true` is returned if any keys were removed.
+    pub(super) fn remove(config: &mut git2::Config, subsection: &str) -> Result<bool, Error> {
+        let mut removed = false;
+        for name in &[CONFIG_ALLOW, CONFIG_DENY, CONFIG_DATA_LIMIT] {
+            removed |= config
+                .remove_multivar(&key(subsection, name), ".*")
+                .map(|()| true)
+                .or_matches::<Error, _, _>(is_not_found_err, || Ok(false))?;
+        }
+
+        Ok(removed)
+    }
+}
+
+fn key(subsection: &str, name: &str) -> String {
+    format!("{}.{}.{}", CONFIG_SECTION, subsection, name)
+}
+
+fn multivar(config: &git2::Config, name: &str) -> Result<BTreeSet<ext::RefspecPattern>, Error> {
+    let mut pats = BTreeSet::new();
+    let entries = config
+        .multivar(name, None)
+        .map(Some)
+        .or_matches::<Error, _, _>(is_not_found_err, || Ok(None))?;
+    if let Some(entries) = entries {
+        for entry in &entries {
+            let entry = entry?;
+            if let Some(value) = entry.value() {
+                pats.insert(ext::RefspecPattern::try_from(value)?);
+            }
+        }
+    }
+
+    Ok(pats)
+}
@@ -38,6 +38,8 @@ pub enum RefsCategory {
     Rad,
     Tags,
     Notes,
+    /// Collaborative objects, eg. issues or patches.
+    Cobs,
 }
 
 impl RefsCategory {
@@ -47,6 +49,7 @@ impl RefsCategory {
             "rad" => Some(Self::Rad),
             "tags" => Some(Self::Tags),
             "notes" => Some(Self::Notes),
+            "cobs" => Some(Self::Cobs),
             _ => None,
         }
     }
@@ -59,6 +62,7 @@ impl Display for RefsCategory {
             Self::Rad => f.write_str("rad"),
             Self::Tags => f.write_str("tags"),
             Self::Notes => f.write_str("notes"),
+            Self::Cobs => f.write_str("cobs"),
         }
     }
 }
@@ -428,6 +432,17 @@ impl<N, R> Reference<N, R, Many> {
             namespace: namespace.into(),
         }
     }
+
+    /// Build a reference that points to:
+    ///     * `refs[/namespaces/<namespace>]/refs[/remotes/<remote>]/cobs/*`
+    pub fn cobs(namespace: impl Into<Option<N>>, remote: impl Into<Option<R>>) -> Self {
+        Self {
+            remote: remote.into(),
+            category: RefsCategory::Cobs,
+            name: refspec_pattern!("*"),
+            namespace: namespace.into(),
+        }
+    }
 }
 
 impl<N, R> Display for Reference<N, R, Many>
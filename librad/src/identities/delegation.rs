@@ -31,6 +31,21 @@ pub trait Delegations: sealed::Sealed {
     /// Nb.: "threshold" means that there must be `quorum_threshold() + 1` votes
     /// to form a quorum.
     fn quorum_threshold(&self) -> usize;
+
+    /// The number of distinct delegates, ie. the maximum number of
+    /// [`Delegations::eligible`] votes.
+    fn delegates(&self) -> usize;
+
+    /// Whether `self` and `other` delegate to the same delegates.
+    fn same_delegates(&self, other: &Self) -> bool;
+
+    /// The threshold of [`Delegations::eligible`] votes required for `next`,
+    /// which replaces `self`, to be approved by `self`.
+    ///
+    /// Defaults to [`Delegations::quorum_threshold`].
+    fn succession_threshold(&self, _next: &Self) -> usize {
+        self.quorum_threshold()
+    }
 }
 
 //// Forwarding impls for `Doc` and `Identity`
@@ -46,7 +61,27 @@ where
     }
 
     fn quorum_threshold(&self) -> usize {
-        self.delegations.quorum_threshold()
+        match self.threshold {
+            Some(threshold) => threshold.quorum.get() - 1,
+            None => self.delegations.quorum_threshold(),
+        }
+    }
+
+    fn delegates(&self) -> usize {
+        self.delegations.delegates()
+    }
+
+    fn same_delegates(&self, other: &Self) -> bool {
+        self.delegations.same_delegates(&other.delegations)
+    }
+
+    fn succession_threshold(&self, next: &Self) -> usize {
+        match self.threshold.and_then(|threshold| threshold.delegations) {
+            Some(n) if self.threshold != next.threshold || !self.same_delegates(next) => {
+                n.get() - 1
+            },
+            _ => self.quorum_threshold(),
+        }
     }
 }
 
@@ -63,6 +98,18 @@ where
     fn quorum_threshold(&self) -> usize {
         self.doc.quorum_threshold()
     }
+
+    fn delegates(&self) -> usize {
+        self.doc.delegates()
+    }
+
+    fn same_delegates(&self, other: &Self) -> bool {
+        self.doc.same_delegates(&other.doc)
+    }
+
+    fn succession_threshold(&self, next: &Self) -> usize {
+        self.doc.succession_threshold(&next.doc)
+    }
 }
 
 /// "Existentialised" delegations.
@@ -88,6 +135,25 @@ impl<T, R: Ord, C: Ord> Delegations for SomeDelegations<T, R, C> {
             SomeDelegations::Indirect(indirect) => indirect.quorum_threshold(),
         }
     }
+
+    fn delegates(&self) -> usize {
+        match self {
+            SomeDelegations::Direct(direct) => direct.delegates(),
+            SomeDelegations::Indirect(indirect) => indirect.delegates(),
+        }
+    }
+
+    fn same_delegates(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SomeDelegations::Direct(this), SomeDelegations::Direct(that)) => {
+                this.same_delegates(that)
+            },
+            (SomeDelegations::Indirect(this), SomeDelegations::Indirect(that)) => {
+                this.same_delegates(that)
+            },
+            (_, _) => false,
+        }
+    }
 }
 
 impl<T, R: Ord, C: Ord> sealed::Sealed for SomeDelegations<T, R, C> {}
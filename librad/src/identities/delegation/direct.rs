@@ -41,7 +41,15 @@ impl Delegations for Direct {
     }
 
     fn quorum_threshold(&self) -> usize {
-        self.0.len() / 2
+        self.delegates() / 2
+    }
+
+    fn delegates(&self) -> usize {
+        self.0.len()
+    }
+
+    fn same_delegates(&self, other: &Self) -> bool {
+        self == other
     }
 }
 
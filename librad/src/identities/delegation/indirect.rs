@@ -280,7 +280,7 @@ impl<T, R, C> IntoIterator for Indirect<T, R, C> {
     }
 }
 
-impl<T, R, C> Delegations for Indirect<T, R, C> {
+impl<T, R: PartialEq, C> Delegations for Indirect<T, R, C> {
     type Error = error::DoubleVote;
 
     fn eligible(&self, votes: BTreeSet<&PublicKey>) -> Result<BTreeSet<&PublicKey>, Self::Error> {
@@ -288,14 +288,26 @@ impl<T, R, C> Delegations for Indirect<T, R, C> {
     }
 
     fn quorum_threshold(&self) -> usize {
-        let direct = self
-            .delegations
-            .iter()
-            .filter(|(_, idx)| idx.is_none())
-            .count();
+        self.delegates() / 2
+    }
+
+    fn delegates(&self) -> usize {
+        let direct = self.iter().direct().count();
         let indirect = self.identities.len();
 
-        (direct + indirect) / 2
+        direct + indirect
+    }
+
+    /// Indirect delegations are compared by their `root`, so an update of an
+    /// indirectly delegating identity (eg. a key rotation) is not considered a
+    /// change of delegates.
+    fn same_delegates(&self, other: &Self) -> bool {
+        self.iter().direct().eq(other.iter().direct())
+            && self.identities.len() == other.identities.len()
+            && self
+                .identities
+                .iter()
+                .all(|id| other.identities.iter().any(|other| other.root == id.root))
     }
 }
 
@@ -8,6 +8,7 @@
 use std::{
     fmt::{Debug, Display},
     marker::PhantomData,
+    num::NonZeroUsize,
     ops::Deref,
 };
 
@@ -23,11 +24,51 @@ pub mod error;
 /// `replaces` is a `tree` oid.
 #[derive(Clone, Debug, PartialEq, serde::Deserialize)]
 pub struct Doc<T, D, Revision> {
-    /// Protocol version. Always serialised as `0` (zero).
+    /// Protocol version. Serialised as `0` (zero), or as `1` (one) if a
+    /// `threshold` is set.
     pub version: u8,
     pub replaces: Option<Revision>,
     pub payload: T,
     pub delegations: D,
+    /// Overrides the simple majority quorum of the `delegations`, if set.
+    #[serde(default)]
+    pub threshold: Option<Threshold>,
+}
+
+/// A quorum policy, overriding the simple majority of
+/// [`Delegations::quorum_threshold`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Threshold {
+    /// The number of eligible signatures required to form a quorum.
+    pub quorum: NonZeroUsize,
+    /// The number of signatures, eligible as per the **parent** revision,
+    /// required to approve a revision which changes the delegations or the
+    /// [`Threshold`] itself. If `None`, `quorum` applies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegations: Option<NonZeroUsize>,
+}
+
+impl Threshold {
+    pub fn new(quorum: NonZeroUsize) -> Self {
+        Self {
+            quorum,
+            delegations: None,
+        }
+    }
+
+    /// Require `n` signatures for changes to the delegations.
+    pub fn with_delegations(self, n: NonZeroUsize) -> Self {
+        Self {
+            delegations: Some(n),
+            ..self
+        }
+    }
+
+    /// Whether the [`Threshold`] can be reached given the number of
+    /// [`Delegations::delegates`].
+    pub fn is_reachable(&self, delegates: usize) -> bool {
+        self.quorum.get() <= delegates && self.delegations.map_or(true, |n| n.get() <= delegates)
+    }
 }
 
 impl<T, D, Revision> serde::Serialize for Doc<T, D, Revision>
@@ -40,11 +81,18 @@ where
     where
         S: serde::Serializer,
     {
-        let mut doc = serializer.serialize_struct("Doc", 4)?;
-        doc.serialize_field("version", &0)?;
+        let (version, len) = match self.threshold {
+            None => (0, 4),
+            Some(_) => (1, 5),
+        };
+        let mut doc = serializer.serialize_struct("Doc", len)?;
+        doc.serialize_field("version", &version)?;
         doc.serialize_field("replaces", &self.replaces)?;
         doc.serialize_field("payload", &self.payload)?;
         doc.serialize_field("delegations", &self.delegations)?;
+        if let Some(threshold) = &self.threshold {
+            doc.serialize_field("threshold", threshold)?;
+        }
         doc.end()
     }
 }
@@ -63,6 +111,7 @@ impl<T, D, R> Doc<T, D, R> {
             replaces: self.replaces,
             payload: f(self.payload),
             delegations: g(self.delegations),
+            threshold: self.threshold,
         }
     }
 
@@ -95,6 +144,7 @@ impl<T, D, R> Doc<T, D, R> {
             replaces: doc.replaces,
             payload: doc.payload?,
             delegations: doc.delegations,
+            threshold: doc.threshold,
         })
     }
 
@@ -111,6 +161,7 @@ impl<T, D, R> Doc<T, D, R> {
             replaces: doc.replaces,
             payload: doc.payload,
             delegations: doc.delegations?,
+            threshold: doc.threshold,
         })
     }
 }
@@ -362,7 +413,7 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Signed> {
     ///
     /// # Errors
     ///
-    /// If the number of signatures does not reach the
+    /// If the number of signatures does not exceed the
     /// [`Delegations::quorum_threshold`].
     pub fn quorum(self) -> Result<Verifying<Identity<T, R, C>, Quorum>, error::Verify<R, C>>
     where
@@ -404,7 +455,7 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Quorum> {
     /// * `self`'s signatures do not reach a quorum of the `parent`'s
     ///   delegations. In other words,
     ///   `parent.eligible(self.signatures.keys()).len() >
-    ///   parent.doc.quorum_threshold()`
+    ///   parent.doc.succession_threshold(&self.doc)`
     /// * `parent.eligible(self.signatures.keys())` returns an error
     pub fn verified(
         self,
@@ -444,7 +495,7 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Quorum> {
                         .map_err(error::Verify::eligibility)?
                         .len();
 
-                    if votes > 0 && votes > parent.doc.quorum_threshold() {
+                    if votes > 0 && votes > parent.doc.succession_threshold(&self.doc) {
                         Ok(self.coerce())
                     } else {
                         Err(error::Verify::ParentQuorum)
@@ -455,6 +506,31 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Quorum> {
     }
 }
 
+impl<T, R, C> Verifying<Identity<T, R, C>, Quorum> {
+    /// Whether `parent` approves `self` by a quorum, but not by as many votes
+    /// as its [`Delegations::succession_threshold`] requires.
+    pub fn is_pending_succession(
+        &self,
+        parent: &Verifying<Identity<T, R, C>, Verified>,
+    ) -> Result<bool, error::Verify<R, C>>
+    where
+        T: Delegations,
+        T::Error: std::error::Error + Send + Sync + 'static,
+
+        R: Debug + Display,
+        C: Debug + Display,
+    {
+        let votes = parent
+            .doc
+            .eligible(self.signatures.keys().collect())
+            .map_err(error::Verify::eligibility)?
+            .len();
+
+        Ok(votes > parent.doc.quorum_threshold()
+            && votes <= parent.doc.succession_threshold(&self.doc))
+    }
+}
+
 /// The result of running [`Verifying::verify`].
 ///
 /// In addition to the most recent verified [`Identity`], the parent used to
@@ -479,6 +555,8 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Verified> {
     ///
     /// [`Signed`] identities in the progeny, which do not pass [`Quorum`] are
     /// skipped. This is to allow proposals to be made over the same protocol.
+    /// Likewise, identities which are signed by a quorum of their parent, but
+    /// do not reach its [`Delegations::succession_threshold`], are skipped.
     pub fn verify<E>(
         self,
         mut progeny: impl Iterator<Item = Result<Verifying<Identity<T, R, C>, Untrusted>, E>>,
@@ -520,6 +598,11 @@ impl<T, R, C> Verifying<Identity<T, R, C>, Verified> {
                                     parent: acc.parent,
                                 }),
                             }
+                        } else if quorum.is_pending_succession(&acc.head)? {
+                            // Signed by a quorum of the parent, but not by as
+                            // many delegates as its `Threshold` requires for
+                            // this change: skip, like a proposal
+                            Ok(acc)
                         } else {
                             quorum.verified(Some(&acc.head)).map(|verified| Folded {
                                 head: verified,
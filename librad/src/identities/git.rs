@@ -14,7 +14,7 @@ use crate::{
     identities::{
         delegation::{self, Delegations},
         generic::{self, Signed, Verified},
-        payload::{self, PersonPayload, ProjectPayload},
+        payload::{self, PersonPayload, ProjectPayload, Registry},
         sign::{Signature, Signatures},
         urn,
     },
@@ -26,7 +26,7 @@ use crate::{
 pub mod error;
 pub mod iter;
 
-pub use generic::Verifying;
+pub use generic::{Threshold, Verifying};
 
 mod load;
 mod sign;
@@ -87,13 +87,17 @@ pub type IndirectDelegation = delegation::Indirect<PersonPayload, Revision, Cont
 #[derive(Clone)]
 pub struct Identities<'a, T> {
     repo: &'a git2::Repository,
+    registry: &'a Registry,
     _marker: PhantomData<T>,
 }
 
+/// Payload extensions are validated against [`Registry::builtin`], see
+/// [`Identities::with_registry`].
 impl<'a, T: 'a> From<&'a git2::Repository> for Identities<'a, T> {
     fn from(repo: &'a git2::Repository) -> Self {
         Self {
             repo,
+            registry: Registry::builtin(),
             _marker: PhantomData,
         }
     }
@@ -101,11 +105,17 @@ impl<'a, T: 'a> From<&'a git2::Repository> for Identities<'a, T> {
 
 impl<'a, T: 'a> From<&Identities<'a, T>> for Identities<'a, T> {
     fn from(other: &Identities<'a, T>) -> Self {
-        Identities::from(other.repo)
+        other.coerce()
     }
 }
 
 impl<'a, T: 'a> Identities<'a, T> {
+    /// Validate the payload extensions of verified identities against
+    /// `registry` (see [`payload::Payload::validate`]).
+    pub fn with_registry(self, registry: &'a Registry) -> Self {
+        Self { registry, ..self }
+    }
+
     /// Convenience to specialise `T` to [`Person`].
     pub fn as_person(&self) -> Identities<'_, Person> {
         self.coerce()
@@ -126,9 +136,10 @@ impl<'a, T: 'a> Identities<'a, T> {
         self.coerce()
     }
 
-    pub fn coerce<U>(&self) -> Identities<'_, U> {
+    pub fn coerce<U>(&self) -> Identities<'a, U> {
         Identities {
             repo: self.repo,
+            registry: self.registry,
             _marker: PhantomData,
         }
     }
@@ -440,9 +451,12 @@ impl<'a> Identities<'a, Person> {
     /// Verify the person history with head commit `head`.
     ///
     /// The returned [`VerifiedPerson`] is the **most recent** identity for
-    /// which the verification succeeded -- which may or may not be `head`.
+    /// which the verification succeeded -- which may or may not be `head`. Its
+    /// payload extensions must be valid as per the [`Registry`] of `self`.
     pub fn verify(&self, head: git2::Oid) -> Result<VerifiedPerson, error::VerifyPerson> {
-        Ok(self.verify_generic(head)?)
+        let person = self.verify_generic(head)?;
+        person.payload().validate(self.registry)?;
+        Ok(person)
     }
 
     /// Create a new [`Person`] from a payload and delegations.
@@ -458,7 +472,26 @@ impl<'a> Identities<'a, Person> {
     where
         S: Signer,
     {
-        let (doc, root) = self.base(payload, delegations)?;
+        self.create_with_threshold(payload, delegations, None, signer)
+    }
+
+    /// Like [`Self::create`], but with a quorum [`Threshold`] overriding the
+    /// simple majority of the `delegations`.
+    ///
+    /// # Errors
+    ///
+    /// If the `threshold` can not be reached by the `delegations`.
+    pub fn create_with_threshold<S>(
+        &self,
+        payload: PersonPayload,
+        delegations: delegation::Direct,
+        threshold: Option<Threshold>,
+        signer: &S,
+    ) -> Result<Person, error::Store>
+    where
+        S: Signer,
+    {
+        let (doc, root) = self.base_with_threshold(payload, delegations, threshold)?;
         let revision = {
             let mut builder = self.repo.treebuilder(None)?;
             builder.insert(root.to_string(), *root, 0o100_644)?;
@@ -489,11 +522,22 @@ impl<'a> Identities<'a, Person> {
         payload: PersonPayload,
         delegations: delegation::Direct,
     ) -> Result<(Doc<PersonPayload, payload::PersonDelegations>, Revision), error::Store> {
+        self.base_with_threshold(payload, delegations, None)
+    }
+
+    fn base_with_threshold(
+        &self,
+        payload: PersonPayload,
+        delegations: delegation::Direct,
+        threshold: Option<Threshold>,
+    ) -> Result<(Doc<PersonPayload, payload::PersonDelegations>, Revision), error::Store> {
+        ensure_reachable(threshold, &delegations)?;
         let doc = Doc {
             version: 0,
             replaces: None,
             payload,
             delegations: payload::PersonDelegations::from(delegations),
+            threshold,
         };
         let root: Revision = self.repo.blob(&Cjson(&doc).canonical_form()?)?.into();
         Ok((doc, root))
@@ -506,6 +550,8 @@ impl<'a> Identities<'a, Person> {
     /// the result is the unwrapped [`Person`] of the `base` argument.
     ///
     /// Otherwise, the result is a new [`Person`] whose parent is `base`.
+    ///
+    /// The [`Threshold`] of `base`, if any, is retained.
     pub fn update<S>(
         &self,
         base: SignedPerson,
@@ -513,6 +559,27 @@ impl<'a> Identities<'a, Person> {
         delegations: impl Into<Option<delegation::Direct>>,
         signer: &S,
     ) -> Result<Person, error::Store>
+    where
+        S: Signer,
+    {
+        let threshold = base.doc.threshold;
+        self.update_with_threshold(base, payload, delegations, threshold, signer)
+    }
+
+    /// Like [`Self::update`], but replacing the [`Threshold`] of `base` with
+    /// `threshold`.
+    ///
+    /// # Errors
+    ///
+    /// If the `threshold` can not be reached by the resulting delegations.
+    pub fn update_with_threshold<S>(
+        &self,
+        base: SignedPerson,
+        payload: impl Into<Option<PersonPayload>>,
+        delegations: impl Into<Option<delegation::Direct>>,
+        threshold: Option<Threshold>,
+        signer: &S,
+    ) -> Result<Person, error::Store>
     where
         S: Signer,
     {
@@ -520,17 +587,18 @@ impl<'a> Identities<'a, Person> {
         let delegations = delegations.into();
 
         // Fast path
-        if payload.is_none() && delegations.is_none() {
+        if payload.is_none() && delegations.is_none() && threshold == base.doc.threshold {
             return Ok(base.into_inner());
         }
 
+        let delegations = delegations.unwrap_or_else(|| base.delegations().clone());
+        ensure_reachable(threshold, &delegations)?;
         let doc = Doc {
             version: 0,
             replaces: Some(base.revision),
             payload: payload.unwrap_or_else(|| base.payload().clone()),
-            delegations: payload::PersonDelegations::from(
-                delegations.unwrap_or_else(|| base.delegations().clone()),
-            ),
+            delegations: payload::PersonDelegations::from(delegations),
+            threshold,
         };
 
         let revision = {
@@ -581,7 +649,8 @@ impl<'a> Identities<'a, Project> {
     /// indirect delegation from succeeding.
     ///
     /// The returned [`VerifiedProject`] is the **most recent** identity for
-    /// which the verification succeeded -- which may or may not be `head`.
+    /// which the verification succeeded -- which may or may not be `head`. Its
+    /// payload extensions must be valid as per the [`Registry`] of `self`.
     pub fn verify<F, E>(
         &self,
         head: git2::Oid,
@@ -592,6 +661,7 @@ impl<'a> Identities<'a, Project> {
         E: std::error::Error + Send + Sync + 'static,
     {
         let generic::Folded { head, parent } = self.fold_verify_generic::<ProjectDoc>(head)?;
+        head.payload().validate(self.registry)?;
         let head = head
             .into_inner()
             .map(|doc| {
@@ -620,7 +690,26 @@ impl<'a> Identities<'a, Project> {
     where
         S: Signer,
     {
-        let (doc, root) = self.base(payload, delegations.clone())?;
+        self.create_with_threshold(payload, delegations, None, signer)
+    }
+
+    /// Like [`Self::create`], but with a quorum [`Threshold`] overriding the
+    /// simple majority of the `delegations`.
+    ///
+    /// # Errors
+    ///
+    /// If the `threshold` can not be reached by the `delegations`.
+    pub fn create_with_threshold<S>(
+        &self,
+        payload: ProjectPayload,
+        delegations: IndirectDelegation,
+        threshold: Option<Threshold>,
+        signer: &S,
+    ) -> Result<Project, error::Store>
+    where
+        S: Signer,
+    {
+        let (doc, root) = self.base_with_threshold(payload, delegations.clone(), threshold)?;
         let revision = {
             let mut builder = self.repo.treebuilder(None)?;
             self.inline_indirect(&mut builder, &delegations)?;
@@ -658,11 +747,28 @@ impl<'a> Identities<'a, Project> {
         ),
         error::Store,
     > {
+        self.base_with_threshold(payload, delegations, None)
+    }
+
+    fn base_with_threshold(
+        &self,
+        payload: ProjectPayload,
+        delegations: IndirectDelegation,
+        threshold: Option<Threshold>,
+    ) -> Result<
+        (
+            Doc<ProjectPayload, payload::ProjectDelegations<Revision>>,
+            Revision,
+        ),
+        error::Store,
+    > {
+        ensure_reachable(threshold, &delegations)?;
         let doc = Doc {
             version: 0,
             replaces: None,
             payload,
             delegations: payload::ProjectDelegations::from(delegations),
+            threshold,
         };
         let root: Revision = self.repo.blob(&Cjson(&doc).canonical_form()?)?.into();
         Ok((doc, root))
@@ -675,6 +781,8 @@ impl<'a> Identities<'a, Project> {
     /// the result is the unwrapped [`Project`] of the `base` argument.
     ///
     /// Otherwise, the result is a new [`Project`] whose parent is `base`.
+    ///
+    /// The [`Threshold`] of `base`, if any, is retained.
     pub fn update<S>(
         &self,
         base: SignedProject,
@@ -682,6 +790,27 @@ impl<'a> Identities<'a, Project> {
         delegations: impl Into<Option<IndirectDelegation>>,
         signer: &S,
     ) -> Result<Project, error::Store>
+    where
+        S: Signer,
+    {
+        let threshold = base.doc.threshold;
+        self.update_with_threshold(base, payload, delegations, threshold, signer)
+    }
+
+    /// Like [`Self::update`], but replacing the [`Threshold`] of `base` with
+    /// `threshold`.
+    ///
+    /// # Errors
+    ///
+    /// If the `threshold` can not be reached by the resulting delegations.
+    pub fn update_with_threshold<S>(
+        &self,
+        base: SignedProject,
+        payload: impl Into<Option<ProjectPayload>>,
+        delegations: impl Into<Option<IndirectDelegation>>,
+        threshold: Option<Threshold>,
+        signer: &S,
+    ) -> Result<Project, error::Store>
     where
         S: Signer,
     {
@@ -689,10 +818,15 @@ impl<'a> Identities<'a, Project> {
         let delegations = delegations.into();
 
         // Fast path
-        if payload.is_none() && delegations.is_none() {
+        if payload.is_none() && delegations.is_none() && threshold == base.doc.threshold {
             return Ok(base.into_inner());
         }
 
+        ensure_reachable(
+            threshold,
+            delegations.as_ref().unwrap_or_else(|| base.delegations()),
+        )?;
+
         // FIXME: reorder stuff to avoid cloning
 
         let doc = Doc {
@@ -703,6 +837,7 @@ impl<'a> Identities<'a, Project> {
                 .clone()
                 .map(payload::ProjectDelegations::from)
                 .unwrap_or_else(|| base.delegations().clone().into()),
+            threshold,
         };
 
         let root = base.root;
@@ -828,3 +963,18 @@ where
     let sig = block_on(signer.sign(rev.as_bytes()))?;
     Ok(Signature::from((signer.public_key().into(), sig.into())))
 }
+
+fn ensure_reachable<D>(threshold: Option<Threshold>, delegations: &D) -> Result<(), error::Store>
+where
+    D: Delegations,
+{
+    match threshold {
+        Some(threshold) if !threshold.is_reachable(delegations.delegates()) => {
+            Err(error::Store::UnreachableThreshold {
+                threshold,
+                delegates: delegations.delegates(),
+            })
+        },
+        _ => Ok(()),
+    }
+}
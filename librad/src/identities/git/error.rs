@@ -11,6 +11,7 @@ use crate::{
     identities::{
         delegation::indirect::error::FromIter as DelegationsFromIterError,
         generic,
+        payload,
         sign,
         ContentId,
         Revision,
@@ -39,6 +40,9 @@ pub enum Load {
     #[error(transparent)]
     Delegation(#[from] DelegationsFromIterError<Revision>),
 
+    #[error(transparent)]
+    Payload(#[from] payload::registry::Invalid),
+
     #[error(transparent)]
     Signatures(#[from] self::Signatures),
 
@@ -61,6 +65,12 @@ pub enum Store {
     #[error(transparent)]
     Load(#[from] self::Load),
 
+    #[error("threshold {threshold:?} can not be reached by {delegates} delegates")]
+    UnreachableThreshold {
+        threshold: generic::Threshold,
+        delegates: usize,
+    },
+
     #[error("failed to produce a signature")]
     Signer(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
 
@@ -121,6 +131,9 @@ pub enum VerifyProject {
     #[error(transparent)]
     Delegation(#[from] DelegationsFromIterError<Revision>),
 
+    #[error(transparent)]
+    Payload(#[from] payload::registry::Invalid),
+
     #[error(transparent)]
     Load(#[from] self::Load),
 
@@ -141,6 +154,9 @@ pub enum VerifyPerson {
     #[error(transparent)]
     Verification(#[from] generic::error::Verify<Revision, ContentId>),
 
+    #[error(transparent)]
+    Payload(#[from] payload::registry::Invalid),
+
     #[error(transparent)]
     Git(#[from] git2::Error),
 }
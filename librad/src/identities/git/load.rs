@@ -46,6 +46,16 @@ impl<'de> serde::Deserialize<'de> for SomeDoc {
         let doc: Doc<SomePayload, SomeDelegations<Revision, ext::oid::FromMultihashError>> =
             serde::Deserialize::deserialize(deserializer)?;
 
+        match (doc.version, &doc.threshold) {
+            (0, None) | (1, Some(_)) => {},
+            (version, _) => {
+                return Err(serde::de::Error::custom(format!(
+                    "unsupported document version {}",
+                    version
+                )))
+            },
+        }
+
         match (doc.payload, doc.delegations) {
             (SomePayload::Person(payload), SomeDelegations::Person(delegations)) => {
                 Ok(Self::Person(Doc {
@@ -53,6 +63,7 @@ impl<'de> serde::Deserialize<'de> for SomeDoc {
                     replaces: doc.replaces,
                     payload,
                     delegations,
+                    threshold: doc.threshold,
                 }))
             },
 
@@ -62,6 +73,7 @@ impl<'de> serde::Deserialize<'de> for SomeDoc {
                     replaces: doc.replaces,
                     payload,
                     delegations,
+                    threshold: doc.threshold,
                 }))
             },
 
@@ -71,6 +83,7 @@ impl<'de> serde::Deserialize<'de> for SomeDoc {
                     replaces: doc.replaces,
                     payload,
                     delegations: (*delegations).iter().copied().map(Either::Left).collect(),
+                    threshold: doc.threshold,
                 }))
             },
 
@@ -26,6 +26,9 @@ use super::{
     urn::{HasProtocol, Urn},
 };
 
+pub mod registry;
+pub use registry::Registry;
+
 lazy_static! {
     /// Base [`Url`] for [`Person`]
     static ref PERSON_NAMESPACE_BASE: Url =
@@ -48,6 +51,14 @@ lazy_static! {
         base.path_segments_mut().unwrap().extend(&["v1"]);
         base
     };
+
+    /// Versioned [`Url`] for [`Revocations`], version 1
+    static ref REVOCATIONS_NAMESPACE_V1: Url =
+        Url::parse("https://radicle.xyz/link/identities/revocations/v1").unwrap();
+
+    /// Versioned [`Url`] for [`Contributor`], version 1
+    static ref CONTRIBUTOR_NAMESPACE_V1: Url =
+        Url::parse("https://radicle.xyz/link/identities/contributor/v1").unwrap();
 }
 
 /// Structure `radicle-link` expects to be part of a [`Payload`] describing a
@@ -59,6 +70,121 @@ pub struct Person {
 
 impl sealed::Sealed for Person {}
 
+/// Extension of a [`PersonPayload`] listing the keys which were retired by
+/// the person, eg. by rotating to a new key.
+///
+/// Unlike keys which were merely removed from the delegations, revoked keys
+/// are no longer trusted to sign `rad/signed_refs`, see
+/// [`crate::git::rotation`].
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Revocations {
+    pub keys: BTreeSet<PublicKey>,
+}
+
+/// Extension of a [`PersonPayload`] describing the person as a contributor to
+/// projects.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Contributor {
+    /// Endpoint collaborators can fetch large files (stored outside of the
+    /// source tree) of the contributor from.
+    ///
+    /// See [`crate::git::include::Include::set_largefiles`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub largefiles: Option<UrlTemplate>,
+}
+
+/// A URL template as per [RFC 6570], eg.
+///
+/// ```text
+/// https://github.com/me/rad-mirror.git/info/lfs/objects/batch
+/// ipfs://{SHA256_CID}
+/// ```
+///
+/// Only the syntax of expressions is checked: the template must be a valid
+/// [`Url`] if every expression is substituted with a plain value.
+///
+/// [RFC 6570]: https://datatracker.ietf.org/doc/html/rfc6570
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct UrlTemplate(String);
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum UrlTemplateError {
+    #[error("unbalanced expression in URL template")]
+    Unbalanced,
+
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+}
+
+impl UrlTemplate {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the template contains any expressions.
+    pub fn has_expressions(&self) -> bool {
+        self.0.contains('{')
+    }
+
+    /// The template as a [`Url`], if it doesn't contain any expressions.
+    pub fn as_url(&self) -> Option<Url> {
+        if self.has_expressions() {
+            None
+        } else {
+            Url::parse(&self.0).ok()
+        }
+    }
+}
+
+impl TryFrom<String> for UrlTemplate {
+    type Error = UrlTemplateError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let mut skeleton = String::with_capacity(s.len());
+        let mut in_expression = false;
+        for c in s.chars() {
+            match (c, in_expression) {
+                ('{', false) => in_expression = true,
+                ('}', true) => {
+                    in_expression = false;
+                    skeleton.push('x')
+                },
+                ('{', true) | ('}', false) => return Err(UrlTemplateError::Unbalanced),
+                (_, true) => {},
+                (c, false) => skeleton.push(c),
+            }
+        }
+        if in_expression {
+            return Err(UrlTemplateError::Unbalanced);
+        }
+        Url::parse(&skeleton)?;
+
+        Ok(Self(s))
+    }
+}
+
+impl TryFrom<&str> for UrlTemplate {
+    type Error = UrlTemplateError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::try_from(s.to_owned())
+    }
+}
+
+impl From<UrlTemplate> for String {
+    fn from(UrlTemplate(s): UrlTemplate) -> Self {
+        s
+    }
+}
+
+impl fmt::Display for UrlTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Structure `radicle-link` expects to be part of a [`Payload`] describing a
 /// project identity.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -117,6 +243,18 @@ impl HasNamespace for Project {
     }
 }
 
+impl HasNamespace for Revocations {
+    fn namespace() -> &'static Url {
+        &REVOCATIONS_NAMESPACE_V1
+    }
+}
+
+impl HasNamespace for Contributor {
+    fn namespace() -> &'static Url {
+        &CONTRIBUTOR_NAMESPACE_V1
+    }
+}
+
 /// Internal trait which helps deal with future versions
 pub trait Subject: HasNamespace + sealed::Sealed {
     fn namespace_matches(url: &Url) -> bool;
@@ -153,6 +291,10 @@ pub enum SomePayload {
 /// [`serde_json::Value`]s. Every member is namespaced by a [`Url`], as
 /// described by its [`HasNamespace`] impl.
 ///
+/// Extensions are not validated when they are set, or during deserialisation:
+/// [`Payload::validate`] checks them against the types known to a
+/// [`Registry`]. Extensions with unknown namespaces are preserved as-is.
+///
 /// Note that it is an error during deserialisation if duplicate namespaces are
 /// found in the input -- this is unlike normal JSON deserialisation, which
 /// would just treat objects as maps, retaining the last key found in the input.
@@ -222,6 +364,24 @@ where
         self.ext.range(range)
     }
 
+    /// The [`registry::Descriptor`]s of the extensions of this payload which
+    /// are known to `registry`.
+    pub fn registered_ext<'a>(&self, registry: &'a Registry) -> Vec<&'a registry::Descriptor> {
+        self.ext
+            .iter()
+            .filter(|(_, v)| !v.is_null())
+            .filter_map(|(k, _)| registry.lookup(k))
+            .collect()
+    }
+
+    /// Validate the extensions of this payload which are known to `registry`
+    /// against their registered type.
+    pub fn validate(&self, registry: &Registry) -> Result<(), registry::Invalid> {
+        self.ext
+            .iter()
+            .try_for_each(|(k, v)| registry.validate(k, v))
+    }
+
     pub fn remove_ext<U>(&mut self) -> Result<Option<U>, serde_json::Error>
     where
         U: HasNamespace + serde::de::DeserializeOwned,
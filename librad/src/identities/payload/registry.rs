@@ -0,0 +1,205 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Registry of known [`Payload`] extensions.
+//!
+//! Crates which define extensions of identity payloads can
+//! [`Registry::register`] them as an [`Extension`]. [`Payload::validate`]
+//! checks the values under a registered namespace against the registered
+//! type. Values under namespaces nobody registered are preserved as-is.
+//!
+//! [`Payload`]: super::Payload
+//! [`Payload::validate`]: super::Payload::validate
+
+use std::{any::TypeId, collections::BTreeMap, fmt};
+
+use thiserror::Error;
+use url::Url;
+
+use super::{Contributor, HasNamespace, Person, Project, Revocations, Subject};
+
+lazy_static! {
+    static ref BUILTIN: Registry = Registry::default();
+}
+
+/// A typed extension of a [`super::Payload`].
+pub trait Extension:
+    HasNamespace + serde::Serialize + serde::de::DeserializeOwned + 'static
+{
+    /// The version of the extension's schema.
+    ///
+    /// Note that a new version which is not backwards-compatible should also
+    /// use a new [`HasNamespace::namespace`].
+    const VERSION: u32;
+
+    /// Check invariants of the extension which can not be expressed by its
+    /// [`serde::Deserialize`] impl.
+    fn validate(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Ok(())
+    }
+}
+
+impl Extension for Revocations {
+    const VERSION: u32 = 1;
+}
+
+impl Extension for Contributor {
+    const VERSION: u32 = 1;
+}
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("extension namespace can not be a subject namespace")]
+    ExtensionIsSubject,
+
+    #[error("extension namespace {namespace} is already registered for `{type_name}`")]
+    Conflict {
+        namespace: Url,
+        type_name: &'static str,
+    },
+}
+
+/// An extension value did not conform to its registered [`Extension`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Invalid {
+    #[error("malformed `{type_name}`: {source}")]
+    Malformed {
+        type_name: &'static str,
+        source: serde_json::Error,
+    },
+
+    #[error("invalid `{type_name}`: {source}")]
+    Validation {
+        type_name: &'static str,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
+    },
+}
+
+/// Information about a registered [`Extension`].
+#[derive(Clone)]
+pub struct Descriptor {
+    pub namespace: &'static Url,
+    pub version: u32,
+    /// The name of the Rust type the extension was registered as.
+    pub type_name: &'static str,
+    type_id: TypeId,
+    validate: fn(&serde_json::Value) -> Result<(), Invalid>,
+}
+
+impl fmt::Debug for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Descriptor")
+            .field("namespace", &self.namespace.as_str())
+            .field("version", &self.version)
+            .field("type_name", &self.type_name)
+            .finish()
+    }
+}
+
+impl Descriptor {
+    fn of<U: Extension>() -> Self {
+        Self {
+            namespace: U::namespace(),
+            version: U::VERSION,
+            type_name: std::any::type_name::<U>(),
+            type_id: TypeId::of::<U>(),
+            validate: validate::<U>,
+        }
+    }
+
+    /// Validate `val` against the registered type.
+    pub fn validate(&self, val: &serde_json::Value) -> Result<(), Invalid> {
+        (self.validate)(val)
+    }
+}
+
+fn validate<U: Extension>(val: &serde_json::Value) -> Result<(), Invalid> {
+    let type_name = std::any::type_name::<U>();
+    let ext = serde_json::from_value::<U>(val.clone())
+        .map_err(|source| Invalid::Malformed { type_name, source })?;
+    ext.validate()
+        .map_err(|source| Invalid::Validation { type_name, source })
+}
+
+/// A set of known [`Extension`]s, keyed by their namespace.
+#[derive(Clone, Debug)]
+pub struct Registry {
+    extensions: BTreeMap<Url, Descriptor>,
+}
+
+/// The [`Extension`]s defined by this crate are registered by default.
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            extensions: vec![
+                Descriptor::of::<Revocations>(),
+                Descriptor::of::<Contributor>(),
+            ]
+            .into_iter()
+            .map(|descriptor| (descriptor.namespace.clone(), descriptor))
+            .collect(),
+        }
+    }
+}
+
+impl Registry {
+    /// The [`Registry`] of the [`Extension`]s defined by this crate, see
+    /// [`Registry::default`].
+    pub fn builtin() -> &'static Self {
+        &BUILTIN
+    }
+
+    /// Register the [`Extension`] `U`.
+    ///
+    /// Registering the same type more than once is a no-op. It is an error to
+    /// register a different type under the same namespace, or to use the
+    /// namespace of a payload subject.
+    pub fn register<U: Extension>(&mut self) -> Result<(), Error> {
+        let namespace = U::namespace();
+        if Person::namespace_matches(namespace) || Project::namespace_matches(namespace) {
+            return Err(Error::ExtensionIsSubject);
+        }
+
+        match self.extensions.get(namespace) {
+            Some(known) if known.type_id == TypeId::of::<U>() => Ok(()),
+            Some(known) => Err(Error::Conflict {
+                namespace: namespace.clone(),
+                type_name: known.type_name,
+            }),
+            None => {
+                self.extensions
+                    .insert(namespace.clone(), Descriptor::of::<U>());
+                Ok(())
+            },
+        }
+    }
+
+    /// Look up the [`Extension`] registered under `namespace`.
+    pub fn lookup(&self, namespace: &Url) -> Option<&Descriptor> {
+        self.extensions.get(namespace)
+    }
+
+    /// All registered [`Extension`]s, ordered by namespace.
+    pub fn registered(&self) -> impl Iterator<Item = &Descriptor> {
+        self.extensions.values()
+    }
+
+    /// Validate `val` if an [`Extension`] is registered under `namespace`.
+    ///
+    /// `null` values are always accepted, as they are omitted when the payload
+    /// is serialised.
+    pub fn validate(&self, namespace: &Url, val: &serde_json::Value) -> Result<(), Invalid> {
+        if val.is_null() {
+            return Ok(());
+        }
+
+        match self.lookup(namespace) {
+            None => Ok(()),
+            Some(descriptor) => descriptor.validate(val),
+        }
+    }
+}
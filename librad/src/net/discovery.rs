@@ -14,6 +14,12 @@ use std::{
 
 use crate::peer::PeerId;
 
+pub mod address_book;
+pub use address_book::AddressBook;
+
+pub mod lan;
+pub use lan::Lan;
+
 pub trait Discovery {
     type Addr;
     type Stream: futures::Stream<Item = (PeerId, Vec<Self::Addr>)> + Send;
@@ -0,0 +1,344 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! A persistent [`Discovery`] of the peers we have seen before.
+//!
+//! The [`AddressBook`] records the addresses of peers we were connected to, as
+//! reported by the membership protocol (see [`AddressBook::observe`]), and how
+//! often we failed to stay connected to them. Peers merely claimed as
+//! providers by gossip are not recorded: the claim is unauthenticated, and
+//! could be used to flood the address book, evicting genuine peers. Addresses
+//! which have not been seen for [`Config::max_age`], and peers which failed
+//! [`Config::max_failures`] times in a row, are aged out. When [`saved`] to
+//! disk, the address book seeds the next startup, so a node can rejoin the
+//! network even if none of its bootstrap seeds are reachable.
+//!
+//! [`saved`]: AddressBook::save
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    vec,
+};
+
+use futures::{Stream, StreamExt as _};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::Discovery;
+use crate::{
+    net::protocol::{event::Upstream, membership::Transition, PeerInfo, RecvError},
+    peer::PeerId,
+};
+
+/// The current version of the on-disk format.
+pub const VERSION: u8 = 1;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("unsupported address book version {0}")]
+    Version(u8),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Persist(#[from] tempfile::PersistError),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Forget addresses which have not been seen for this long.
+    pub max_age: Duration,
+    /// Forget peers after this many consecutive failures.
+    pub max_failures: u32,
+    /// Retain at most this many peers, preferring the most recently seen ones.
+    pub max_peers: usize,
+    /// Write the address book to disk at most this often while
+    /// [`AddressBook::track`]ing events.
+    pub flush_interval: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_secs(30 * 24 * 60 * 60),
+            max_failures: 5,
+            max_peers: 1024,
+            flush_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// What we know about a peer.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    /// The addresses of the peer, along with the time (in seconds since the
+    /// UNIX epoch) they were last seen.
+    pub addrs: BTreeMap<SocketAddr, u64>,
+    /// The number of times in a row the peer was evicted from our membership
+    /// view since we were last connected to it.
+    pub failures: u32,
+}
+
+impl Entry {
+    /// The time (in seconds since the UNIX epoch) any of the addresses was
+    /// last seen.
+    pub fn last_seen(&self) -> u64 {
+        self.addrs.values().copied().max().unwrap_or(0)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Book {
+    version: u8,
+    peers: BTreeMap<PeerId, Entry>,
+}
+
+/// A [`Discovery`] of previously seen peers, which can be persisted to a file.
+///
+/// Cloning an [`AddressBook`] is cheap, and all clones share the same state.
+#[derive(Clone)]
+pub struct AddressBook {
+    path: PathBuf,
+    config: Config,
+    peers: Arc<RwLock<BTreeMap<PeerId, Entry>>>,
+}
+
+impl AddressBook {
+    /// Load the address book stored at `path`.
+    ///
+    /// If the file does not exist, the address book is empty. Note that the
+    /// file is not created until the address book is [`Self::save`]d.
+    pub fn load(path: impl Into<PathBuf>, config: Config) -> Result<Self, Error> {
+        let path = path.into();
+        let peers = match fs::File::open(&path) {
+            Ok(file) => {
+                let book: Book = serde_json::from_reader(io::BufReader::new(file))?;
+                if book.version != VERSION {
+                    return Err(Error::Version(book.version));
+                }
+                book.peers
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let this = Self {
+            path,
+            config,
+            peers: Arc::new(RwLock::new(peers)),
+        };
+        this.prune(SystemTime::now());
+        Ok(this)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Atomically write the address book to [`Self::path`].
+    pub fn save(&self) -> Result<(), Error> {
+        let dir = self.path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(dir)?;
+
+        let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+        {
+            let book = Book {
+                version: VERSION,
+                peers: self.peers.read().clone(),
+            };
+            serde_json::to_writer(io::BufWriter::new(tmp.as_file_mut()), &book)?;
+        }
+        tmp.as_file().sync_all()?;
+        tmp.persist(&self.path)?;
+
+        Ok(())
+    }
+
+    pub fn get(&self, peer: &PeerId) -> Option<Entry> {
+        self.peers.read().get(peer).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.read().is_empty()
+    }
+
+    /// Record that `peer` was seen at `addrs`.
+    ///
+    /// If `connected` is `true`, the failure count of the peer is reset.
+    pub fn seen<I>(&self, peer: PeerId, addrs: I, connected: bool)
+    where
+        I: IntoIterator<Item = SocketAddr>,
+    {
+        let now = secs(SystemTime::now());
+        let mut peers = self.peers.write();
+        let entry = peers.entry(peer).or_default();
+        for addr in addrs {
+            entry.addrs.insert(addr, now);
+        }
+        if connected {
+            entry.failures = 0;
+        }
+        if entry.addrs.is_empty() {
+            peers.remove(&peer);
+        }
+    }
+
+    /// Record that we failed to stay connected to `peer`.
+    ///
+    /// Unknown peers are ignored.
+    pub fn failed(&self, peer: &PeerId) {
+        let mut peers = self.peers.write();
+        if let Some(entry) = peers.get_mut(peer) {
+            entry.failures = entry.failures.saturating_add(1);
+            if entry.failures >= self.config.max_failures {
+                peers.remove(peer);
+            }
+        }
+    }
+
+    pub fn record_transition(&self, transition: &Transition<SocketAddr>) {
+        match transition {
+            Transition::Promoted(info) => {
+                let (peer, addrs): (PeerId, Vec<SocketAddr>) = info.clone().into();
+                self.seen(peer, addrs, true)
+            },
+            Transition::Demoted(info) => self.record_peer_info(info),
+            Transition::Evicted(info) => self.failed(&info.peer_id),
+        }
+    }
+
+    pub fn record_peer_info(&self, info: &PeerInfo<SocketAddr>) {
+        let (peer, addrs): (PeerId, Vec<SocketAddr>) = info.clone().into();
+        self.seen(peer, addrs, false)
+    }
+
+    /// Record the peer information carried by `event`, if it is a membership
+    /// [`Transition`].
+    ///
+    /// Returns `true` if the address book was updated.
+    pub fn observe(&self, event: &Upstream) -> bool {
+        match event {
+            Upstream::Membership(transition) => {
+                self.record_transition(transition);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Record the protocol `events` until the stream ends, writing the address
+    /// book to disk every [`Config::flush_interval`] if it changed, and once
+    /// more when the stream ends.
+    pub async fn track<S>(self, events: S) -> Result<(), Error>
+    where
+        S: Stream<Item = Result<Upstream, RecvError>>,
+    {
+        futures::pin_mut!(events);
+
+        let mut dirty = false;
+        let mut flushed = Instant::now();
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(event) => dirty |= self.observe(&event),
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!("address book lagged behind by {} events", n);
+                    continue;
+                },
+                Err(RecvError::Closed) => break,
+            }
+
+            if dirty && flushed.elapsed() >= self.config.flush_interval {
+                self.prune(SystemTime::now());
+                self.save()?;
+                dirty = false;
+                flushed = Instant::now();
+            }
+        }
+
+        self.prune(SystemTime::now());
+        self.save()
+    }
+
+    /// Age out addresses not seen since `now - max_age`, and apply the
+    /// [`Config`] limits.
+    pub fn prune(&self, now: SystemTime) {
+        let cutoff = secs(now).saturating_sub(self.config.max_age.as_secs());
+        let mut peers = self.peers.write();
+
+        for entry in peers.values_mut() {
+            entry.addrs.retain(|_, last_seen| *last_seen >= cutoff);
+        }
+        let max_failures = self.config.max_failures;
+        peers.retain(|_, entry| !entry.addrs.is_empty() && entry.failures < max_failures);
+
+        if peers.len() > self.config.max_peers {
+            let mut by_age = peers
+                .iter()
+                .map(|(peer, entry)| (entry.last_seen(), *peer))
+                .collect::<Vec<_>>();
+            by_age.sort_unstable();
+            let excess = by_age.len() - self.config.max_peers;
+            for (_, peer) in by_age.into_iter().take(excess) {
+                peers.remove(&peer);
+            }
+        }
+    }
+}
+
+impl Discovery for AddressBook {
+    type Addr = SocketAddr;
+    type Stream = futures::stream::Iter<vec::IntoIter<(PeerId, Vec<SocketAddr>)>>;
+
+    /// Yield the known peers, most recently seen first.
+    ///
+    /// The addresses of each peer are ordered the same way.
+    fn discover(self) -> Self::Stream {
+        let mut peers = self
+            .peers
+            .read()
+            .iter()
+            .map(|(peer, entry)| {
+                let mut addrs = entry.addrs.iter().collect::<Vec<_>>();
+                addrs.sort_by(|(_, a), (_, b)| b.cmp(a));
+                (
+                    entry.last_seen(),
+                    *peer,
+                    addrs.into_iter().map(|(addr, _)| *addr).collect(),
+                )
+            })
+            .collect::<Vec<(u64, PeerId, Vec<SocketAddr>)>>();
+        peers.sort_by(|(a, _, _), (b, _, _)| b.cmp(a));
+
+        futures::stream::iter(
+            peers
+                .into_iter()
+                .map(|(_, peer, addrs)| (peer, addrs))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+fn secs(t: SystemTime) -> u64 {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,394 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Discovery of peers on the local network.
+//!
+//! Peers periodically send a signed [`Announcement`] of their
+//! [`PeerAdvertisement`] to a UDP multicast group, and listen for the
+//! announcements of others. Announcements for a different [`Network`] are
+//! ignored, as are announcements which are not signed by the announced
+//! [`PeerId`], or which are not newer than the last one seen from that peer.
+//! The last announcement of a peer is remembered only until it becomes stale,
+//! and for a bounded number of peers.
+//!
+//! The multicast TTL defaults to `1`, so announcements do not leave the local
+//! network segment.
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_stream::stream;
+use futures::{stream::BoxStream, FutureExt as _};
+use minicbor::{Decode, Encode};
+use socket2::{Domain, Protocol, Socket, Type};
+use thiserror::Error;
+use tokio::net::UdpSocket;
+
+use super::Discovery;
+use crate::{
+    data::BoundedVec,
+    keys::Signature,
+    net::{protocol::PeerAdvertisement, Network},
+    peer::PeerId,
+    signer::Signer,
+};
+
+/// The default multicast group, from the IPv4 organization-local scope.
+pub const DEFAULT_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 76, 67);
+
+/// The default port of the multicast group.
+pub const DEFAULT_PORT: u16 = 17677;
+
+/// Upper bound of the size of an encoded [`Announcement`].
+const MAX_DATAGRAM_SIZE: usize = 1200;
+
+/// Upper bound of the number of peers whose last announcement is remembered.
+const MAX_SEEN: usize = 4096;
+
+/// How long to wait before receiving again after a receive error.
+const RECV_ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("invalid signature")]
+    InvalidSignature,
+
+    #[error("announcement exceeds {} bytes", MAX_DATAGRAM_SIZE)]
+    TooLarge,
+
+    #[error("failed to sign announcement")]
+    Sign(#[source] Box<dyn std::error::Error + Send + Sync + 'static>),
+
+    #[error(transparent)]
+    Encode(#[from] minicbor::encode::Error<io::Error>),
+
+    #[error(transparent)]
+    Decode(#[from] minicbor::decode::Error),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// The multicast group to announce to, and receive announcements from.
+    pub group: Ipv4Addr,
+    /// The port of the multicast group.
+    pub port: u16,
+    /// The local interface to join the group on.
+    ///
+    /// [`Ipv4Addr::UNSPECIFIED`] lets the OS choose.
+    pub interface: Ipv4Addr,
+    /// The multicast TTL.
+    pub ttl: u32,
+    /// Send an [`Announcement`] this often.
+    pub interval: Duration,
+    /// Ignore announcements whose timestamp differs from the local clock by
+    /// more than this.
+    pub max_skew: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            group: DEFAULT_GROUP,
+            port: DEFAULT_PORT,
+            interface: Ipv4Addr::UNSPECIFIED,
+            ttl: 1,
+            interval: Duration::from_secs(10),
+            max_skew: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// The payload of a LAN announcement.
+///
+/// # Wire Encoding
+///
+/// The announcement is CBOR-encoded, and sent as a 2-element CBOR array of
+/// the encoded announcement (as a byte string) and the signature over it.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+#[cbor(array)]
+pub struct Announcement {
+    #[n(0)]
+    pub peer_id: PeerId,
+
+    /// The identifier of the logical [`Network`] the peer participates in,
+    /// empty for [`Network::Main`].
+    #[n(1)]
+    #[cbor(with = "minicbor::bytes")]
+    pub network: Vec<u8>,
+
+    #[n(2)]
+    pub advertisement: PeerAdvertisement<SocketAddr>,
+
+    /// Seconds since the UNIX epoch.
+    #[n(3)]
+    pub timestamp: u64,
+}
+
+#[derive(Encode, Decode)]
+#[cbor(array)]
+struct Signed {
+    #[n(0)]
+    #[cbor(with = "minicbor::bytes")]
+    announcement: Vec<u8>,
+
+    #[n(1)]
+    signature: Signature,
+}
+
+impl Announcement {
+    pub fn new(peer_id: PeerId, network: &Network, listen_addrs: &[SocketAddr]) -> Self {
+        let mut addrs = BoundedVec::from(std::iter::empty());
+        addrs.extend_fill(listen_addrs.iter().copied());
+        Self {
+            peer_id,
+            network: network_id(network),
+            advertisement: PeerAdvertisement {
+                listen_addrs: addrs,
+                capabilities: Default::default(),
+            },
+            timestamp: now(),
+        }
+    }
+
+    /// Encode and sign the announcement with `signer`, which must be the
+    /// signer of [`Self::peer_id`].
+    pub async fn sign<S>(&self, signer: &S) -> Result<Vec<u8>, Error>
+    where
+        S: Signer,
+    {
+        let announcement = minicbor::to_vec(self)?;
+        let signature = signer
+            .sign(&announcement)
+            .await
+            .map(Signature::from)
+            .map_err(|e| Error::Sign(Box::new(e)))?;
+        let signed = minicbor::to_vec(Signed {
+            announcement,
+            signature,
+        })?;
+        if signed.len() > MAX_DATAGRAM_SIZE {
+            Err(Error::TooLarge)
+        } else {
+            Ok(signed)
+        }
+    }
+
+    /// Decode a signed announcement, and verify its signature.
+    pub fn verify(bytes: &[u8]) -> Result<Self, Error> {
+        let Signed {
+            announcement,
+            signature,
+        } = minicbor::decode(bytes)?;
+        let this: Self = minicbor::decode(&announcement)?;
+        if signature.verify(&announcement, this.peer_id.as_public_key()) {
+            Ok(this)
+        } else {
+            Err(Error::InvalidSignature)
+        }
+    }
+
+    /// The advertised listen addresses, where unspecified IP addresses are
+    /// replaced by the IP of `sender`.
+    pub fn addrs(&self, sender: SocketAddr) -> Vec<SocketAddr> {
+        self.advertisement
+            .listen_addrs
+            .iter()
+            .map(|addr| {
+                if addr.ip().is_unspecified() {
+                    SocketAddr::new(sender.ip(), addr.port())
+                } else {
+                    *addr
+                }
+            })
+            .collect()
+    }
+}
+
+/// A [`Discovery`] of peers announcing themselves on the local network.
+///
+/// The local peer is announced with the `listen_addrs` it was constructed
+/// with, typically the ones of the bound endpoint (see
+/// [`crate::net::peer::Peer::bind`]).
+#[derive(Clone)]
+pub struct Lan<S> {
+    signer: S,
+    network: Network,
+    listen_addrs: Vec<SocketAddr>,
+    config: Config,
+}
+
+impl<S> Lan<S>
+where
+    S: Signer + Clone,
+{
+    pub fn new(signer: S, network: Network, listen_addrs: Vec<SocketAddr>, config: Config) -> Self {
+        Self {
+            signer,
+            network,
+            listen_addrs,
+            config,
+        }
+    }
+
+    fn bind(&self) -> io::Result<UdpSocket> {
+        let Config {
+            group,
+            port,
+            interface,
+            ttl,
+            ..
+        } = self.config;
+
+        let sock = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        // Allow other peers on the same host to join the group
+        sock.set_reuse_address(true)?;
+        sock.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)).into())?;
+        sock.join_multicast_v4(&group, &interface)?;
+        sock.set_multicast_if_v4(&interface)?;
+        sock.set_multicast_ttl_v4(ttl)?;
+        sock.set_multicast_loop_v4(true)?;
+        sock.set_nonblocking(true)?;
+
+        UdpSocket::from_std(sock.into())
+    }
+
+    /// Verify the announcement carried by `datagram`, and return the peer and
+    /// its addresses if it is acceptable.
+    fn accept(
+        &self,
+        local_id: PeerId,
+        seen: &mut HashMap<PeerId, u64>,
+        datagram: &[u8],
+        sender: SocketAddr,
+    ) -> Option<(PeerId, Vec<SocketAddr>)> {
+        let ann = match Announcement::verify(datagram) {
+            Ok(ann) => ann,
+            Err(e) => {
+                tracing::debug!(err = %e, %sender, "invalid LAN announcement");
+                return None;
+            },
+        };
+
+        if ann.peer_id == local_id || ann.network != network_id(&self.network) {
+            return None;
+        }
+        let now = now();
+        let skew = if now > ann.timestamp {
+            now - ann.timestamp
+        } else {
+            ann.timestamp - now
+        };
+        if skew > self.config.max_skew.as_secs() {
+            tracing::debug!(peer = %ann.peer_id, %sender, "stale LAN announcement");
+            return None;
+        }
+        match seen.get(&ann.peer_id) {
+            Some(prev) if *prev >= ann.timestamp => return None,
+            Some(_) => {},
+            None if seen.len() >= MAX_SEEN => {
+                self.expire(seen, now);
+                if seen.len() >= MAX_SEEN {
+                    tracing::debug!(peer = %ann.peer_id, %sender, "too many LAN peers");
+                    return None;
+                }
+            },
+            None => {},
+        }
+        seen.insert(ann.peer_id, ann.timestamp);
+
+        let addrs = ann.addrs(sender);
+        if addrs.is_empty() {
+            None
+        } else {
+            Some((ann.peer_id, addrs))
+        }
+    }
+
+    /// Forget the peers whose last announcement would now be rejected as
+    /// stale anyway.
+    fn expire(&self, seen: &mut HashMap<PeerId, u64>, now: u64) {
+        let cutoff = now.saturating_sub(self.config.max_skew.as_secs());
+        seen.retain(|_, timestamp| *timestamp >= cutoff);
+    }
+}
+
+impl<S> Discovery for Lan<S>
+where
+    S: Signer + Clone,
+{
+    type Addr = SocketAddr;
+    type Stream = BoxStream<'static, (PeerId, Vec<SocketAddr>)>;
+
+    fn discover(self) -> Self::Stream {
+        Box::pin(stream! {
+            let socket = match self.bind() {
+                Ok(socket) => socket,
+                Err(e) => {
+                    tracing::error!(err = %e, "failed to join LAN discovery group");
+                    return;
+                },
+            };
+            let group = SocketAddrV4::new(self.config.group, self.config.port);
+            let local_id = PeerId::from_signer(&self.signer);
+
+            let mut seen = HashMap::new();
+            let mut interval = tokio::time::interval(self.config.interval);
+            let mut buf = [0; MAX_DATAGRAM_SIZE];
+            loop {
+                let recv = {
+                    let tick = interval.tick().fuse();
+                    let recv = socket.recv_from(&mut buf).fuse();
+                    futures::pin_mut!(tick, recv);
+                    futures::select! {
+                        _ = tick => None,
+                        recv = recv => Some(recv),
+                    }
+                };
+
+                match recv {
+                    None => {
+                        self.expire(&mut seen, now());
+                        let ann = Announcement::new(local_id, &self.network, &self.listen_addrs);
+                        let sent = match ann.sign(&self.signer).await {
+                            Ok(datagram) => socket.send_to(&datagram, group).await.map(|_| ()),
+                            Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+                        };
+                        if let Err(e) = sent {
+                            tracing::warn!(err = %e, "failed to send LAN announcement");
+                        }
+                    },
+                    Some(Ok((n, sender))) => {
+                        if let Some(found) = self.accept(local_id, &mut seen, &buf[..n], sender) {
+                            yield found
+                        }
+                    },
+                    Some(Err(e)) => {
+                        tracing::warn!(err = %e, "LAN discovery receive error");
+                        tokio::time::sleep(RECV_ERROR_BACKOFF).await;
+                    },
+                }
+            }
+        })
+    }
+}
+
+fn network_id(network: &Network) -> Vec<u8> {
+    match network {
+        Network::Main => vec![],
+        Network::Custom(id) => id.to_vec(),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -11,7 +11,13 @@ use futures_timer::Delay;
 use super::protocol::{self, gossip};
 use crate::{
     executor,
-    git::{self, storage::Fetchers, Urn},
+    git::{
+        self,
+        identities::local::LocalIdentity,
+        replication,
+        storage::{fetcher, Fetchers},
+        Urn,
+    },
     signer::Signer,
     PeerId,
 };
@@ -252,6 +258,103 @@ where
         Ok(self.spawner.blocking(move || blocking(&storage)).await)
     }
 
+    /// Replicate `urn` from the given peer, see [`replication::replicate`].
+    ///
+    /// If `config` is `None`, the replication config of the protocol is used.
+    ///
+    /// The progress of each fetch is reported to [`Self::subscribe`]rs as
+    /// [`event::upstream::Replication::Progress`] events.
+    pub async fn replicate<C>(
+        &self,
+        from: impl Into<(PeerId, Vec<SocketAddr>)>,
+        urn: Urn,
+        config: C,
+        whoami: Option<LocalIdentity>,
+    ) -> Result<replication::ReplicateResult, error::Replicate>
+    where
+        C: Into<Option<replication::Config>>,
+    {
+        let (remote_peer, addr_hints) = from.into();
+        let config = config.into().unwrap_or(self.config.protocol.replication);
+        let phone = self.phone.clone();
+        self.using_storage(move |storage| {
+            let fetcher = fetcher::PeerToPeer::new(urn.clone(), remote_peer, addr_hints)
+                .build(storage)
+                .map_err(error::Replicate::MkFetcher)??
+                .on_progress(move |progress| {
+                    phone.emit(event::upstream::Replication::Progress {
+                        urn: urn.clone(),
+                        remote_peer,
+                        progress,
+                    })
+                });
+            Ok(replication::replicate(storage, fetcher, config, whoami)?)
+        })
+        .await?
+    }
+
+    /// Synchronise `urns` with the given peer, see [`protocol::sync`].
+    ///
+    /// Only the URNs whose [`protocol::sync::Digest`] diverges from the
+    /// remote peer's are replicated. Failing to replicate a URN does not abort
+    /// the sync, but is recorded in [`protocol::sync::Summary::failed`].
+    ///
+    /// If the remote peer fails to answer the `SYNC` request (eg. because it
+    /// does not support it), all `urns` are replicated.
+    pub async fn sync(
+        &self,
+        from: impl Into<(PeerId, Vec<SocketAddr>)>,
+        urns: Vec<Urn>,
+    ) -> Result<protocol::sync::Summary, error::Sync> {
+        use protocol::sync::{Digest, Summary, MAX_URNS};
+
+        let (remote_peer, addr_hints) = from.into();
+        let digests = self
+            .using_read_only(move |storage| {
+                urns.iter()
+                    .map(|urn| Digest::tracked(storage, urn))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .await??;
+
+        let mut summary = Summary::default();
+        for chunk in digests.chunks(MAX_URNS) {
+            let (diverged, unknown) = match self
+                .phone
+                .sync((remote_peer, addr_hints.clone()), chunk.to_vec())
+                .await
+            {
+                Ok(resp) => resp,
+                Err(e @ protocol::error::Sync::Rpc(_))
+                | Err(e @ protocol::error::Sync::NoResponse(_)) => {
+                    tracing::warn!(err = ?e, "sync request failed, replicating all urns");
+                    (chunk.to_vec(), vec![])
+                },
+                Err(e) => return Err(e.into()),
+            };
+            for Digest { urn, .. } in chunk {
+                if unknown.contains(urn) {
+                    summary.unknown.push(urn.clone());
+                } else if !diverged.iter().any(|theirs| &theirs.urn == urn) {
+                    summary.in_sync.push(urn.clone());
+                } else {
+                    match self
+                        .replicate((remote_peer, addr_hints.clone()), urn.clone(), None, None)
+                        .await
+                    {
+                        Ok(_) => summary.replicated.push(urn.clone()),
+                        Err(e) => {
+                            tracing::warn!(err = ?e, %urn, "failed to replicate diverged urn");
+                            summary.failed.push(urn.clone())
+                        },
+                    }
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
     /// Borrow a [`git::storage::ReadOnly`] from the pool, and run a blocking
     /// computation on it.
     pub async fn using_read_only<F, A>(&self, blocking: F) -> Result<A, error::Storage>
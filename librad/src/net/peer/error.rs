@@ -5,7 +5,10 @@
 
 use thiserror::Error;
 
-use crate::{git::storage, net::protocol::cache};
+use crate::{
+    git::{replication, storage, tracking},
+    net::protocol::{self, cache},
+};
 
 #[derive(Debug, Error)]
 #[non_exhaustive]
@@ -23,6 +26,33 @@ impl From<storage::PoolError> for Storage {
     }
 }
 
+#[derive(Debug, Error)]
+pub enum Replicate {
+    #[error(transparent)]
+    Concurrent(#[from] storage::fetcher::Info),
+
+    #[error("unable to create fetcher")]
+    MkFetcher(#[source] git2::Error),
+
+    #[error(transparent)]
+    Replication(#[from] replication::Error),
+
+    #[error(transparent)]
+    Storage(#[from] Storage),
+}
+
+#[derive(Debug, Error)]
+pub enum Sync {
+    #[error(transparent)]
+    Protocol(#[from] protocol::error::Sync),
+
+    #[error(transparent)]
+    Tracking(#[from] tracking::Error),
+
+    #[error(transparent)]
+    Storage(#[from] Storage),
+}
+
 #[derive(Debug, Error)]
 pub enum Init {
     #[error("no async context found, try calling `.enter()` on the runtime")]
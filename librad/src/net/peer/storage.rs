@@ -130,11 +130,19 @@ impl Storage {
             .await
     }
 
+    /// Determine if `peer` is tracked in the context of `urn`, either
+    /// explicitly or because any peer is tracked (see
+    /// [`tracking::track_any`]).
     async fn is_tracked(&self, urn: Urn, peer: PeerId) -> Result<bool, Error> {
         let git = self.pool.get().await?;
         Ok(self
             .spawner
-            .blocking(move || tracking::is_tracked(&git, &urn, peer))
+            .blocking(move || {
+                Ok::<_, tracking::Error>(
+                    tracking::is_tracked(&git, &urn, peer)?
+                        || tracking::is_tracked_any(&git, &urn)?,
+                )
+            })
             .await?)
     }
 }
@@ -43,6 +43,8 @@ pub mod gossip;
 pub mod interrogation;
 pub mod io;
 pub mod membership;
+pub mod relay;
+pub mod sync;
 
 mod info;
 pub use info::{Capability, PartialPeerInfo, PeerAdvertisement, PeerInfo};
@@ -59,7 +61,7 @@ pub use tincans::{Interrogation, RecvError};
 
 mod state;
 pub use state::Quota;
-use state::{RateLimits, State, StateConfig, Storage};
+use state::{RateLimits, RelayLimits, State, StateConfig, Storage};
 
 pub type Endpoint = quic::Endpoint<2>;
 
@@ -73,6 +75,14 @@ pub struct Config {
     pub replication: replication::Config,
     pub fetch: config::Fetch,
     pub rate_limits: Quota,
+    /// Forward streams between connected peers, and advertise
+    /// [`Capability::Relay`], see [`relay`].
+    ///
+    /// Only useful for peers which are reachable from the outside. Peers which
+    /// can't decode unknown capabilities reject the advertisements of a peer
+    /// with this enabled, so it should only be turned on once the network has
+    /// upgraded.
+    pub relay: bool,
     // TODO: transport, ...
 }
 
@@ -187,6 +197,7 @@ where
             config.rate_limits.membership,
             nonzero!(1024 * 1024usize),
         )),
+        relay: RelayLimits::new(config.rate_limits.relay),
     };
 
     let state = State {
@@ -199,6 +210,7 @@ where
         config: StateConfig {
             replication: config.replication,
             fetch: config.fetch,
+            relay: config.relay,
         },
         nonces,
         caches,
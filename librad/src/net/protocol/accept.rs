@@ -52,7 +52,7 @@ where
                             to: info,
                             message: state
                                 .membership
-                                .hello(io::peer_advertisement(&state.endpoint)())
+                                .hello(io::peer_advertisement(&state)())
                                 .into(),
                         })
                         .collect::<Vec<_>>(),
@@ -70,7 +70,7 @@ where
                     message: membership::Message::Shuffle {
                         origin: PeerInfo {
                             peer_id: state.local_id,
-                            advertised_info: io::peer_advertisement(&state.endpoint)(),
+                            advertised_info: io::peer_advertisement(&state)(),
                             seen_addrs: iter::empty().into(),
                         },
                         peers: sample,
@@ -135,6 +135,12 @@ where
                 Downstream::Interrogation(inter) => {
                     control::interrogation(state.clone(), inter).await
                 },
+                // The responder may take a while to compute its digests, so
+                // don't hold up ground control
+                Downstream::Sync(sync) => state
+                    .spawner
+                    .spawn(control::sync(state.clone(), sync))
+                    .detach(),
             },
         }
     }
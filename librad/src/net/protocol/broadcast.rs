@@ -169,9 +169,10 @@ where
                             message: reply().into(),
                         }]
                     } else {
-                        // FIXME: if we cannot reach origin, we may still want to
-                        // broadcast the `Have`, in the hopes that it will travel
-                        // back the path it came here
+                        // FIXME: if we cannot reach origin, neither directly
+                        // nor via a relay, we may still want to broadcast the
+                        // `Have`, in the hopes that it will travel back the path
+                        // it came here
                         vec![AttemptSend {
                             to: origin,
                             message: reply().into(),
@@ -21,7 +21,7 @@ pub(super) async fn gossip<S>(
 
     let origin = PeerInfo {
         peer_id: state.local_id,
-        advertised_info: io::peer_advertisement(&state.endpoint)(),
+        advertised_info: io::peer_advertisement(state)(),
         seen_addrs: iter::empty().into(),
     };
     // TODO: answer `Want`s from a provider cache
@@ -115,3 +115,26 @@ pub(super) async fn interrogation<S>(
         tx.send(resp).ok();
     }
 }
+
+pub(super) async fn sync<S>(
+    state: State<S>,
+    event::downstream::Sync {
+        peer: (peer, addr_hints),
+        request,
+        reply,
+    }: event::downstream::Sync,
+) where
+    S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + Clone + 'static,
+{
+    let chan = reply.lock().take();
+    if let Some(tx) = chan {
+        let resp = match state.connection(peer, addr_hints).await {
+            None => Err(error::Sync::NoConnection(peer)),
+            Some(conn) => match io::send::request(&conn, request).await {
+                Err(e) => Err(e.into()),
+                Ok(resp) => resp.ok_or(error::Sync::NoResponse(peer)),
+            },
+        };
+        tx.send(resp).ok();
+    }
+}
@@ -7,7 +7,7 @@ use std::fmt::Debug;
 
 use thiserror::Error;
 
-use super::interrogation;
+use super::{interrogation, sync};
 use crate::{git::storage::pool::PoolError, net::quic, PeerId};
 
 mod internal;
@@ -50,3 +50,28 @@ impl From<internal::Rpc<quic::BidiStream>> for Interrogation {
         Self::Rpc(Box::new(e))
     }
 }
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Sync {
+    #[error("unable to obtain a connection to {0}")]
+    NoConnection(PeerId),
+
+    #[error("no response from {0}")]
+    NoResponse(PeerId),
+
+    #[error("error response: {0:?}")]
+    ErrorResponse(sync::Error),
+
+    #[error("network stack not available")]
+    Unavailable,
+
+    #[error(transparent)]
+    Rpc(#[from] Box<internal::Rpc<quic::BidiStream>>),
+}
+
+impl From<internal::Rpc<quic::BidiStream>> for Sync {
+    fn from(e: internal::Rpc<quic::BidiStream>) -> Self {
+        Self::Rpc(Box::new(e))
+    }
+}
@@ -5,7 +5,7 @@
 
 use std::{collections::HashMap, net::SocketAddr};
 
-use super::{broadcast, cache, error, gossip, interrogation, membership};
+use super::{broadcast, cache, error, gossip, interrogation, membership, sync};
 use crate::PeerId;
 
 #[derive(Clone)]
@@ -13,6 +13,7 @@ pub enum Downstream {
     Gossip(downstream::Gossip),
     Info(downstream::Info),
     Interrogation(downstream::Interrogation),
+    Sync(downstream::Sync),
 }
 
 pub mod downstream {
@@ -74,6 +75,13 @@ pub mod downstream {
         pub reply:
             Reply<Result<interrogation::Response<'static, SocketAddr>, error::Interrogation>>,
     }
+
+    #[derive(Clone)]
+    pub struct Sync {
+        pub peer: (PeerId, Vec<SocketAddr>),
+        pub request: sync::Request,
+        pub reply: Reply<Result<sync::Response, error::Sync>>,
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -83,6 +91,7 @@ pub enum Upstream {
     Gossip(Box<upstream::Gossip<SocketAddr, gossip::Payload>>),
     Membership(membership::Transition<SocketAddr>),
     Caches(upstream::Caches),
+    Replication(upstream::Replication),
 }
 
 pub mod upstream {
@@ -94,7 +103,10 @@ pub mod upstream {
     use futures_timer::Delay;
     use thiserror::Error;
 
-    use crate::net::protocol::{PeerInfo, RecvError};
+    use crate::{
+        git::{fetch, Urn},
+        net::protocol::{PeerInfo, RecvError},
+    };
 
     #[derive(Clone, Debug)]
     pub enum Endpoint {
@@ -151,6 +163,24 @@ pub mod upstream {
         }
     }
 
+    /// Events of replications initiated via [`crate::net::peer::Peer`].
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    pub enum Replication {
+        /// A fetch of `urn` from `remote_peer` made progress.
+        Progress {
+            urn: Urn,
+            remote_peer: PeerId,
+            progress: fetch::Progress,
+        },
+    }
+
+    impl From<Replication> for Upstream {
+        fn from(r: Replication) -> Self {
+            Self::Replication(r)
+        }
+    }
+
     #[derive(Debug, Error)]
     pub enum ExpectError {
         #[error("timeout waiting for matching event")]
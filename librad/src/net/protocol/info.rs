@@ -16,6 +16,10 @@ use crate::peer::PeerId;
 pub enum Capability {
     #[n(0)]
     Reserved = 0,
+    /// The peer forwards streams to other peers it is connected to, see
+    /// [`crate::net::protocol::relay`].
+    #[n(1)]
+    Relay = 1,
 }
 
 pub type PeerInfo<Addr> = GenericPeerInfo<Addr, PeerAdvertisement<Addr>>;
@@ -182,7 +186,7 @@ impl<'__b777, Addr: minicbor::Decode<'__b777>> minicbor::Decode<'__b777>
             for __i777 in 0..__len777 {
                 match __i777 {
                     0 => listen_addrs = Some(radicle_data::bounded::decode_truncate(__d777)?),
-                    2 => capabilities = Some(minicbor::Decode::decode(__d777)?),
+                    2 => capabilities = Some(decode_capabilities(__d777)?),
                     _ => __d777.skip()?,
                 }
             }
@@ -191,7 +195,7 @@ impl<'__b777, Addr: minicbor::Decode<'__b777>> minicbor::Decode<'__b777>
             while minicbor::data::Type::Break != __d777.datatype()? {
                 match __i777 {
                     0 => listen_addrs = Some(radicle_data::bounded::decode_truncate(__d777)?),
-                    2 => capabilities = Some(minicbor::Decode::decode(__d777)?),
+                    2 => capabilities = Some(decode_capabilities(__d777)?),
                     _ => __d777.skip()?,
                 }
                 __i777 += 1
@@ -218,6 +222,46 @@ impl<'__b777, Addr: minicbor::Decode<'__b777>> minicbor::Decode<'__b777>
         })
     }
 }
+/// Decode the advertised capabilities, skipping the ones we don't know about.
+///
+/// Peers may advertise capabilities introduced after this version, which must
+/// not prevent us from decoding the rest of their advertisement.
+fn decode_capabilities(
+    d: &mut minicbor::Decoder,
+) -> Result<BTreeSet<Capability>, minicbor::decode::Error> {
+    fn decode_one(
+        d: &mut minicbor::Decoder,
+        caps: &mut BTreeSet<Capability>,
+    ) -> Result<(), minicbor::decode::Error> {
+        let pos = d.position();
+        match Capability::decode(d) {
+            Ok(cap) => {
+                caps.insert(cap);
+            },
+            Err(minicbor::decode::Error::UnknownVariant(_)) => {
+                d.set_position(pos);
+                d.skip()?;
+            },
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    let mut caps = BTreeSet::new();
+    if let Some(len) = d.array()? {
+        for _ in 0..len {
+            decode_one(d, &mut caps)?
+        }
+    } else {
+        while minicbor::data::Type::Break != d.datatype()? {
+            decode_one(d, &mut caps)?
+        }
+        d.skip()?
+    }
+
+    Ok(caps)
+}
+
 impl<Addr> PeerAdvertisement<Addr> {
     pub fn new(listen_addr: Addr) -> Self {
         Self {
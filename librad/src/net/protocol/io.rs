@@ -3,15 +3,14 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{iter, net::SocketAddr};
+use std::{collections::BTreeSet, iter, net::SocketAddr};
 
 use data::BoundedVec;
 
 use super::{
     gossip,
-    info::{PartialPeerInfo, PeerAdvertisement},
+    info::{Capability, PartialPeerInfo, PeerAdvertisement},
     membership,
-    Endpoint,
     ProtocolStorage,
     State,
 };
@@ -28,7 +27,7 @@ pub mod graft;
 pub(super) mod recv;
 
 pub mod send;
-pub use send::{rpc::Rpc, send_rpc};
+pub use send::{rpc::Rpc, send_relayed, send_rpc};
 
 pub(super) mod streams;
 
@@ -42,13 +41,8 @@ where
     }
 
     if let Some((conn, ingress)) = connect(&state.endpoint, peer, addrs).await {
-        let rpc_sent = send_rpc::<_, ()>(
-            &conn,
-            state
-                .membership
-                .hello(peer_advertisement(&state.endpoint)()),
-        )
-        .await;
+        let rpc_sent =
+            send_rpc::<_, ()>(&conn, state.membership.hello(peer_advertisement(&state)())).await;
 
         match rpc_sent {
             Err(e) => tracing::warn!(err = ?e, "failed to send membership hello"),
@@ -64,7 +58,7 @@ where
                 state
                     .tick(membership::tocks(
                         &state.membership,
-                        peer_advertisement(&state.endpoint),
+                        peer_advertisement(&state),
                         ticks,
                     ))
                     .await;
@@ -77,15 +71,19 @@ where
     }
 }
 
-pub(super) fn peer_advertisement(
-    endpoint: &Endpoint,
+pub(super) fn peer_advertisement<S>(
+    state: &State<S>,
 ) -> impl Fn() -> PeerAdvertisement<SocketAddr> + '_ {
     move || {
         let mut listen_addrs = BoundedVec::from(iter::empty());
-        listen_addrs.extend_fill(endpoint.listen_addrs());
+        listen_addrs.extend_fill(state.endpoint.listen_addrs());
+        let mut capabilities = BTreeSet::new();
+        if state.config.relay {
+            capabilities.insert(Capability::Relay);
+        }
         PeerAdvertisement {
             listen_addrs,
-            capabilities: Default::default(),
+            capabilities,
         }
     }
 }
@@ -4,7 +4,7 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 mod git;
-pub(in crate::net::protocol) use git::git;
+pub(in crate::net::protocol) use git::{git, relayed_git};
 
 mod gossip;
 pub(in crate::net::protocol) use gossip::gossip;
@@ -14,3 +14,9 @@ pub(in crate::net::protocol) use interrogation::interrogation;
 
 mod membership;
 pub(in crate::net::protocol) use membership::{connection_lost, membership};
+
+mod relay;
+pub(in crate::net::protocol) use relay::relay;
+
+mod sync;
+pub(in crate::net::protocol) use sync::sync;
@@ -32,6 +32,32 @@ enum Error {
 }
 
 pub(in crate::net::protocol) async fn git<S, T>(state: State<S>, stream: Upgraded<upgrade::Git, T>)
+where
+    S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + Clone + 'static,
+    T: Duplex + RemoteInfo<Addr = SocketAddr>,
+    <T as Duplex>::Read: AsyncRead + Send + Sync + Unpin + 'static,
+    <T as Duplex>::Write: AsyncWrite + Send + Sync + Unpin + 'static,
+{
+    serve(state, stream, true).await
+}
+
+/// Serve a git stream received via a relay.
+///
+/// The remote peer of a relayed stream is only claimed by the relay, so we
+/// don't rere from it.
+pub(in crate::net::protocol) async fn relayed_git<S, T>(
+    state: State<S>,
+    stream: Upgraded<upgrade::Git, T>,
+) where
+    S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + Clone + 'static,
+    T: Duplex + RemoteInfo<Addr = SocketAddr>,
+    <T as Duplex>::Read: AsyncRead + Send + Sync + Unpin + 'static,
+    <T as Duplex>::Write: AsyncWrite + Send + Sync + Unpin + 'static,
+{
+    serve(state, stream, false).await
+}
+
+async fn serve<S, T>(state: State<S>, stream: Upgraded<upgrade::Git, T>, may_rere: bool)
 where
     S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + Clone + 'static,
     T: Duplex + RemoteInfo<Addr = SocketAddr>,
@@ -50,7 +76,7 @@ where
                 .run()
                 .err_into::<Error>()
                 .and_then(|()| async {
-                    if let Some(n) = nonce {
+                    if let Some(n) = nonce.filter(|_| may_rere) {
                         // Only rere if we have a fresh nonce
                         if !state.nonces.contains(&n) {
                             return rere(state.clone(), repo, remote_peer, remote_addr).await;
@@ -51,7 +51,7 @@ pub(in crate::net::protocol) async fn gossip<S, T>(
                 state
                     .tick(membership::tocks(
                         &state.membership,
-                        peer_advertisement(&state.endpoint),
+                        peer_advertisement(&state),
                         ticks,
                     ))
                     .await;
@@ -62,7 +62,7 @@ pub(in crate::net::protocol) async fn gossip<S, T>(
             Ok(msg) => {
                 let peer_info = || PeerInfo {
                     peer_id: state.local_id,
-                    advertised_info: peer_advertisement(&state.endpoint)(),
+                    advertised_info: peer_advertisement(&state)(),
                     seen_addrs: iter::empty().into(),
                 };
                 match broadcast::apply(&state.membership, &state.storage, peer_info, remote_id, msg)
@@ -79,7 +79,7 @@ pub(in crate::net::protocol) async fn gossip<S, T>(
                         state
                             .tick(membership::tocks(
                                 &state.membership,
-                                peer_advertisement(&state.endpoint),
+                                peer_advertisement(&state),
                                 Some(disconnect(remote_id)),
                             ))
                             .await;
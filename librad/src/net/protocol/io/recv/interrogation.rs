@@ -23,7 +23,7 @@ use crate::{
             cache,
             interrogation::{self, Request, Response},
             io::{self, codec},
-            Endpoint,
+            PeerAdvertisement,
             State,
         },
         upgrade::{self, Upgraded},
@@ -63,14 +63,19 @@ pub(in crate::net::protocol) async fn interrogation<S, T>(
         match x {
             Err(e) => tracing::warn!(err = ?e, "interrogation recv error"),
             Ok(req) => {
-                let resp = handle_request(&state.endpoint, &state.caches.urns, remote_addr, req)
-                    .map(Cow::from)
-                    .unwrap_or_else(|e| {
-                        tracing::error!(err = ?e, "error handling request");
-                        match e {
-                            Error::Cbor(_) => Cow::from(&*INTERNAL_ERROR),
-                        }
-                    });
+                let resp = handle_request(
+                    io::peer_advertisement(&state),
+                    &state.caches.urns,
+                    remote_addr,
+                    req,
+                )
+                .map(Cow::from)
+                .unwrap_or_else(|e| {
+                    tracing::error!(err = ?e, "error handling request");
+                    match e {
+                        Error::Cbor(_) => Cow::from(&*INTERNAL_ERROR),
+                    }
+                });
 
                 if let Err(e) = send.into_sink().send(resp).await {
                     tracing::warn!(err = ?e, "interrogation send error")
@@ -81,7 +86,7 @@ pub(in crate::net::protocol) async fn interrogation<S, T>(
 }
 
 fn handle_request(
-    endpoint: &Endpoint,
+    advertisement: impl Fn() -> PeerAdvertisement<SocketAddr>,
     urns: &cache::urns::Filter,
     remote_addr: SocketAddr,
     req: interrogation::Request,
@@ -89,9 +94,7 @@ fn handle_request(
     use either::Either::*;
 
     match req {
-        Request::GetAdvertisement => {
-            Left(Response::Advertisement(io::peer_advertisement(endpoint)()))
-        },
+        Request::GetAdvertisement => Left(Response::Advertisement(advertisement())),
         Request::EchoAddr => Left(Response::YourAddr(remote_addr)),
         Request::GetUrns => {
             let urns = urns.get();
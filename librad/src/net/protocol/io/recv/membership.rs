@@ -62,7 +62,7 @@ pub(in crate::net::protocol) async fn membership<S, T>(
 
                     let disconnect = membership::tocks(
                         &state.membership,
-                        peer_advertisement(&state.endpoint),
+                        peer_advertisement(&state),
                         Some(membership::Tick::Reply {
                             to: remote_id,
                             message: membership::Message::Disconnect,
@@ -79,7 +79,7 @@ pub(in crate::net::protocol) async fn membership<S, T>(
 
                 match membership::apply(
                     &state.membership,
-                    peer_advertisement(&state.endpoint),
+                    peer_advertisement(&state),
                     remote_id,
                     remote_addr,
                     msg,
@@ -108,7 +108,7 @@ where
     state
         .tick(membership::tocks(
             &state.membership,
-            peer_advertisement(&state.endpoint),
+            peer_advertisement(&state),
             ticks,
         ))
         .await
@@ -0,0 +1,217 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{io, net::SocketAddr, time::Duration};
+
+use futures::{
+    future::{self, TryFutureExt as _},
+    io::{AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _},
+};
+use futures_timer::Delay;
+
+use crate::{
+    net::{
+        connection::{CloseReason, Duplex as _, RemotePeer as _},
+        protocol::{
+            gossip,
+            io::recv,
+            relay::{self, Header, Relayed},
+            ProtocolStorage,
+            RelayLimits,
+            State,
+        },
+        quic,
+        upgrade::{self, UpgradeRequest, Upgraded},
+    },
+    PeerId,
+};
+
+/// Timeout waiting for the [`Header`] after an [`UpgradeRequest::Relay`].
+const RECV_HEADER_TIMEOUT: Duration = Duration::from_secs(23);
+
+/// Size of the buffer used to copy relayed bytes.
+const COPY_BUF_SIZE: usize = 16 * 1024;
+
+pub(in crate::net::protocol) async fn relay<S>(
+    state: State<S>,
+    stream: Upgraded<upgrade::Relay, quic::BidiStream>,
+) where
+    S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + Clone + 'static,
+{
+    let remote_id = stream.remote_peer_id();
+    let mut stream = stream.into_stream();
+
+    let header = {
+        let timeout = async {
+            Delay::new(RECV_HEADER_TIMEOUT).await;
+            Err(relay::Error::from(io::Error::from(io::ErrorKind::TimedOut)))
+        };
+        let recv = relay::read_header(&mut stream);
+        futures::pin_mut!(timeout);
+        futures::pin_mut!(recv);
+
+        future::try_select(timeout, recv)
+            .map_ok(|ok| future::Either::factor_first(ok).0)
+            .map_err(|er| future::Either::factor_first(er).0)
+            .await
+    };
+    let header = match header {
+        Ok(header) => header,
+        Err(e) => {
+            tracing::warn!(err = ?e, "invalid relay header");
+            return stream.close(CloseReason::InvalidUpgrade);
+        },
+    };
+
+    if !header.is_relayable() {
+        tracing::warn!(?header, "invalid relay request");
+        return stream.close(CloseReason::InvalidUpgrade);
+    }
+
+    if header.to == state.local_id {
+        // The origin can't be verified, so only take the relay's word for it if
+        // it is one of our active peers
+        if header.from == state.local_id || !state.membership.is_active(&remote_id) {
+            tracing::warn!(?header, relay = %remote_id, "untrusted relayed stream");
+            return stream.close(CloseReason::InvalidUpgrade);
+        }
+        relayed(state, stream, header).await
+    } else {
+        // Only relay on behalf of the peer which asks us to
+        if header.from != remote_id {
+            tracing::warn!(?header, "invalid relay request");
+            return stream.close(CloseReason::InvalidUpgrade);
+        }
+        forward(state, stream, header).await
+    }
+}
+
+/// We are the target of a relayed stream.
+///
+/// Gossip is attributed to the relay, not the origin: the origin is usually
+/// not a member of our partial view, and gossip messages carry their origin
+/// anyway. Git streams are attributed to the origin, but as we can't verify
+/// it, we don't fetch back from it.
+async fn relayed<S>(state: State<S>, stream: quic::BidiStream, header: Header)
+where
+    S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + Clone + 'static,
+{
+    match header.upgrade {
+        UpgradeRequest::Git => {
+            let stream = Relayed::new(stream, header.from);
+            recv::relayed_git(state, Upgraded::new(stream)).await
+        },
+        UpgradeRequest::Gossip => recv::gossip(state, Upgraded::new(stream)).await,
+        _ => stream.close(CloseReason::InvalidUpgrade),
+    }
+}
+
+/// We are asked to forward a stream to the target.
+#[tracing::instrument(skip(state, stream), fields(from = %header.from, to = %header.to))]
+async fn forward<S>(state: State<S>, stream: quic::BidiStream, header: Header)
+where
+    S: ProtocolStorage<SocketAddr, Update = gossip::Payload> + Clone + 'static,
+{
+    if !state.config.relay {
+        tracing::warn!("relay request, but relaying is disabled");
+        return stream.close(CloseReason::InvalidUpgrade);
+    }
+    let limits = &state.limits.relay;
+    let _permit = match limits.admit(&header.from) {
+        Some(permit) => permit,
+        None => {
+            tracing::warn!("relay quota exceeded");
+            return stream.close(CloseReason::TooManyConnections);
+        },
+    };
+
+    // Only forward to peers we are already connected to, so we can't be used
+    // to dial out on someone else's behalf
+    let conn = match state.endpoint.get_connection(header.to) {
+        Some(conn) => conn,
+        None => {
+            tracing::info!("relay target not connected");
+            return stream.close(CloseReason::ConnectionError);
+        },
+    };
+    let target = match conn.open_bidi().await {
+        Ok(target) => target,
+        Err(e) => {
+            tracing::warn!(err = ?e, "unable to open stream to relay target");
+            return stream.close(CloseReason::ConnectionError);
+        },
+    };
+    match relay::request(target, &header).await {
+        Err(e) => {
+            tracing::warn!(err = ?e, "relay request to target failed");
+            stream.close(CloseReason::ConnectionError)
+        },
+        Ok(target) => {
+            let spliced = splice(stream, target, &header.from, limits);
+            let timeout = Delay::new(limits.max_duration);
+            futures::pin_mut!(spliced);
+            match future::select(spliced, timeout).await {
+                future::Either::Left((Err(e), _)) => {
+                    tracing::info!(err = ?e, "relayed stream terminated")
+                },
+                future::Either::Left((Ok(()), _)) => {},
+                future::Either::Right(_) => tracing::info!("relayed stream timed out"),
+            }
+        },
+    }
+}
+
+/// Copy bytes between `a` and `b` in both directions, until both are done.
+///
+/// All bytes are charged to `from`, and the splice is aborted when either its
+/// budget or [`RelayLimits::max_bytes`] are exceeded.
+async fn splice(
+    a: quic::BidiStream,
+    b: quic::BidiStream,
+    from: &PeerId,
+    limits: &RelayLimits,
+) -> io::Result<()> {
+    let (a_recv, mut a_send) = a.split();
+    let (b_recv, mut b_send) = b.split();
+
+    let a_to_b = async {
+        copy(a_recv, &mut b_send, from, limits).await?;
+        b_send.close().await
+    };
+    let b_to_a = async {
+        copy(b_recv, &mut a_send, from, limits).await?;
+        a_send.close().await
+    };
+
+    future::try_join(a_to_b, b_to_a).await.map(|_| ())
+}
+
+async fn copy<R, W>(
+    mut recv: R,
+    send: &mut W,
+    from: &PeerId,
+    limits: &RelayLimits,
+) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut buf = vec![0; COPY_BUF_SIZE];
+    let mut total = 0u64;
+    loop {
+        let n = recv.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        total = total.saturating_add(n as u64);
+        if total > limits.max_bytes || !limits.bytes.charge(from, n as u64) {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "relay byte limit exceeded",
+            ));
+        }
+        send.write_all(&buf[..n]).await?;
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{borrow::Cow, net::SocketAddr};
+
+use futures::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt as _},
+    SinkExt as _,
+    StreamExt as _,
+};
+use futures_codec::FramedRead;
+use thiserror::Error;
+
+use crate::{
+    git::storage::{self, PoolError, ReadOnlyStorage as _},
+    net::{
+        connection::{Duplex, RemotePeer as _},
+        protocol::{
+            io::codec,
+            sync::{self, Digest, Request, Response},
+            State,
+        },
+        upgrade::{self, Upgraded},
+    },
+    PeerId,
+};
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error(transparent)]
+    Cbor(#[from] minicbor::encode::Error<std::io::Error>),
+
+    #[error(transparent)]
+    Storage(#[from] storage::Error),
+
+    #[error(transparent)]
+    Pool(#[from] PoolError),
+}
+
+lazy_static! {
+    static ref INTERNAL_ERROR: Vec<u8> =
+        minicbor::to_vec(&Response::Error(sync::Error::Internal)).unwrap();
+}
+
+pub(in crate::net::protocol) async fn sync<S, T>(
+    state: State<S>,
+    stream: Upgraded<upgrade::SyncRefs, T>,
+) where
+    S: storage::Pooled<storage::Storage> + Send + Sync + 'static,
+    T: Duplex<Addr = SocketAddr>,
+    T::Read: AsyncRead + Unpin,
+    T::Write: AsyncWrite + Unpin,
+{
+    let remote_id = stream.remote_peer_id();
+
+    let (recv, send) = stream.into_stream().split();
+    let mut recv = FramedRead::new(recv, codec::Codec::<Request>::new());
+    if let Some(x) = recv.next().await {
+        match x {
+            Err(e) => tracing::warn!(err = ?e, "sync recv error"),
+            Ok(req) => {
+                let resp = handle_request(&state, remote_id, req)
+                    .await
+                    .map(Cow::from)
+                    .unwrap_or_else(|e| {
+                        tracing::error!(err = ?e, "error handling sync request");
+                        Cow::from(&*INTERNAL_ERROR)
+                    });
+
+                if let Err(e) = send.into_sink().send(resp).await {
+                    tracing::warn!(err = ?e, "sync send error")
+                }
+            },
+        }
+    }
+}
+
+async fn handle_request<S>(
+    state: &State<S>,
+    remote_id: PeerId,
+    req: Request,
+) -> Result<Vec<u8>, Error>
+where
+    S: storage::Pooled<storage::Storage> + Send + Sync + 'static,
+{
+    let storage = state.storage.get().await?;
+    let resp = state
+        .spawner
+        .blocking(move || -> Result<Response, Error> {
+            match req {
+                Request::Digests(theirs) => {
+                    let mut diverged = Vec::new();
+                    let mut unknown = Vec::new();
+                    for theirs in theirs.into_iter().take(sync::MAX_URNS) {
+                        if !storage.has_urn(&theirs.urn)? {
+                            unknown.push(theirs.urn);
+                            continue;
+                        }
+
+                        // Only compare the peers the initiator tracks -- if
+                        // that's not us, our own tip is of no interest to it
+                        let peers = theirs
+                            .tips
+                            .keys()
+                            .copied()
+                            .filter(|peer| *peer != remote_id);
+                        let ours = Digest::compute(&storage, &theirs.urn, peers)?;
+                        if ours.diverges(&theirs) {
+                            diverged.push(ours);
+                        }
+                    }
+
+                    Ok(Response::Digests { diverged, unknown })
+                },
+            }
+        })
+        .await?;
+
+    Ok(minicbor::to_vec(&resp)?)
+}
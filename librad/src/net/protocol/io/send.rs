@@ -4,7 +4,7 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 pub mod rpc;
-pub use rpc::send_rpc;
+pub use rpc::{send_relayed, send_rpc};
 
 pub mod request_response;
 pub use request_response::request;
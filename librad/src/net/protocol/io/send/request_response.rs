@@ -11,7 +11,7 @@ use futures_codec::Framed;
 use crate::net::{
     codec::CborCodec,
     connection::{RemoteAddr as _, RemotePeer as _},
-    protocol::{error, interrogation, quic, upgrade},
+    protocol::{error, interrogation, quic, sync, upgrade},
 };
 
 pub trait Request {
@@ -26,6 +26,12 @@ impl Request for interrogation::Request {
     const UPGRADE: Self::Upgrade = upgrade::Interrogation;
 }
 
+impl Request for sync::Request {
+    type Response = sync::Response;
+    type Upgrade = upgrade::SyncRefs;
+    const UPGRADE: Self::Upgrade = upgrade::SyncRefs;
+}
+
 #[tracing::instrument(
     skip(conn, req),
     fields(
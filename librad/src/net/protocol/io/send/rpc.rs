@@ -12,7 +12,7 @@ use crate::net::{
     connection::{RemoteAddr as _, RemotePeer},
     protocol::{broadcast, error, io::codec, membership},
     quic,
-    upgrade,
+    upgrade::{self, Upgraded},
 };
 
 #[derive(Debug)]
@@ -101,3 +101,18 @@ where
 
     Ok(())
 }
+
+/// Send a gossip message over a stream obtained via a relay.
+pub async fn send_relayed<P>(
+    stream: Upgraded<upgrade::Gossip, quic::BidiStream>,
+    msg: broadcast::Message<SocketAddr, P>,
+) -> Result<(), error::Rpc<quic::SendStream>>
+where
+    P: minicbor::Encode,
+{
+    let mut framed = FramedWrite::new(stream.into_stream(), codec::Gossip::new());
+    framed.send(msg).await?;
+    framed.close().await?;
+
+    Ok(())
+}
@@ -92,6 +92,8 @@ mod incoming {
             Ok(Gossip(up)) => recv::gossip(state, up).await,
             Ok(Membership(up)) => recv::membership(state, up).await,
             Ok(Interrogation(up)) => recv::interrogation(state, up).await,
+            Ok(Relay(up)) => recv::relay(state, up).await,
+            Ok(SyncRefs(up)) => recv::sync(state, up).await,
         }
     }
 
@@ -109,6 +111,8 @@ mod incoming {
 
             Ok(Git(up)) => deny_uni(up.into_stream(), "git"),
             Ok(Interrogation(up)) => deny_uni(up.into_stream(), "interrogation"),
+            Ok(Relay(up)) => deny_uni(up.into_stream(), "relay"),
+            Ok(SyncRefs(up)) => deny_uni(up.into_stream(), "sync"),
 
             Ok(Gossip(up)) => recv::gossip(state, up).await,
             Ok(Membership(up)) => recv::membership(state, up).await,
@@ -146,6 +146,10 @@ where
         self.0.read().active().collect()
     }
 
+    pub fn active_info(&self) -> Vec<PartialPeerInfo<Addr>> {
+        self.0.read().active_info().collect()
+    }
+
     pub fn is_active(&self, peer: &PeerId) -> bool {
         self.0.read().is_active(peer)
     }
@@ -249,6 +253,10 @@ where
         self.view.active()
     }
 
+    pub fn active_info(&self) -> impl Iterator<Item = PartialPeerInfo<Addr>> + '_ {
+        self.view.active_info()
+    }
+
     pub fn known(&self) -> impl Iterator<Item = PeerId> + '_ {
         self.view.known()
     }
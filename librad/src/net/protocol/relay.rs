@@ -0,0 +1,224 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Relaying of streams between peers which are not connected to each other.
+//!
+//! A peer which is not reachable from the outside (eg. because it is behind a
+//! NAT) can ask a peer it is connected to, and which advertises
+//! [`Capability::Relay`], to forward a stream to another peer the relay is
+//! connected to. The initiator opens a stream to the relay, sends
+//! an [`UpgradeRequest::Relay`] followed by a [`Header`] naming itself, the
+//! target, and the upgrade to forward. The relay checks that the `from` field
+//! matches the authenticated identity of the initiator, opens a stream to the
+//! target (which must already be connected), sends the same [`Header`], and
+//! copies bytes in both directions until either side finishes.
+//!
+//! The target can't verify the `from` field, so it only accepts relayed
+//! streams from peers in its active membership view.
+//!
+//! Only [`UpgradeRequest::Git`] and [`UpgradeRequest::Gossip`] streams can be
+//! relayed. Git streams are served as if they were received from the `from`
+//! peer directly, except that the target doesn't fetch back from the
+//! unverified origin. Relayed gossip is attributed to the relay -- the
+//! messages name their origin anyways.
+//!
+//! Relaying is opt-in (see [`crate::net::protocol::Config::relay`]). It is
+//! limited in rate and bytes per initiator, in rate and number of concurrent
+//! streams in total, as well as in size and duration per stream (see
+//! [`crate::net::protocol::Quota::relay`]).
+//!
+//! [`Capability::Relay`]: crate::net::protocol::Capability::Relay
+
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::io::{AsyncRead, AsyncReadExt as _, AsyncWrite, AsyncWriteExt as _};
+use minicbor::{Decode, Encode};
+use thiserror::Error;
+
+use crate::{
+    net::{
+        connection::{Duplex, RemoteAddr, RemotePeer},
+        upgrade::{self, UpgradeRequest},
+    },
+    peer::PeerId,
+};
+
+/// Maximum length of an encoded [`Header`].
+pub const MAX_HEADER_LEN: usize = 1024;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("relay header exceeds {} bytes", MAX_HEADER_LEN)]
+    TooLarge,
+
+    #[error("failed to upgrade stream")]
+    Upgrade(#[source] upgrade::ErrorSource),
+
+    #[error(transparent)]
+    Encode(#[from] minicbor::encode::Error<io::Error>),
+
+    #[error(transparent)]
+    Decode(#[from] minicbor::decode::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Sent after an [`UpgradeRequest::Relay`], to both the relay and the target.
+///
+/// # Wire Encoding
+///
+/// The header is CBOR-encoded, and prefixed by its length as a 2-byte
+/// big-endian integer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+#[cbor(array)]
+pub struct Header {
+    /// The peer which initiated the relayed stream.
+    #[n(0)]
+    pub from: PeerId,
+
+    /// The peer the stream is to be forwarded to.
+    #[n(1)]
+    pub to: PeerId,
+
+    /// The upgrade the stream is to be forwarded as.
+    #[n(2)]
+    pub upgrade: UpgradeRequest,
+}
+
+impl Header {
+    /// Whether [`Self::upgrade`] may be relayed.
+    pub fn is_relayable(&self) -> bool {
+        matches!(self.upgrade, UpgradeRequest::Git | UpgradeRequest::Gossip)
+    }
+}
+
+pub async fn write_header<W>(w: &mut W, header: &Header) -> Result<(), Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    let cbor = minicbor::to_vec(header)?;
+    if cbor.len() > MAX_HEADER_LEN {
+        return Err(Error::TooLarge);
+    }
+    w.write_all(&(cbor.len() as u16).to_be_bytes()).await?;
+    w.write_all(&cbor).await?;
+    Ok(w.flush().await?)
+}
+
+pub async fn read_header<R>(r: &mut R) -> Result<Header, Error>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut len = [0; 2];
+    r.read_exact(&mut len).await?;
+    let len = u16::from_be_bytes(len) as usize;
+    if len > MAX_HEADER_LEN {
+        return Err(Error::TooLarge);
+    }
+    let mut buf = vec![0; len];
+    r.read_exact(&mut buf).await?;
+    Ok(minicbor::decode(&buf)?)
+}
+
+/// Request `stream` to be relayed according to `header`.
+///
+/// On success, the returned stream is connected to [`Header::to`], and can be
+/// used as if it was upgraded to [`Header::upgrade`].
+pub async fn request<S>(stream: S, header: &Header) -> Result<S, Error>
+where
+    S: AsyncWrite + Unpin + Send + Sync,
+{
+    let mut stream = upgrade::upgrade(stream, upgrade::Relay)
+        .await
+        .map_err(|e| Error::Upgrade(e.source))?
+        .into_stream();
+    write_header(&mut stream, header).await?;
+    Ok(stream)
+}
+
+/// A stream received via a relay.
+///
+/// [`RemotePeer::remote_peer_id`] is the peer which initiated the stream, while
+/// [`RemoteAddr::remote_addr`] is the address of the relay.
+pub struct Relayed<S> {
+    stream: S,
+    origin: PeerId,
+}
+
+impl<S> Relayed<S> {
+    pub fn new(stream: S, origin: PeerId) -> Self {
+        Self { stream, origin }
+    }
+
+    pub fn into_stream(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> RemotePeer for Relayed<S> {
+    fn remote_peer_id(&self) -> PeerId {
+        self.origin
+    }
+}
+
+impl<S> RemoteAddr for Relayed<S>
+where
+    S: RemoteAddr<Addr = SocketAddr>,
+{
+    type Addr = SocketAddr;
+
+    fn remote_addr(&self) -> SocketAddr {
+        self.stream.remote_addr()
+    }
+}
+
+impl<S> Duplex for Relayed<S>
+where
+    S: Duplex<Addr = SocketAddr>,
+{
+    type Read = S::Read;
+    type Write = S::Write;
+
+    fn split(self) -> (Self::Read, Self::Write) {
+        self.stream.split()
+    }
+}
+
+impl<S> AsyncRead for Relayed<S>
+where
+    S: AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncRead::poll_read(Pin::new(&mut self.get_mut().stream), cx, buf)
+    }
+}
+
+impl<S> AsyncWrite for Relayed<S>
+where
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(Pin::new(&mut self.get_mut().stream), cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(Pin::new(&mut self.get_mut().stream), cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_close(Pin::new(&mut self.get_mut().stream), cx)
+    }
+}
@@ -3,11 +3,19 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::{net::SocketAddr, ops::Deref, sync::Arc};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    ops::Deref,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures::future::TryFutureExt as _;
 use nonzero_ext::nonzero;
+use parking_lot::Mutex;
 use rand_pcg::Pcg64Mcg;
+use tokio::sync::{Semaphore, SemaphorePermit};
 use tracing::Instrument as _;
 
 use super::{
@@ -19,7 +27,9 @@ use super::{
     io,
     membership,
     nonce,
+    relay,
     tick,
+    Capability,
     Endpoint,
     ProtocolStorage,
     TinCans,
@@ -34,7 +44,10 @@ use crate::{
         replication,
         storage::{self, PoolError, PooledRef},
     },
-    net::{quic, upgrade},
+    net::{
+        quic,
+        upgrade::{self, UpgradeRequest, Upgraded},
+    },
     rate_limit::{self, Direct, Keyed, RateLimiter},
     PeerId,
 };
@@ -43,6 +56,7 @@ use crate::{
 pub(super) struct StateConfig {
     pub replication: replication::Config,
     pub fetch: config::Fetch,
+    pub relay: bool,
 }
 
 /// Runtime state of a protocol instance.
@@ -110,6 +124,55 @@ where
     pub fn has_connection(&self, to: PeerId) -> bool {
         self.endpoint.get_connection(to).is_some()
     }
+
+    /// Connected peers which advertise [`Capability::Relay`].
+    pub fn relays(&self) -> Vec<PeerId> {
+        self.membership
+            .active_info()
+            .into_iter()
+            .filter(|info| {
+                info.advertised_info
+                    .as_ref()
+                    .map_or(false, |ad| ad.capabilities.contains(&Capability::Relay))
+            })
+            .map(|info| info.peer_id)
+            .collect()
+    }
+
+    /// Open a stream to `to` via the first of [`Self::relays`] which accepts
+    /// it.
+    pub async fn relayed<U>(&self, to: PeerId, up: U) -> Option<Upgraded<U, quic::BidiStream>>
+    where
+        U: Into<UpgradeRequest>,
+    {
+        let header = relay::Header {
+            from: self.local_id,
+            to,
+            upgrade: up.into(),
+        };
+        for via in self.relays() {
+            if via == to {
+                continue;
+            }
+            let conn = match self.endpoint.get_connection(via) {
+                Some(conn) => conn,
+                None => continue,
+            };
+            let stream = match conn.open_bidi().await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::warn!(err = ?e, relay = %via, "unable to open stream to relay");
+                    continue;
+                },
+            };
+            match relay::request(stream, &header).await {
+                Ok(stream) => return Some(Upgraded::new(stream)),
+                Err(e) => tracing::warn!(err = ?e, relay = %via, "relay request failed"),
+            }
+        }
+
+        None
+    }
 }
 
 #[async_trait]
@@ -129,8 +192,15 @@ where
             .await
         {
             None => {
-                span.in_scope(|| tracing::error!("unable to obtain connection"));
-                None
+                let upgraded = self
+                    .relayed(*to, upgrade::Git)
+                    .instrument(span.clone())
+                    .await;
+                if upgraded.is_none() {
+                    span.in_scope(|| tracing::error!("unable to obtain connection"));
+                }
+
+                upgraded.map(|up| Box::new(up) as Box<dyn GitStream>)
             },
 
             Some(conn) => {
@@ -159,6 +229,80 @@ where
 #[derive(Clone)]
 pub(super) struct RateLimits {
     pub membership: Arc<RateLimiter<Keyed<PeerId>>>,
+    pub relay: RelayLimits,
+}
+
+#[derive(Clone)]
+pub(super) struct RelayLimits {
+    pub per_peer: Arc<RateLimiter<Keyed<PeerId>>>,
+    pub total: Arc<RateLimiter<Direct>>,
+    pub streams: Arc<Semaphore>,
+    pub bytes: Arc<ByteBudget>,
+    pub max_bytes: u64,
+    pub max_duration: Duration,
+}
+
+impl RelayLimits {
+    pub fn new(quota: RelayQuota) -> Self {
+        Self {
+            per_peer: Arc::new(RateLimiter::keyed(
+                quota.per_peer,
+                nonzero!(256 * 1024usize),
+            )),
+            total: Arc::new(RateLimiter::direct(quota.total)),
+            streams: Arc::new(Semaphore::new(quota.max_streams)),
+            bytes: Arc::new(ByteBudget::new(quota.bytes_per_peer)),
+            max_bytes: quota.max_bytes,
+            max_duration: quota.max_duration,
+        }
+    }
+
+    /// Admit relaying a stream on behalf of `from`, if within quota.
+    ///
+    /// The stream counts towards [`RelayQuota::max_streams`] until the returned
+    /// permit is dropped.
+    pub fn admit(&self, from: &PeerId) -> Option<SemaphorePermit<'_>> {
+        if self.per_peer.check_key(from).is_err() || self.total.check().is_err() {
+            return None;
+        }
+        self.streams.try_acquire().ok()
+    }
+}
+
+/// Bytes relayed per initiating peer within [`ByteBudget::WINDOW`].
+pub(super) struct ByteBudget {
+    limit: u64,
+    used: Mutex<HashMap<PeerId, (Instant, u64)>>,
+}
+
+impl ByteBudget {
+    const WINDOW: Duration = Duration::from_secs(3600);
+    /// Number of entries above which expired ones are removed.
+    const MAX_ENTRIES: usize = 1024;
+
+    fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Charge `n` bytes to `peer`, returning `false` if this exceeds its
+    /// budget.
+    pub fn charge(&self, peer: &PeerId, n: u64) -> bool {
+        let now = Instant::now();
+        let mut used = self.used.lock();
+        if used.len() >= Self::MAX_ENTRIES {
+            used.retain(|_, (start, _)| now.duration_since(*start) < Self::WINDOW);
+        }
+        let (start, bytes) = used.entry(*peer).or_insert((now, 0));
+        if now.duration_since(*start) >= Self::WINDOW {
+            *start = now;
+            *bytes = 0;
+        }
+        *bytes = bytes.saturating_add(n);
+        *bytes <= self.limit
+    }
 }
 
 /// Rate limit quota.
@@ -175,6 +319,8 @@ pub struct Quota {
     pub membership: rate_limit::Quota,
     /// See [`StorageQuota`].
     pub storage: StorageQuota,
+    /// See [`RelayQuota`].
+    pub relay: RelayQuota,
 }
 
 impl Default for Quota {
@@ -183,6 +329,7 @@ impl Default for Quota {
             gossip: GossipQuota::default(),
             membership: rate_limit::Quota::per_second(nonzero!(1u32)).allow_burst(nonzero!(10u32)),
             storage: StorageQuota::default(),
+            relay: RelayQuota::default(),
         }
     }
 }
@@ -232,6 +379,61 @@ impl Default for StorageQuota {
     }
 }
 
+/// Relay quota.
+///
+/// Only applies if the local peer is configured to relay streams.
+#[derive(Clone, Debug)]
+pub struct RelayQuota {
+    /// Streams to relay per initiating peer.
+    ///
+    /// When this limit is breached, relay requests from the peer will be
+    /// rejected.
+    ///
+    /// Default: 30/min (burst: 10)
+    pub per_peer: rate_limit::Quota,
+    /// Streams to relay in total.
+    ///
+    /// Default: 300/min
+    pub total: rate_limit::Quota,
+    /// Streams to relay concurrently.
+    ///
+    /// When this limit is reached, further relay requests will be rejected.
+    ///
+    /// Default: 64
+    pub max_streams: usize,
+    /// Bytes to relay per initiating peer and hour, in both directions.
+    ///
+    /// When this limit is breached, the peer's relayed streams are dropped.
+    ///
+    /// Default: 1GiB
+    pub bytes_per_peer: u64,
+    /// Bytes to relay per stream and direction.
+    ///
+    /// When this limit is breached, the stream is dropped.
+    ///
+    /// Default: 128MiB
+    pub max_bytes: u64,
+    /// Time a relayed stream may stay open.
+    ///
+    /// When this limit is reached, the stream is dropped.
+    ///
+    /// Default: 10min
+    pub max_duration: Duration,
+}
+
+impl Default for RelayQuota {
+    fn default() -> Self {
+        Self {
+            per_peer: rate_limit::Quota::per_minute(nonzero!(30u32)).allow_burst(nonzero!(10u32)),
+            total: rate_limit::Quota::per_minute(nonzero!(300u32)),
+            max_streams: 64,
+            bytes_per_peer: 1024 * 1024 * 1024,
+            max_bytes: 128 * 1024 * 1024,
+            max_duration: Duration::from_secs(600),
+        }
+    }
+}
+
 //
 // Peer Storage (gossip)
 //
@@ -0,0 +1,144 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! Direct peer-to-peer `SYNC`.
+//!
+//! The initiator sends a [`Digest`] of each of the URNs it has, consisting of
+//! the tips of the `rad/signed_refs` of the peers it tracks in the respective
+//! namespace. The responder replies with its own [`Digest`]s of the URNs it
+//! has, too, but only for those which differ from the initiator's. This
+//! requires one roundtrip per [`MAX_URNS`] URNs, after which the initiator
+//! only needs to replicate the namespaces which have diverged, see
+//! [`crate::net::peer::Peer::sync`].
+//!
+//! Note that a diverging [`Digest`] does not imply that the responder is ahead
+//! of the initiator.
+
+use std::collections::BTreeMap;
+
+use git_ext as ext;
+use minicbor::{Decode, Encode};
+
+use crate::{
+    git::{
+        storage::{self, ReadOnlyStorage as _},
+        tracking,
+        types::{Namespace, Reference},
+        Urn,
+    },
+    PeerId,
+};
+
+pub use super::interrogation::Error;
+
+/// Maximum number of [`Digest`]s per [`Request`].
+///
+/// The responder ignores any excess [`Digest`]s.
+pub const MAX_URNS: usize = 256;
+
+/// The tips of the `rad/signed_refs` of a set of peers in the namespace of a
+/// URN.
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+#[cbor(array)]
+pub struct Digest {
+    #[n(0)]
+    pub urn: Urn,
+
+    /// `None` if the peer's `rad/signed_refs` are not present.
+    #[n(1)]
+    pub tips: BTreeMap<PeerId, Option<ext::Oid>>,
+}
+
+impl Digest {
+    /// Compute the [`Digest`] of `urn` over `peers`.
+    ///
+    /// The tip of the local peer is its own `rad/signed_refs`, while for all
+    /// other peers it is the remote-tracking `remotes/<peer>/rad/signed_refs`.
+    pub fn compute<S, P>(storage: &S, urn: &Urn, peers: P) -> Result<Self, storage::Error>
+    where
+        S: AsRef<storage::ReadOnly>,
+        P: IntoIterator<Item = PeerId>,
+    {
+        let storage = storage.as_ref();
+        let local_id = *storage.peer_id();
+        let mut tips = BTreeMap::new();
+        for peer in peers {
+            let remote = Some(peer).filter(|peer| *peer != local_id);
+            let tip = storage
+                .reference(&Reference::rad_signed_refs(Namespace::from(urn), remote))?
+                .and_then(|r| r.target())
+                .map(ext::Oid::from);
+            tips.insert(peer, tip);
+        }
+
+        Ok(Self {
+            urn: urn.clone(),
+            tips,
+        })
+    }
+
+    /// Compute the [`Digest`] of `urn` over the peers tracked by the local
+    /// peer, as sent by the initiator of a `SYNC`.
+    pub fn tracked<S>(storage: &S, urn: &Urn) -> Result<Self, tracking::Error>
+    where
+        S: AsRef<storage::ReadOnly>,
+    {
+        let peers = tracking::tracked(storage, urn)?.collect::<Vec<_>>();
+        Ok(Self::compute(storage, urn, peers)?)
+    }
+
+    /// Whether `self` has a tip for any peer which is absent from, or
+    /// different in `other`.
+    pub fn diverges(&self, other: &Self) -> bool {
+        self.tips
+            .iter()
+            .any(|(peer, tip)| tip.is_some() && other.tips.get(peer) != Some(tip))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub enum Request {
+    /// Ask the responder for its [`Digest`]s of the given URNs, over the
+    /// peers in the initiator's [`Digest`].
+    #[n(0)]
+    #[cbor(array)]
+    Digests(#[n(0)] Vec<Digest>),
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+pub enum Response {
+    /// An application-level error occurred, which prevented the responder from
+    /// fulfilling the request.
+    #[n(0)]
+    #[cbor(array)]
+    Error(#[n(0)] Error),
+
+    /// Response to a [`Request::Digests`].
+    ///
+    /// URNs which are in neither `diverged` nor `unknown` are in sync.
+    #[n(1)]
+    #[cbor(array)]
+    Digests {
+        /// The responder's [`Digest`]s which differ from the initiator's.
+        #[n(0)]
+        diverged: Vec<Digest>,
+        /// The URNs the responder does not have.
+        #[n(1)]
+        unknown: Vec<Urn>,
+    },
+}
+
+/// The outcome of a `SYNC` with a remote peer.
+#[derive(Clone, Debug, Default)]
+pub struct Summary {
+    /// URNs which were already in sync with the remote peer.
+    pub in_sync: Vec<Urn>,
+    /// URNs the remote peer does not have.
+    pub unknown: Vec<Urn>,
+    /// URNs which diverged, and were replicated from the remote peer.
+    pub replicated: Vec<Urn>,
+    /// URNs which diverged, but could not be replicated.
+    pub failed: Vec<Urn>,
+}
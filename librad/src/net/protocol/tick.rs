@@ -11,7 +11,7 @@ use futures::{
 };
 
 use super::{error, gossip, io, membership, PeerInfo, ProtocolStorage, State};
-use crate::PeerId;
+use crate::{net::upgrade, PeerId};
 
 #[derive(Debug)]
 pub(super) enum Tock<A, P> {
@@ -46,7 +46,7 @@ where
                         mcfly.extend(
                             membership::tocks(
                                 &state.membership,
-                                io::peer_advertisement(&state.endpoint),
+                                io::peer_advertisement(&state),
                                 Some(tick),
                             )
                             .into_iter()
@@ -102,13 +102,29 @@ where
             },
 
             AttemptSend { to, message } => {
-                let conn = state
+                match state
                     .connection(to.peer_id, to.addrs().copied().collect::<Vec<_>>())
                     .await
-                    .ok_or(error::BestEffortSend::CouldNotConnect { to })?;
-                Ok(io::send_rpc(&conn, message)
-                    .await
-                    .map_err(error::BestEffortSend::SendGossip)?)
+                {
+                    Some(conn) => Ok(io::send_rpc(&conn, message)
+                        .await
+                        .map_err(error::BestEffortSend::SendGossip)?),
+                    // Gossip may still reach `to` through a relay
+                    None => match message {
+                        io::Rpc::Gossip(msg) => {
+                            let stream = state
+                                .relayed(to.peer_id, upgrade::Gossip)
+                                .await
+                                .ok_or(error::BestEffortSend::CouldNotConnect { to })?;
+                            Ok(io::send_relayed(stream, msg)
+                                .await
+                                .map_err(error::BestEffortSend::SendGossip)?)
+                        },
+                        io::Rpc::Membership(_) => {
+                            Err(error::BestEffortSend::CouldNotConnect { to }.into())
+                        },
+                    },
+                }
             },
 
             Disconnect { peer } => {
@@ -15,8 +15,9 @@ use super::{
     gossip,
     info::PeerAdvertisement,
     interrogation,
+    sync,
 };
-use crate::{identities::xor::Xor, PeerId};
+use crate::{git::Urn, identities::xor::Xor, PeerId};
 
 #[derive(Clone)]
 pub struct TinCans {
@@ -133,6 +134,46 @@ impl TinCans {
         }
     }
 
+    /// Send the local [`sync::Digest`]s to `peer`, see [`sync`].
+    ///
+    /// Returns the remote peer's [`sync::Digest`]s which diverge from the local
+    /// ones, and the URNs the remote peer does not have.
+    pub async fn sync(
+        &self,
+        peer: impl Into<(PeerId, Vec<SocketAddr>)>,
+        digests: Vec<sync::Digest>,
+    ) -> Result<(Vec<sync::Digest>, Vec<Urn>), error::Sync> {
+        use event::downstream::Sync;
+
+        let (tx, rx) = replier();
+        let msg = Downstream::Sync(Sync {
+            peer: peer.into(),
+            request: sync::Request::Digests(digests),
+            reply: tx,
+        });
+        if let Err(tincan::error::SendError(e)) = self.downstream.send(msg) {
+            match e {
+                Downstream::Sync(Sync { reply, .. }) => {
+                    reply
+                        .lock()
+                        .take()
+                        .expect("if chan send failed, there can't be another contender")
+                        .send(Err(error::Sync::Unavailable))
+                        .ok();
+                },
+
+                _ => unreachable!(),
+            }
+        }
+
+        rx.await
+            .unwrap_or(Err(error::Sync::Unavailable))
+            .and_then(|resp| match resp {
+                sync::Response::Digests { diverged, unknown } => Ok((diverged, unknown)),
+                sync::Response::Error(e) => Err(error::Sync::ErrorResponse(e)),
+            })
+    }
+
     pub fn subscribe(&self) -> impl futures::Stream<Item = Result<event::Upstream, RecvError>> {
         let mut r = self.upstream.subscribe();
         async_stream::stream! { loop { yield r.recv().await } }
@@ -51,6 +51,13 @@ pub struct Membership;
 #[derive(Debug)]
 pub struct Interrogation;
 
+#[derive(Debug)]
+pub struct Relay;
+
+/// See [`crate::net::protocol::sync`].
+#[derive(Debug)]
+pub struct SyncRefs;
+
 /// Signal the (sub-) protocol about to be sent over a given QUIC stream.
 ///
 /// This is only valid as the first message sent by the initiator of a fresh
@@ -73,6 +80,8 @@ pub enum UpgradeRequest {
     Git = 1,
     Membership = 2,
     Interrogation = 3,
+    Relay = 4,
+    SyncRefs = 5,
 }
 
 impl From<Gossip> for UpgradeRequest {
@@ -99,6 +108,18 @@ impl From<Interrogation> for UpgradeRequest {
     }
 }
 
+impl From<Relay> for UpgradeRequest {
+    fn from(_relay: Relay) -> Self {
+        UpgradeRequest::Relay
+    }
+}
+
+impl From<SyncRefs> for UpgradeRequest {
+    fn from(_sync: SyncRefs) -> Self {
+        UpgradeRequest::SyncRefs
+    }
+}
+
 impl minicbor::Encode for UpgradeRequest {
     fn encode<W: minicbor::encode::Write>(
         &self,
@@ -121,6 +142,8 @@ impl<'de> minicbor::Decode<'de> for UpgradeRequest {
                 1 => Ok(Self::Git),
                 2 => Ok(Self::Membership),
                 3 => Ok(Self::Interrogation),
+                4 => Ok(Self::Relay),
+                5 => Ok(Self::SyncRefs),
                 n => Err(minicbor::decode::Error::UnknownVariant(n as u32)),
             },
             n => Err(minicbor::decode::Error::UnknownVariant(n as u32)),
@@ -234,6 +257,8 @@ pub enum SomeUpgraded<S> {
     Git(Upgraded<Git, S>),
     Membership(Upgraded<Membership, S>),
     Interrogation(Upgraded<Interrogation, S>),
+    Relay(Upgraded<Relay, S>),
+    SyncRefs(Upgraded<SyncRefs, S>),
 }
 
 impl<S> SomeUpgraded<S> {
@@ -246,6 +271,8 @@ impl<S> SomeUpgraded<S> {
             Self::Git(up) => SomeUpgraded::Git(up.map(f)),
             Self::Membership(up) => SomeUpgraded::Membership(up.map(f)),
             Self::Interrogation(up) => SomeUpgraded::Interrogation(up.map(f)),
+            Self::Relay(up) => SomeUpgraded::Relay(up.map(f)),
+            Self::SyncRefs(up) => SomeUpgraded::SyncRefs(up.map(f)),
         }
     }
 }
@@ -304,6 +331,8 @@ where
                 UpgradeRequest::Interrogation => {
                     SomeUpgraded::Interrogation(Upgraded::new(incoming))
                 },
+                UpgradeRequest::Relay => SomeUpgraded::Relay(Upgraded::new(incoming)),
+                UpgradeRequest::SyncRefs => SomeUpgraded::SyncRefs(Upgraded::new(incoming)),
             };
 
             Ok(upgrade)
@@ -4,7 +4,9 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 use std::{
+    collections::BTreeMap,
     env,
+    fs,
     io,
     path::{Path, PathBuf},
 };
@@ -21,9 +23,17 @@ const RAD_PROFILE: &str = "RAD_PROFILE";
 pub enum Error {
     #[error("the profile {0} does not exist")]
     DoesNotExist(ProfileId),
+    #[error("the profile {0} already exists")]
+    AlreadyExists(ProfileId),
+    #[error("the profile {0} is the active profile")]
+    Active(ProfileId),
+    #[error("the storage at {0} differs from the original after moving it")]
+    Integrity(PathBuf),
     #[error(transparent)]
     ProfileId(#[from] id::Error),
     #[error(transparent)]
+    Git(#[from] git2::Error),
+    #[error(transparent)]
     Io(#[from] io::Error),
 }
 
@@ -68,6 +78,17 @@ impl RadHome {
             Self::Root(root) => root.clone(),
         })
     }
+
+    /// The directories holding the data of the profile `id`.
+    fn profile_dirs(&self, id: &ProfileId) -> Result<Vec<PathBuf>, io::Error> {
+        Ok(match self {
+            Self::ProjectDirs => {
+                let proj = project_dirs()?;
+                vec![proj.config_dir().join(id), proj.data_dir().join(id)]
+            },
+            Self::Root(root) => vec![root.join(id)],
+        })
+    }
 }
 
 impl Profile {
@@ -123,6 +144,107 @@ impl Profile {
         Ok(profiles)
     }
 
+    /// Delete the profile `id` under `home`, including its keys and storage.
+    ///
+    /// It is an error to delete the active profile, use [`Profile::set`] to
+    /// switch to another profile first.
+    pub fn delete(home: &RadHome, id: ProfileId) -> Result<(), Error> {
+        if !exists(home, &id)? {
+            return Err(Error::DoesNotExist(id));
+        }
+        if ProfileId::active(home)?.as_ref() == Some(&id) {
+            return Err(Error::Active(id));
+        }
+
+        for dir in home.profile_dirs(&id)? {
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rename the profile `from` under `home` to `to`, which must not exist.
+    ///
+    /// If `from` is the active profile, `to` becomes the active profile.
+    pub fn rename(home: &RadHome, from: ProfileId, to: ProfileId) -> Result<Self, Error> {
+        if !exists(home, &from)? {
+            return Err(Error::DoesNotExist(from));
+        }
+        if exists(home, &to)? {
+            return Err(Error::AlreadyExists(to));
+        }
+
+        let was_active = ProfileId::active(home)?.as_ref() == Some(&from);
+        let before = snapshot(Self::from_home(home, Some(from.clone()))?.paths())?;
+        for (src, dst) in home
+            .profile_dirs(&from)?
+            .into_iter()
+            .zip(home.profile_dirs(&to)?)
+        {
+            if src.exists() {
+                fs::rename(src, dst)?;
+            }
+        }
+
+        let profile = Self::from_home(home, Some(to))?;
+        verify(&before, profile.paths())?;
+        if was_active {
+            profile.id.set_active(home)?;
+        }
+
+        Ok(profile)
+    }
+
+    /// Move the profile `id` from `from` to `to`, eg. to relocate its storage
+    /// to another disk.
+    ///
+    /// The data of the profile is copied to `to` first. Only if the storage of
+    /// the copy is intact, the original is removed. If the profile is the
+    /// active profile of `from`, it becomes the active profile of `to`, and
+    /// `from` is left without an active profile.
+    ///
+    /// Note that working copies including files from
+    /// [`Paths::git_includes_dir`] need to be pointed to the new location.
+    pub fn migrate(from: &RadHome, to: &RadHome, id: ProfileId) -> Result<Self, Error> {
+        let source = Self::get(from, id.clone())?.ok_or_else(|| Error::DoesNotExist(id.clone()))?;
+        if exists(to, &id)? {
+            return Err(Error::AlreadyExists(id));
+        }
+
+        let before = snapshot(source.paths())?;
+        let target = Self::from_home(to, Some(id.clone()))?;
+        let copied = source
+            .paths()
+            .all_dirs()
+            .zip(target.paths().all_dirs())
+            .try_for_each(|(src, dst)| copy_dir(src, dst))
+            .map_err(Error::from)
+            .and_then(|()| verify(&before, target.paths()));
+        if let Err(e) = copied {
+            for dir in to.profile_dirs(&id)? {
+                if dir.exists() {
+                    fs::remove_dir_all(dir)?;
+                }
+            }
+            return Err(e);
+        }
+
+        let was_active = ProfileId::active(from)?.as_ref() == Some(&id);
+        for dir in from.profile_dirs(&id)? {
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+        }
+        if was_active {
+            id.set_active(to)?;
+            ProfileId::unset_active(from)?;
+        }
+
+        Ok(target)
+    }
+
     /// Creates a profile by loading the profile identifier and paths from
     /// the environment variables or well-known file.
     ///
@@ -197,3 +319,53 @@ fn exists(home: &RadHome, id: &ProfileId) -> Result<bool, Error> {
     let path = config.join(id.as_str());
     Ok(path.is_dir())
 }
+
+/// The references of the storage under `paths`, if it is initialised.
+type Snapshot = Option<BTreeMap<String, git2::Oid>>;
+
+fn snapshot(paths: &Paths) -> Result<Snapshot, Error> {
+    let repo = match git2::Repository::open_bare(paths.git_dir()) {
+        Ok(repo) => repo,
+        Err(e) if e.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut refs = BTreeMap::new();
+    for r in repo.references()? {
+        let r = r?;
+        if let (Some(name), Some(target)) = (r.name(), r.target()) {
+            refs.insert(name.to_owned(), target);
+        }
+    }
+    Ok(Some(refs))
+}
+
+/// Ensure the storage under `paths` has the same references as `before`, and
+/// that their targets are present.
+fn verify(before: &Snapshot, paths: &Paths) -> Result<(), Error> {
+    let after = snapshot(paths)?;
+    if &after != before {
+        return Err(Error::Integrity(paths.git_dir().to_path_buf()));
+    }
+    if let Some(refs) = after {
+        let odb = git2::Repository::open_bare(paths.git_dir())?.odb()?;
+        if !refs.values().all(|oid| odb.exists(*oid)) {
+            return Err(Error::Integrity(paths.git_dir().to_path_buf()));
+        }
+    }
+    Ok(())
+}
+
+/// Recursively copy the contents of `src` into `dst`.
+fn copy_dir(src: &Path, dst: &Path) -> Result<(), io::Error> {
+    fs::create_dir_all(dst)?;
+    for entry in src.read_dir()? {
+        let entry = entry?;
+        let dst = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &dst)?;
+        } else {
+            fs::copy(entry.path(), dst)?;
+        }
+    }
+    Ok(())
+}
@@ -166,6 +166,14 @@ impl ProfileId {
         Ok(())
     }
 
+    /// Remove the `active_profile` file, if it exists.
+    pub(super) fn unset_active(home: &RadHome) -> Result<(), Error> {
+        match fs::remove_file(home.config()?.join(ACTIVE)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         &self.0
     }
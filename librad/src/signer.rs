@@ -12,6 +12,9 @@ use keystore::sign;
 
 use crate::{keys, peer::PeerId};
 
+#[cfg(unix)]
+pub mod agent;
+
 /// A blanket trait over [`sign::Signer`] that can be shared safely among
 /// threads.
 pub trait Signer:
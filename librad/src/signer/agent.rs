@@ -0,0 +1,280 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+//! A [`sign::Signer`] backed by an `ssh-agent`.
+//!
+//! The secret key is held by the agent (or a hardware token the agent talks
+//! to), and never enters the process. Only the subset of the [agent protocol]
+//! needed to list `ssh-ed25519` keys, sign with them, and add them is
+//! implemented.
+//!
+//! [agent protocol]: https://datatracker.ietf.org/doc/html/draft-miller-ssh-agent
+
+use std::{
+    convert::TryFrom as _,
+    env,
+    io::{self, Read as _, Write as _},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+};
+
+use keystore::sign;
+use thiserror::Error;
+use zeroize::Zeroize as _;
+
+use super::{BoxedSigner, SomeSigner};
+use crate::keys::{PublicKey, SecretKey, Signature};
+
+/// Environment variable pointing to the socket of the running `ssh-agent`.
+pub const SSH_AUTH_SOCK: &str = "SSH_AUTH_SOCK";
+
+/// The key type of ed25519 keys, as used in key blobs and signatures.
+pub const KEY_TYPE: &str = "ssh-ed25519";
+
+/// Messages of the agent protocol.
+pub mod message {
+    pub const FAILURE: u8 = 5;
+    pub const SUCCESS: u8 = 6;
+    pub const REQUEST_IDENTITIES: u8 = 11;
+    pub const IDENTITIES_ANSWER: u8 = 12;
+    pub const SIGN_REQUEST: u8 = 13;
+    pub const SIGN_RESPONSE: u8 = 14;
+    pub const ADD_IDENTITY: u8 = 17;
+}
+
+/// Upper bound of the size of a message we are willing to read.
+const MAX_MESSAGE_SIZE: u32 = 256 * 1024;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("`{}` is not set", SSH_AUTH_SOCK)]
+    NoAgent,
+
+    #[error("the ssh-agent does not hold the key {0}")]
+    KeyNotFound(PublicKey),
+
+    #[error("the ssh-agent refused the request")]
+    Failure,
+
+    #[error("unexpected response {0} from the ssh-agent")]
+    UnexpectedResponse(u8),
+
+    #[error("malformed message from the ssh-agent")]
+    Malformed,
+
+    #[error("the ssh-agent returned an invalid signature")]
+    InvalidSignature,
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A [`sign::Signer`] which delegates signing with `key` to the `ssh-agent`
+/// listening on `path`.
+///
+/// A new connection to the agent is made for every request. Note that I/O is
+/// blocking, even in [`sign::Signer::sign`], so that signing via
+/// [`crate::signer::Signer::sign_blocking`] does not depend on a reactor.
+#[derive(Clone, Debug)]
+pub struct SshAgent {
+    path: PathBuf,
+    key: PublicKey,
+}
+
+impl SshAgent {
+    pub fn new(path: impl Into<PathBuf>, key: PublicKey) -> Self {
+        Self {
+            path: path.into(),
+            key,
+        }
+    }
+
+    /// Use the agent pointed to by [`SSH_AUTH_SOCK`].
+    pub fn from_env(key: PublicKey) -> Result<Self, Error> {
+        env::var_os(SSH_AUTH_SOCK)
+            .map(|path| Self::new(path, key))
+            .ok_or(Error::NoAgent)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Ensure the agent is reachable and holds our key.
+    pub fn check(self) -> Result<Self, Error> {
+        if self.identities()?.contains(&self.key) {
+            Ok(self)
+        } else {
+            Err(Error::KeyNotFound(self.key))
+        }
+    }
+
+    /// The `ssh-ed25519` keys held by the agent. Other key types are ignored.
+    pub fn identities(&self) -> Result<Vec<PublicKey>, Error> {
+        let res = self.request(message::REQUEST_IDENTITIES, &[])?;
+        let mut body = expect(message::IDENTITIES_ANSWER, &res)?;
+
+        let n = read_u32(&mut body)?;
+        let mut keys = Vec::new();
+        for _ in 0..n {
+            let blob = read_string(&mut body)?;
+            let _comment = read_string(&mut body)?;
+            if let Some(key) = decode_key(&blob) {
+                keys.push(key)
+            }
+        }
+
+        Ok(keys)
+    }
+
+    /// Add `key` to the agent.
+    ///
+    /// This is intended for handing over a key from an encrypted file store
+    /// to the agent once, eg. at the start of a session.
+    pub fn add(&self, key: &SecretKey, comment: &str) -> Result<(), Error> {
+        let public = key.public();
+        let mut secret = Vec::with_capacity(64);
+        secret.extend_from_slice(key.as_ref());
+        secret.extend_from_slice(public.as_ref());
+
+        let mut req = Vec::new();
+        write_string(&mut req, KEY_TYPE.as_bytes());
+        write_string(&mut req, public.as_ref());
+        write_string(&mut req, &secret);
+        write_string(&mut req, comment.as_bytes());
+        secret.zeroize();
+
+        let res = self.request(message::ADD_IDENTITY, &req);
+        req.zeroize();
+        expect(message::SUCCESS, &res?).map(|_| ())
+    }
+
+    /// Sign `data` with our key.
+    pub fn sign_with_agent(&self, data: &[u8]) -> Result<Signature, Error> {
+        let mut req = Vec::new();
+        write_string(&mut req, &encode_key(&self.key));
+        write_string(&mut req, data);
+        write_u32(&mut req, 0);
+
+        let res = match self.request(message::SIGN_REQUEST, &req) {
+            // The agent responds with a failure if it doesn't know the key
+            Err(Error::Failure) => return Err(Error::KeyNotFound(self.key)),
+            x => x?,
+        };
+        let mut body = expect(message::SIGN_RESPONSE, &res)?;
+
+        let blob = read_string(&mut body)?;
+        let mut blob = blob.as_slice();
+        if read_string(&mut blob)? != KEY_TYPE.as_bytes() {
+            return Err(Error::InvalidSignature);
+        }
+        let sig = <[u8; 64]>::try_from(read_string(&mut blob)?.as_slice())
+            .map(|sig| Signature::from(sign::Signature(sig)))
+            .map_err(|_| Error::InvalidSignature)?;
+        if sig.verify(data, &self.key) {
+            Ok(sig)
+        } else {
+            Err(Error::InvalidSignature)
+        }
+    }
+
+    fn request(&self, typ: u8, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut stream = UnixStream::connect(&self.path)?;
+
+        let mut msg = Vec::with_capacity(payload.len() + 5);
+        write_u32(&mut msg, payload.len() as u32 + 1);
+        msg.push(typ);
+        msg.extend_from_slice(payload);
+        stream.write_all(&msg)?;
+
+        let mut len = [0; 4];
+        stream.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len);
+        if len == 0 || len > MAX_MESSAGE_SIZE {
+            return Err(Error::Malformed);
+        }
+        let mut res = vec![0; len as usize];
+        stream.read_exact(&mut res)?;
+
+        if res[0] == message::FAILURE {
+            Err(Error::Failure)
+        } else {
+            Ok(res)
+        }
+    }
+}
+
+#[async_trait]
+impl sign::Signer for SshAgent {
+    type Error = Error;
+
+    fn public_key(&self) -> sign::PublicKey {
+        sign::PublicKey((*self.key).into())
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<sign::Signature, Self::Error> {
+        self.sign_with_agent(data)
+            .map(|sig| sign::Signature(sig.into()))
+    }
+}
+
+impl From<SshAgent> for BoxedSigner {
+    fn from(agent: SshAgent) -> Self {
+        Self::from(SomeSigner { signer: agent })
+    }
+}
+
+/// Encode `key` as an `ssh-ed25519` key blob.
+pub fn encode_key(key: &PublicKey) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(51);
+    write_string(&mut blob, KEY_TYPE.as_bytes());
+    write_string(&mut blob, key.as_ref());
+    blob
+}
+
+/// Decode an `ssh-ed25519` key blob, returning `None` for other key types.
+pub fn decode_key(mut blob: &[u8]) -> Option<PublicKey> {
+    let typ = read_string(&mut blob).ok()?;
+    if typ != KEY_TYPE.as_bytes() {
+        return None;
+    }
+    let key = read_string(&mut blob).ok()?;
+    PublicKey::from_slice(&key)
+}
+
+/// Check the type of the response `res`, returning its body.
+fn expect(typ: u8, res: &[u8]) -> Result<&[u8], Error> {
+    match res.split_first() {
+        Some((t, body)) if *t == typ => Ok(body),
+        Some((t, _)) => Err(Error::UnexpectedResponse(*t)),
+        None => Err(Error::Malformed),
+    }
+}
+
+fn write_u32(buf: &mut Vec<u8>, n: u32) {
+    buf.extend_from_slice(&n.to_be_bytes())
+}
+
+fn write_string(buf: &mut Vec<u8>, s: &[u8]) {
+    write_u32(buf, s.len() as u32);
+    buf.extend_from_slice(s)
+}
+
+fn read_u32(buf: &mut impl io::Read) -> Result<u32, Error> {
+    let mut n = [0; 4];
+    buf.read_exact(&mut n).map_err(|_| Error::Malformed)?;
+    Ok(u32::from_be_bytes(n))
+}
+
+fn read_string(buf: &mut impl io::Read) -> Result<Vec<u8>, Error> {
+    let len = read_u32(buf)?;
+    if len > MAX_MESSAGE_SIZE {
+        return Err(Error::Malformed);
+    }
+    let mut s = vec![0; len as usize];
+    buf.read_exact(&mut s).map_err(|_| Error::Malformed)?;
+    Ok(s)
+}
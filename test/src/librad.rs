@@ -9,3 +9,5 @@ pub mod internal;
 pub mod keys;
 pub mod net;
 pub mod peer;
+#[cfg(unix)]
+pub mod signer;
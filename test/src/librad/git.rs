@@ -117,6 +117,22 @@ impl<'a> Device<'a> {
         Ok(Self { cur, ..self })
     }
 
+    pub fn update_with_threshold(
+        self,
+        delegations: impl Into<Option<delegation::Direct>>,
+        threshold: Option<Threshold>,
+    ) -> anyhow::Result<Self> {
+        let cur = self.git.update_with_threshold(
+            Verifying::from(self.cur).signed()?,
+            None,
+            delegations,
+            threshold,
+            self.key,
+        )?;
+
+        Ok(Self { cur, ..self })
+    }
+
     pub fn update_from(self, other: &Device<'a>) -> anyhow::Result<Self> {
         let cur = self.git.update_from(
             Verifying::from(self.cur).signed()?,
@@ -123,6 +123,7 @@ where
             replaces: None,
             payload: Boring,
             delegations,
+            threshold: None,
         },
         signatures,
     }
@@ -172,6 +173,7 @@ where
                     replaces,
                     payload: Boring,
                     delegations,
+                    threshold: None,
                 },
                 signatures,
             },
@@ -318,6 +320,7 @@ where
                 replaces: inner_replaces,
                 payload: Boring,
                 delegations,
+                threshold: None,
             },
             signatures,
         };
@@ -4,4 +4,5 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 pub mod connection;
+pub mod discovery;
 pub mod protocol;
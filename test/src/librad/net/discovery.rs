@@ -0,0 +1,29 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{
+    net::{Ipv4Addr, UdpSocket},
+    time::Duration,
+};
+
+use librad::net::discovery::lan;
+
+/// A [`lan::Config`] which keeps announcements on the loopback interface.
+///
+/// Each call picks a fresh port, so concurrently running tests don't see each
+/// other's announcements.
+pub fn loopback() -> lan::Config {
+    let port = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|sock| sock.local_addr())
+        .expect("unable to allocate a port")
+        .port();
+    lan::Config {
+        port,
+        interface: Ipv4Addr::LOCALHOST,
+        ttl: 0,
+        interval: Duration::from_millis(100),
+        ..Default::default()
+    }
+}
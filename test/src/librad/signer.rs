@@ -0,0 +1,138 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{
+    convert::TryFrom as _,
+    io::{self, Read as _, Write as _},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use tempfile::TempDir;
+
+use librad::{
+    keys::SecretKey,
+    signer::agent::{encode_key, message, KEY_TYPE},
+};
+
+/// An in-process stand-in for `ssh-agent`, serving the subset of the agent
+/// protocol used by [`librad::signer::agent::SshAgent`] on a Unix socket.
+pub struct MockAgent {
+    path: PathBuf,
+    keys: Arc<Mutex<Vec<SecretKey>>>,
+    _tmp: TempDir,
+}
+
+impl MockAgent {
+    pub fn spawn() -> io::Result<Self> {
+        let tmp = tempfile::tempdir()?;
+        let path = tmp.path().join("agent.sock");
+        let listener = UnixListener::bind(&path)?;
+        let keys = Arc::new(Mutex::new(Vec::new()));
+        {
+            let keys = Arc::clone(&keys);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            let _ = serve(stream, &keys);
+                        },
+                        Err(_) => break,
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            path,
+            keys,
+            _tmp: tmp,
+        })
+    }
+
+    pub fn with_key(self, key: SecretKey) -> Self {
+        self.keys.lock().unwrap().push(key);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn serve(mut stream: UnixStream, keys: &Mutex<Vec<SecretKey>>) -> io::Result<()> {
+    loop {
+        let mut len = [0; 4];
+        match stream.read_exact(&mut len) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            x => x?,
+        }
+        let mut msg = vec![0; u32::from_be_bytes(len) as usize];
+        stream.read_exact(&mut msg)?;
+
+        let res = respond(&msg, keys).unwrap_or_else(|| vec![message::FAILURE]);
+        let mut out = Vec::with_capacity(res.len() + 4);
+        out.extend_from_slice(&(res.len() as u32).to_be_bytes());
+        out.extend_from_slice(&res);
+        stream.write_all(&out)?;
+    }
+}
+
+fn respond(msg: &[u8], keys: &Mutex<Vec<SecretKey>>) -> Option<Vec<u8>> {
+    let (typ, mut body) = msg.split_first()?;
+    let mut res = Vec::new();
+    match *typ {
+        message::REQUEST_IDENTITIES => {
+            let keys = keys.lock().unwrap();
+            res.push(message::IDENTITIES_ANSWER);
+            res.extend_from_slice(&(keys.len() as u32).to_be_bytes());
+            for key in keys.iter() {
+                put_string(&mut res, &encode_key(&key.public()));
+                put_string(&mut res, b"mock");
+            }
+        },
+        message::SIGN_REQUEST => {
+            let blob = take_string(&mut body)?;
+            let data = take_string(&mut body)?;
+            let keys = keys.lock().unwrap();
+            let key = keys.iter().find(|key| encode_key(&key.public()) == blob)?;
+            let sig: [u8; 64] = key.sign(&data).into();
+
+            let mut sig_blob = Vec::new();
+            put_string(&mut sig_blob, KEY_TYPE.as_bytes());
+            put_string(&mut sig_blob, &sig);
+            res.push(message::SIGN_RESPONSE);
+            put_string(&mut res, &sig_blob);
+        },
+        message::ADD_IDENTITY => {
+            if take_string(&mut body)? != KEY_TYPE.as_bytes() {
+                return None;
+            }
+            let _public = take_string(&mut body)?;
+            let secret = take_string(&mut body)?;
+            let seed = <[u8; 32]>::try_from(secret.get(..32)?).ok()?;
+            keys.lock().unwrap().push(SecretKey::from_seed(seed));
+            res.push(message::SUCCESS);
+        },
+        _ => return None,
+    }
+
+    Some(res)
+}
+
+fn put_string(buf: &mut Vec<u8>, s: &[u8]) {
+    buf.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    buf.extend_from_slice(s);
+}
+
+fn take_string(buf: &mut &[u8]) -> Option<Vec<u8>> {
+    let mut len = [0; 4];
+    buf.read_exact(&mut len).ok()?;
+    let mut s = vec![0; u32::from_be_bytes(len) as usize];
+    buf.read_exact(&mut s).ok()?;
+    Some(s)
+}
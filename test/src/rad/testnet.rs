@@ -99,10 +99,11 @@ impl LocalAddr for RunningTestPeer {
     }
 }
 
-async fn boot<I, J>(seeds: I) -> anyhow::Result<BoundTestPeer>
+async fn boot<I, J, F>(seeds: I, configure: F) -> anyhow::Result<BoundTestPeer>
 where
     I: IntoIterator<Item = (PeerId, J)>,
     J: IntoIterator<Item = SocketAddr>,
+    F: FnOnce(&mut protocol::Config),
 {
     let tmp = tempdir()?;
     let paths = Paths::from_root(tmp.path())?;
@@ -112,7 +113,7 @@ where
     git::storage::Storage::init(&paths, key.clone())?;
 
     let listen_addr = *LOCALHOST_ANY;
-    let protocol = protocol::Config {
+    let mut protocol = protocol::Config {
         paths,
         listen_addr,
         advertised_addrs: None,
@@ -121,7 +122,9 @@ where
         replication: Default::default(),
         fetch: Default::default(),
         rate_limits: Default::default(),
+        relay: false,
     };
+    configure(&mut protocol);
     let disco = seeds.into_iter().collect::<discovery::Static>();
     let peer = Peer::new(peer::Config {
         signer: key,
@@ -221,44 +224,48 @@ pub struct Config {
     pub bootstrap: Bootstrap,
 }
 
-async fn bootstrap(config: Config) -> anyhow::Result<Vec<BoundTestPeer>> {
+async fn bootstrap<F>(config: Config, configure: F) -> anyhow::Result<Vec<BoundTestPeer>>
+where
+    F: Fn(usize, &mut protocol::Config),
+{
     let num_peers = config.num_peers.get();
     let mut peers = Vec::with_capacity(num_peers);
 
     match config.bootstrap {
         Bootstrap::None => {
-            for _ in 0..num_peers {
-                let peer = boot::<Option<_>, Option<_>>(None).await?;
+            for i in 0..num_peers {
+                let peer = boot::<Option<_>, Option<_>, _>(None, |cfg| configure(i, cfg)).await?;
                 peers.push(peer);
             }
         },
 
         Bootstrap::First => {
-            let bootstrap_node = boot::<Option<_>, Option<_>>(None).await?;
+            let bootstrap_node =
+                boot::<Option<_>, Option<_>, _>(None, |cfg| configure(0, cfg)).await?;
             let bootstrap = Some((
                 bootstrap_node.bound.peer_id(),
                 bootstrap_node.listen_addrs(),
             ));
             peers.push(bootstrap_node);
 
-            for _ in 1..num_peers {
-                let peer = boot(bootstrap.clone()).await?;
+            for i in 1..num_peers {
+                let peer = boot(bootstrap.clone(), |cfg| configure(i, cfg)).await?;
                 peers.push(peer);
             }
         },
 
         Bootstrap::Prev => {
             let mut bootstrap: Option<(PeerId, Vec<SocketAddr>)> = None;
-            for _ in 0..num_peers {
-                let peer = boot(bootstrap.take()).await?;
+            for i in 0..num_peers {
+                let peer = boot(bootstrap.take(), |cfg| configure(i, cfg)).await?;
                 bootstrap = Some((peer.bound.peer_id(), peer.bound.listen_addrs()));
                 peers.push(peer);
             }
         },
 
         Bootstrap::Fixed(bootstrap) => {
-            for _ in 0..num_peers {
-                let peer = boot(bootstrap.clone()).await?;
+            for i in 0..num_peers {
+                let peer = boot(bootstrap.clone(), |cfg| configure(i, cfg)).await?;
                 peers.push(peer);
             }
         },
@@ -304,12 +311,21 @@ impl Drop for Testnet {
 }
 
 pub fn run(config: Config) -> anyhow::Result<Testnet> {
+    run_with(config, |_, _| {})
+}
+
+/// Like [`run`], but allows to adjust the [`protocol::Config`] of each peer,
+/// identified by its index.
+pub fn run_with<F>(config: Config, configure: F) -> anyhow::Result<Testnet>
+where
+    F: Fn(usize, &mut protocol::Config),
+{
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()?;
 
     let min_connected = config.min_connected;
-    let bootstrapped = rt.block_on(bootstrap(config))?;
+    let bootstrapped = rt.block_on(bootstrap(config, configure))?;
     let num_peers = bootstrapped.len();
 
     let mut sig = Vec::with_capacity(num_peers);
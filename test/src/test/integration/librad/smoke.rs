@@ -4,9 +4,16 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 mod clone;
+mod dry_run;
+mod enrollment;
 mod fetch_limit;
 mod gossip;
 mod graft;
 mod interrogation;
+mod partial;
+mod progress;
+mod proposal;
 mod regression;
+mod relay;
 mod saturation;
+mod sync;
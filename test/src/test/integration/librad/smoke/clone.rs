@@ -18,8 +18,11 @@ use librad::{
         identities,
         replication,
         storage::{fetcher, ReadOnlyStorage as _},
+        tracking,
         types::{Namespace, Reference},
+        Urn,
     },
+    identities::payload,
 };
 
 fn default_config() -> testnet::Config {
@@ -131,6 +134,86 @@ fn when_disconnected_and_no_addr_hints() {
     )
 }
 
+/// A failed clone of a URN for which any peer is tracked should not leave the
+/// remote peer tracked.
+#[test]
+fn failed_clone_does_not_track() {
+    logging::init();
+
+    let net = testnet::run(default_config()).unwrap();
+    net.enter(async {
+        let host = net.peers().index(0);
+        let leecher = net.peers().index(1);
+
+        // The host doesn't have this URN
+        let urn = Urn::new(git2::Oid::zero().into());
+        let host_id = host.peer_id();
+        let host_addrs = host.listen_addrs().iter().copied().collect::<Vec<_>>();
+        let cfg = leecher.protocol_config().replication;
+        leecher
+            .using_storage(move |storage| {
+                tracking::track_any(storage, &urn).unwrap();
+
+                let fetcher = fetcher::PeerToPeer::new(urn.clone(), host_id, host_addrs)
+                    .build(storage)
+                    .unwrap()
+                    .unwrap();
+                let res = replication::replicate(storage, fetcher, cfg, None);
+                assert!(res.is_err());
+                assert!(!tracking::is_tracked(storage, &urn, host_id).unwrap());
+            })
+            .await
+            .unwrap();
+    })
+}
+
+/// Cloning a second project of a delegate we already know about must not
+/// conflict with the delegate's identity adopted by the first clone.
+#[test]
+fn projects_sharing_a_delegate() {
+    logging::init();
+
+    let net = testnet::run(default_config()).unwrap();
+    net.enter(async {
+        let host = net.peers().index(0);
+        let leecher = net.peers().index(1);
+
+        let (first, second) = host
+            .using_storage(move |storage| {
+                let first = TestProject::create(storage)?;
+                let second = TestProject::from_project_payload(
+                    storage,
+                    first.owner.clone(),
+                    payload::Project {
+                        name: "radicle-surf".into(),
+                        description: None,
+                        default_branch: Some("next".into()),
+                    },
+                )?;
+                Ok::<_, anyhow::Error>((first, second))
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        first.pull(host, leecher).await.unwrap();
+        second.pull(host, leecher).await.unwrap();
+
+        let owner = first.owner.urn();
+        let urns = vec![first.project.urn(), second.project.urn()];
+        leecher
+            .using_storage(move |storage| {
+                assert!(storage.has_urn(&owner).unwrap());
+                for urn in urns {
+                    assert!(storage.has_urn(&urn).unwrap());
+                    assert!(identities::project::get(storage, &urn).unwrap().is_some());
+                }
+            })
+            .await
+            .unwrap();
+    })
+}
+
 struct Host<'a> {
     project: TestProject,
     peer: &'a RunningTestPeer,
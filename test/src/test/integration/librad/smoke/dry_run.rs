@@ -0,0 +1,87 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::ops::Index as _;
+
+use crate::{
+    logging,
+    rad::{identities::TestProject, testnet},
+};
+use librad::{
+    self,
+    git::{
+        replication::{self, dry_run},
+        storage::{fetcher, ReadOnlyStorage as _},
+    },
+    reflike,
+};
+
+fn config() -> testnet::Config {
+    testnet::Config {
+        num_peers: nonzero!(2usize),
+        min_connected: 2,
+        bootstrap: testnet::Bootstrap::from_env(),
+    }
+}
+
+/// A dry-run clone reports what would be fetched, but leaves the storage
+/// untouched.
+#[test]
+fn clone_leaves_storage_untouched() {
+    logging::init();
+
+    let net = testnet::run(config()).unwrap();
+    net.enter(async {
+        let host = net.peers().index(0);
+        let leecher = net.peers().index(1);
+
+        let TestProject { project, owner } = host
+            .using_storage(move |storage| TestProject::create(storage))
+            .await
+            .unwrap()
+            .unwrap();
+        let urn = project.urn();
+        let host_peer = host.peer_id();
+        let host_addrs = host.listen_addrs().iter().copied().collect::<Vec<_>>();
+        let cfg = leecher.protocol_config().replication;
+
+        leecher
+            .using_storage(move |storage| {
+                let fetcher = fetcher::PeerToPeer::new(urn.clone(), host_peer, host_addrs)
+                    .build(storage)
+                    .unwrap()
+                    .unwrap();
+                let report = replication::dry_run(storage, fetcher, cfg).unwrap();
+
+                assert!(matches!(
+                    report.result,
+                    Ok(replication::ReplicateResult {
+                        mode: replication::Mode::Clone,
+                        ..
+                    })
+                ));
+
+                let host_refs = &report.refs[&urn][&Some(host_peer)];
+                assert!(host_refs.new.contains_key(&reflike!("rad/id")));
+                assert!(host_refs.new.contains_key(&reflike!("rad/signed_refs")));
+                assert!(host_refs.updated.is_empty());
+                assert!(host_refs.removed.is_empty());
+                assert!(report.refs.contains_key(&owner.urn()));
+
+                assert_eq!(
+                    report.identities[&urn][&Some(host_peer)],
+                    dry_run::IdentityChange {
+                        old: None,
+                        new: Some(project.revision),
+                    }
+                );
+
+                assert!(!storage.has_urn(&urn).unwrap());
+                assert!(!storage.has_urn(&owner.urn()).unwrap());
+            })
+            .await
+            .unwrap();
+    })
+}
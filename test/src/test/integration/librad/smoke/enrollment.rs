@@ -0,0 +1,82 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::ops::Index as _;
+
+use librad::git::identities::{self, enrollment};
+
+use crate::{
+    logging,
+    rad::{identities::TestPerson, testnet},
+};
+
+fn config() -> testnet::Config {
+    testnet::Config {
+        num_peers: nonzero!(2usize),
+        min_connected: 2,
+        bootstrap: testnet::Bootstrap::from_env(),
+    }
+}
+
+/// Enroll a second device into a person, and check that both devices converge
+/// on the co-signed revision.
+#[test]
+fn enroll_second_device() {
+    logging::init();
+
+    let net = testnet::run(config()).unwrap();
+    net.enter(async {
+        let laptop = net.peers().index(0);
+        let phone = net.peers().index(1);
+
+        let person = laptop
+            .using_storage(move |storage| TestPerson::create(storage))
+            .await
+            .unwrap()
+            .unwrap();
+        let urn = person.owner.urn();
+        person.pull(laptop, phone).await.unwrap();
+
+        let request = enrollment::Request::new(&urn, phone.signer()).unwrap();
+        laptop
+            .using_storage(move |storage| enrollment::enroll(storage, &request))
+            .await
+            .unwrap()
+            .unwrap();
+
+        person.pull(laptop, phone).await.unwrap();
+        let local_id = phone
+            .using_storage({
+                let urn = urn.clone();
+                let from = laptop.peer_id();
+                move |storage| enrollment::complete(storage, &urn, from)
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(local_id.signatures.contains_key(&laptop.peer_id()));
+        assert!(local_id.signatures.contains_key(&phone.peer_id()));
+
+        person.pull(phone, laptop).await.unwrap();
+        let on_laptop = laptop
+            .using_storage({
+                let urn = urn.clone();
+                move |storage| identities::person::verify(storage, &urn)
+            })
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(on_laptop.content_id, local_id.content_id);
+
+        let default = phone
+            .using_storage(move |storage| identities::local::default(storage))
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(default.urn(), urn);
+    })
+}
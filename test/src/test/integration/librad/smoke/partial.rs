@@ -0,0 +1,158 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{num::NonZeroU32, ops::Index as _};
+
+use blocking::unblock;
+use librad::{
+    git::{
+        fetch,
+        local::url::LocalUrl,
+        replication,
+        storage::{shallow, ReadOnlyStorage as _},
+        types::{remote, Fetchspec, Force, Remote},
+    },
+    git_ext as ext,
+    reflike,
+    refspec_pattern,
+};
+use tempfile::tempdir;
+
+use crate::{
+    git::create_commit,
+    logging,
+    rad::{identities::TestProject, testnet},
+};
+
+fn config() -> testnet::Config {
+    testnet::Config {
+        num_peers: nonzero!(2usize),
+        min_connected: 2,
+        bootstrap: testnet::Bootstrap::from_env(),
+    }
+}
+
+/// Clone a project with a history of two commits with depth 1, then deepen it.
+#[test]
+fn shallow_clone_and_deepen() {
+    logging::init();
+
+    let net = testnet::run(config()).unwrap();
+    net.enter(async {
+        let host = net.peers().index(0);
+        let leecher = net.peers().index(1);
+
+        let TestProject { project, .. } = host
+            .using_storage(move |storage| TestProject::create(storage))
+            .await
+            .unwrap()
+            .unwrap();
+        let urn = project.urn();
+
+        let repo_path = tempdir().unwrap();
+        let (root, tip) = unblock({
+            let repo_path = repo_path.path().to_path_buf();
+            let urn = urn.clone();
+            let host = (*host).clone();
+            move || {
+                let master = reflike!("refs/heads/master");
+                let repo = git2::Repository::init(&repo_path).unwrap();
+                let root = create_commit(&repo, master.clone()).unwrap();
+                let tip = {
+                    let author = git2::Signature::now("The Animal", "animal@muppets.com").unwrap();
+                    let parent = repo.find_commit(root).unwrap();
+                    repo.commit(
+                        Some(master.as_str()),
+                        &author,
+                        &author,
+                        "Second commit",
+                        &parent.tree().unwrap(),
+                        &[&parent],
+                    )
+                    .unwrap()
+                };
+
+                Remote::rad_remote::<_, Fetchspec>(LocalUrl::from(urn), None)
+                    .push(
+                        host,
+                        &repo,
+                        remote::LocalPushspec::Matching {
+                            pattern: refspec_pattern!("refs/heads/*"),
+                            force: Force::True,
+                        },
+                    )
+                    .unwrap()
+                    .for_each(drop);
+
+                (root, tip)
+            }
+        })
+        .await;
+
+        let host_peer = host.peer_id();
+        let host_addrs = host.listen_addrs().iter().copied().collect::<Vec<_>>();
+        let partial = fetch::Partial {
+            depth: NonZeroU32::new(1),
+            filter: None,
+        };
+        let result = leecher
+            .replicate(
+                (host_peer, host_addrs.clone()),
+                urn.clone(),
+                replication::Config {
+                    partial,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(result.mode, replication::Mode::Clone));
+        assert_eq!(result.partial, partial);
+
+        leecher
+            .using_storage(move |storage| {
+                let repo = git2::Repository::open_bare(storage.path()).unwrap();
+                assert!(storage.has_object(ext::Oid::from(tip)).unwrap());
+                assert!(!storage.has_object(ext::Oid::from(root)).unwrap());
+                assert_eq!(
+                    shallow::read(&repo).unwrap(),
+                    vec![tip.into()].into_iter().collect()
+                );
+            })
+            .await
+            .unwrap();
+
+        // Deepen to the full history
+        leecher
+            .using_storage({
+                let urn = urn.clone();
+                move |storage| {
+                    storage
+                        .config()
+                        .unwrap()
+                        .set_partial(&urn, fetch::Partial::default())
+                        .unwrap()
+                }
+            })
+            .await
+            .unwrap();
+        let result = leecher
+            .replicate((host_peer, host_addrs), urn.clone(), None, None)
+            .await
+            .unwrap();
+        assert!(matches!(result.mode, replication::Mode::Fetch));
+        assert!(result.partial.is_full());
+
+        leecher
+            .using_storage(move |storage| {
+                let repo = git2::Repository::open_bare(storage.path()).unwrap();
+                assert!(storage.has_object(ext::Oid::from(root)).unwrap());
+                assert!(shallow::read(&repo).unwrap().is_empty());
+            })
+            .await
+            .unwrap();
+    })
+}
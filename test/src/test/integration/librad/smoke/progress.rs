@@ -0,0 +1,137 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{ops::Index as _, time::Duration};
+
+use futures::StreamExt;
+
+use crate::{
+    logging,
+    rad::{identities::TestProject, testnet},
+};
+use librad::{
+    git::{fetch, replication, storage::ReadOnlyStorage as _},
+    net::protocol::event::{self, upstream, Upstream},
+};
+
+fn config() -> testnet::Config {
+    testnet::Config {
+        num_peers: nonzero!(2usize),
+        min_connected: 2,
+        bootstrap: testnet::Bootstrap::from_env(),
+    }
+}
+
+/// Replicating via the `Peer` reports the progress of the fetches.
+#[test]
+fn replicate_reports_progress() {
+    logging::init();
+
+    let net = testnet::run(config()).unwrap();
+    net.enter(async {
+        let host = net.peers().index(0);
+        let leecher = net.peers().index(1);
+
+        let TestProject { project, .. } = host
+            .using_storage(move |storage| TestProject::create(storage))
+            .await
+            .unwrap()
+            .unwrap();
+        let urn = project.urn();
+        let host_peer = host.peer_id();
+        let host_addrs = host.listen_addrs().iter().copied().collect::<Vec<_>>();
+
+        let events = leecher.subscribe();
+        let result = leecher
+            .replicate((host_peer, host_addrs), urn.clone(), None, None)
+            .await
+            .unwrap();
+        assert!(matches!(result.mode, replication::Mode::Clone));
+
+        let progress = event::upstream::expect(
+            events.boxed(),
+            {
+                let urn = urn.clone();
+                move |event| {
+                    matches!(
+                        event,
+                        Upstream::Replication(upstream::Replication::Progress {
+                            urn: progress_urn,
+                            remote_peer,
+                            progress: fetch::Progress {
+                                phase: fetch::Phase::Peek,
+                                ..
+                            },
+                        }) if progress_urn == &urn && remote_peer == &host_peer
+                    )
+                }
+            },
+            Duration::from_secs(5),
+        )
+        .await;
+        assert!(progress.is_ok());
+
+        let has_urn = leecher
+            .using_storage(move |storage| storage.has_urn(&urn))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(has_urn)
+    })
+}
+
+/// Fetches which transfer no objects still report their final progress.
+#[test]
+fn refetch_reports_final_progress() {
+    logging::init();
+
+    let net = testnet::run(config()).unwrap();
+    net.enter(async {
+        let host = net.peers().index(0);
+        let leecher = net.peers().index(1);
+
+        let TestProject { project, .. } = host
+            .using_storage(move |storage| TestProject::create(storage))
+            .await
+            .unwrap()
+            .unwrap();
+        let urn = project.urn();
+        let host_peer = host.peer_id();
+        let host_addrs = host.listen_addrs().iter().copied().collect::<Vec<_>>();
+
+        leecher
+            .replicate((host_peer, host_addrs.clone()), urn.clone(), None, None)
+            .await
+            .unwrap();
+
+        // Nothing changed on the host, so there is nothing to transfer
+        let events = leecher.subscribe();
+        let result = leecher
+            .replicate((host_peer, host_addrs), urn.clone(), None, None)
+            .await
+            .unwrap();
+        assert!(matches!(result.mode, replication::Mode::Fetch));
+
+        let progress = event::upstream::expect(
+            events.boxed(),
+            move |event| {
+                matches!(
+                    event,
+                    Upstream::Replication(upstream::Replication::Progress {
+                        urn: progress_urn,
+                        remote_peer,
+                        progress: fetch::Progress {
+                            total_objects: 0,
+                            ..
+                        },
+                    }) if progress_urn == &urn && remote_peer == &host_peer
+                )
+            },
+            Duration::from_secs(5),
+        )
+        .await;
+        assert!(progress.is_ok());
+    })
+}
@@ -0,0 +1,109 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::ops::Index as _;
+
+use either::Either;
+use librad::{
+    git::identities::{self, audit::Delegate, project::proposal},
+    identities::delegation,
+};
+
+use crate::{
+    logging,
+    rad::{identities::TestProject, testnet},
+};
+
+fn config() -> testnet::Config {
+    testnet::Config {
+        num_peers: nonzero!(2usize),
+        min_connected: 2,
+        bootstrap: testnet::Bootstrap::from_env(),
+    }
+}
+
+/// Propose to add a maintainer to a project, and let the new maintainer
+/// discover and co-sign the proposal.
+#[test]
+fn cosign_added_maintainer() {
+    logging::init();
+
+    let net = testnet::run(config()).unwrap();
+    net.enter(async {
+        let peer1 = net.peers().index(0);
+        let peer2 = net.peers().index(1);
+
+        let proj = peer1
+            .using_storage(move |storage| TestProject::create(storage))
+            .await
+            .unwrap()
+            .unwrap();
+        let urn = proj.project.urn();
+        proj.pull(peer1, peer2).await.unwrap();
+
+        let key = *peer2.peer_id().as_public_key();
+        let proposed = peer1
+            .using_storage({
+                let urn = urn.clone();
+                let owner = proj.owner.clone();
+                move |storage| {
+                    identities::project::update(
+                        storage,
+                        &urn,
+                        None,
+                        None,
+                        delegation::Indirect::try_from_iter(vec![
+                            Either::Left(key),
+                            Either::Right(owner),
+                        ])
+                        .unwrap(),
+                    )
+                }
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        proj.pull(peer1, peer2).await.unwrap();
+        let proposals = peer2
+            .using_storage({
+                let urn = urn.clone();
+                move |storage| proposal::list(storage, &urn)
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        match proposals.as_slice() {
+            [pending] => {
+                assert_eq!(pending.revision(), proposed.revision);
+                assert!(pending.proposers.contains(&peer1.peer_id()));
+                assert_eq!(
+                    pending.delegations.added,
+                    Some(Delegate::Key(key)).into_iter().collect()
+                );
+                assert!(pending.payload.is_none());
+            },
+            _ => panic!("expected one proposal, got {:?}", proposals),
+        }
+
+        let (cosigned, verified, pending) = peer2
+            .using_storage({
+                let urn = urn.clone();
+                let revision = proposed.revision;
+                move |storage| -> anyhow::Result<_> {
+                    let cosigned = proposal::cosign(storage, &urn, revision)?;
+                    let verified = identities::project::verify(storage, &urn)?.unwrap();
+                    let pending = proposal::list(storage, &urn)?;
+                    Ok((cosigned, verified, pending))
+                }
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(cosigned.signatures.contains_key(&key));
+        assert_eq!(verified.content_id, cosigned.content_id);
+        assert!(pending.is_empty());
+    })
+}
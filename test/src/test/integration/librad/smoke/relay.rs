@@ -0,0 +1,115 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{net::SocketAddr, ops::Index as _, time::Duration};
+
+use nonempty::NonEmpty;
+
+use librad::{
+    git::storage::ReadOnlyStorage as _,
+    net::protocol::{
+        self,
+        event::{self, upstream::predicate},
+        gossip,
+    },
+};
+
+use crate::{
+    logging,
+    rad::{identities::TestProject, testnet},
+};
+
+/// An address from the documentation range, which is never dialed.
+const UNREACHABLE: &str = "192.0.2.1:12345";
+
+fn config() -> testnet::Config {
+    testnet::Config {
+        num_peers: nonzero!(3usize),
+        min_connected: 3,
+        // The topology depends on each peer bootstrapping through the previous
+        // one
+        bootstrap: testnet::Bootstrap::Prev,
+    }
+}
+
+/// Arrange the peers in a line, with the one in the middle acting as a relay.
+///
+/// The outer peers keep only a single active connection, so they don't connect
+/// to each other. The last peer can't be dialed, so gossip can reach it only
+/// through the relay.
+fn configure(i: usize, cfg: &mut protocol::Config) {
+    if i == 1 {
+        cfg.relay = true;
+    } else {
+        cfg.membership.max_active = 1;
+    }
+    if i == 2 {
+        cfg.advertised_addrs = Some(NonEmpty::new(UNREACHABLE.parse::<SocketAddr>().unwrap()));
+    }
+}
+
+#[test]
+fn relays_git_and_gossip() {
+    logging::init();
+
+    let net = testnet::run_with(config(), configure).unwrap();
+    net.enter(async {
+        let alice = net.peers().index(0);
+        let relay = net.peers().index(1);
+        let carol = net.peers().index(2);
+
+        assert!(!carol.connected_peers().await.contains(&alice.peer_id()));
+
+        let project = alice
+            .using_storage(move |s| TestProject::create(s))
+            .await
+            .unwrap()
+            .unwrap();
+        let urn = project.project.urn();
+
+        // Without any addresses to dial, carol fetches via the relay
+        carol
+            .replicate((alice.peer_id(), vec![]), urn.clone(), None, None)
+            .await
+            .unwrap();
+        assert!(carol
+            .using_storage({
+                let urn = urn.clone();
+                move |s| s.has_urn(&urn)
+            })
+            .await
+            .unwrap()
+            .unwrap());
+        assert!(!relay
+            .using_storage({
+                let urn = urn.clone();
+                move |s| s.has_urn(&urn)
+            })
+            .await
+            .unwrap()
+            .unwrap());
+
+        // Alice can't reach carol directly, so she answers carol's query via
+        // the relay
+        let carol_events = carol.subscribe();
+        carol
+            .query(gossip::Payload {
+                origin: None,
+                urn,
+                rev: None,
+            })
+            .unwrap();
+        futures::pin_mut!(carol_events);
+        event::upstream::expect(
+            carol_events,
+            predicate::gossip_from(alice.peer_id()),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+
+        assert!(!carol.connected_peers().await.contains(&alice.peer_id()));
+    })
+}
@@ -0,0 +1,161 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::ops::Index as _;
+
+use crate::{
+    logging,
+    rad::{identities::TestProject, testnet},
+};
+use librad::{
+    git::{storage::ReadOnlyStorage as _, util},
+    git_ext::tree,
+    reflike,
+};
+
+fn config() -> testnet::Config {
+    testnet::Config {
+        num_peers: nonzero!(2usize),
+        min_connected: 2,
+        bootstrap: testnet::Bootstrap::from_env(),
+    }
+}
+
+/// Syncing only replicates the URNs which diverge from the remote peer.
+#[test]
+fn sync_replicates_diverged() {
+    logging::init();
+
+    let net = testnet::run(config()).unwrap();
+    net.enter(async {
+        let alice = net.peers().index(0);
+        let bob = net.peers().index(1);
+        let alice_addrs = alice.listen_addrs().iter().copied().collect::<Vec<_>>();
+
+        let project = alice
+            .using_storage(move |s| TestProject::create(s))
+            .await
+            .unwrap()
+            .unwrap();
+        project.pull(alice, bob).await.unwrap();
+        let urn = project.project.urn();
+
+        let bobs_own = bob
+            .using_storage(move |s| TestProject::create(s))
+            .await
+            .unwrap()
+            .unwrap()
+            .project
+            .urn();
+
+        // Nothing changed since bob pulled, and alice doesn't know bob's project
+        let summary = bob
+            .sync(
+                (alice.peer_id(), alice_addrs.clone()),
+                vec![urn.clone(), bobs_own.clone()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(summary.in_sync, vec![urn.clone()]);
+        assert_eq!(summary.unknown, vec![bobs_own]);
+        assert!(summary.replicated.is_empty());
+        assert!(summary.failed.is_empty());
+
+        // Alice moves ahead, so bob should replicate
+        let commit_urn = urn.clone().with_path(reflike!("refs/heads/next"));
+        let commit_id = alice
+            .using_storage({
+                let urn = commit_urn.clone();
+                move |s| {
+                    util::quick_commit(
+                        s,
+                        &urn,
+                        vec![("HI", tree::blob(b"Hi Bob"))].into_iter().collect(),
+                        "say hi to bob",
+                    )
+                }
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        let summary = bob
+            .sync((alice.peer_id(), alice_addrs), vec![urn.clone()])
+            .await
+            .unwrap();
+        assert_eq!(summary.replicated, vec![urn.clone()]);
+        assert!(summary.in_sync.is_empty());
+
+        let bob_has_commit = bob
+            .using_storage({
+                let urn = commit_urn.map_path(|path| {
+                    path.map(|path| {
+                        reflike!("refs/remotes")
+                            .join(alice.peer_id())
+                            .join(path.strip_prefix("refs").unwrap())
+                    })
+                });
+                move |s| s.has_commit(&urn, Box::new(commit_id))
+            })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(bob_has_commit, "bob is missing alice's commit");
+    })
+}
+
+/// A responder which the initiator doesn't track is not compared by its own
+/// tip.
+#[test]
+fn sync_ignores_untracked_responder() {
+    logging::init();
+
+    let net = testnet::run(testnet::Config {
+        num_peers: nonzero!(3usize),
+        min_connected: 3,
+        bootstrap: testnet::Bootstrap::from_env(),
+    })
+    .unwrap();
+    net.enter(async {
+        let alice = net.peers().index(0);
+        let bob = net.peers().index(1);
+        let carol = net.peers().index(2);
+        let carol_addrs = carol.listen_addrs().iter().copied().collect::<Vec<_>>();
+
+        let project = alice
+            .using_storage(move |s| TestProject::create(s))
+            .await
+            .unwrap()
+            .unwrap();
+        project.pull(alice, bob).await.unwrap();
+        project.pull(alice, carol).await.unwrap();
+        let urn = project.project.urn();
+
+        // Carol moves ahead, but bob only tracks alice
+        carol
+            .using_storage({
+                let urn = urn.clone().with_path(reflike!("refs/heads/next"));
+                move |s| {
+                    util::quick_commit(
+                        s,
+                        &urn,
+                        vec![("HI", tree::blob(b"Hi Bob"))].into_iter().collect(),
+                        "say hi to bob",
+                    )
+                }
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        let summary = bob
+            .sync((carol.peer_id(), carol_addrs), vec![urn.clone()])
+            .await
+            .unwrap();
+        assert_eq!(summary.in_sync, vec![urn]);
+        assert!(summary.replicated.is_empty());
+        assert!(summary.failed.is_empty());
+    })
+}
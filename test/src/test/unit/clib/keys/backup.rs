@@ -0,0 +1,124 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use assert_matches::assert_matches;
+use link_clib::keys::{
+    backup::{self, Backup, Error},
+    file_storage,
+    Passphrase,
+};
+use radicle_keystore::{
+    crypto::{Pwhash, KDF_PARAMS_TEST},
+    pinentry::SecUtf8,
+    Keystore as _,
+};
+
+use librad::{git::storage::Storage, keys::SecretKey, peer::PeerId, profile::Profile};
+
+fn passphrase(passphrase: &str) -> Pwhash<Passphrase> {
+    Pwhash::new(
+        Passphrase::from(SecUtf8::from(passphrase)),
+        *KDF_PARAMS_TEST,
+    )
+}
+
+fn profile_with_key(key: SecretKey) -> (tempfile::TempDir, Profile) {
+    let tmp = tempfile::tempdir().unwrap();
+    let profile = Profile::from_root(tmp.path(), None).unwrap();
+    file_storage(&profile, passphrase("key"))
+        .put_key(key)
+        .unwrap();
+    (tmp, profile)
+}
+
+fn empty_profile() -> (tempfile::TempDir, Profile) {
+    let tmp = tempfile::tempdir().unwrap();
+    let profile = Profile::from_root(tmp.path(), None).unwrap();
+    (tmp, profile)
+}
+
+/// Serialise `backup`, apply `f` to the JSON, and read it back.
+fn tamper<F>(backup: &Backup, f: F) -> Result<Backup, Error>
+where
+    F: FnOnce(&mut serde_json::Value),
+{
+    let mut json = serde_json::to_value(backup).unwrap();
+    f(&mut json);
+    Backup::from_reader(serde_json::to_vec(&json).unwrap().as_slice())
+}
+
+#[test]
+fn export_import_roundtrip() {
+    let key = SecretKey::new();
+    let (_tmp, profile) = profile_with_key(key.clone());
+
+    let backup = backup::export(&profile, passphrase("key"), passphrase("backup")).unwrap();
+    assert_eq!(backup.peer_id, PeerId::from(&key));
+
+    let mut buf = Vec::new();
+    backup.to_writer(&mut buf).unwrap();
+    let backup = Backup::from_reader(buf.as_slice()).unwrap();
+
+    let (_tmp, imported) = empty_profile();
+    let peer_id =
+        backup::import(&imported, backup, passphrase("backup"), passphrase("new")).unwrap();
+    assert_eq!(peer_id, PeerId::from(&key));
+    assert_eq!(
+        file_storage(&imported, passphrase("new"))
+            .get_key()
+            .unwrap()
+            .secret_key
+            .public(),
+        key.public()
+    );
+}
+
+#[test]
+fn rejects_unknown_version() {
+    let (_tmp, profile) = profile_with_key(SecretKey::new());
+    let backup = backup::export(&profile, passphrase("key"), passphrase("backup")).unwrap();
+
+    assert_matches!(
+        tamper(&backup, |json| json["version"] = (backup::VERSION + 1).into()),
+        Err(Error::Version(v)) if v == backup::VERSION + 1
+    )
+}
+
+#[test]
+fn rejects_corrupt_backup() {
+    let key = SecretKey::new();
+    let (_tmp, profile) = profile_with_key(key.clone());
+    let backup = backup::export(&profile, passphrase("key"), passphrase("backup")).unwrap();
+
+    let other = PeerId::from(SecretKey::new());
+    let tampered = tamper(&backup, |json| {
+        json["peerId"] = serde_json::to_value(other).unwrap()
+    })
+    .unwrap();
+
+    let (_tmp, imported) = empty_profile();
+    assert_matches!(
+        backup::import(&imported, tampered, passphrase("backup"), passphrase("new")),
+        Err(Error::Corrupt { claimed, actual })
+            if claimed == other && actual == PeerId::from(&key)
+    )
+}
+
+#[test]
+fn rejects_storage_mismatch() {
+    let key = SecretKey::new();
+    let (_tmp, profile) = profile_with_key(key.clone());
+    let backup = backup::export(&profile, passphrase("key"), passphrase("backup")).unwrap();
+
+    let (_tmp, imported) = empty_profile();
+    let configured = SecretKey::new();
+    Storage::open(imported.paths(), configured.clone()).unwrap();
+
+    assert_matches!(
+        backup::import(&imported, backup, passphrase("backup"), passphrase("new")),
+        Err(Error::StorageMismatch { key: k, configured: c })
+            if k == PeerId::from(&key) && c == PeerId::from(&configured)
+    )
+}
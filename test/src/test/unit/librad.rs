@@ -12,3 +12,5 @@ mod net;
 mod paths;
 mod peer;
 mod profile;
+#[cfg(unix)]
+mod signer;
@@ -4,12 +4,14 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 mod fetch;
+mod identities;
 mod include;
 mod local;
 mod p2p;
 mod project;
 mod refs;
 mod replication;
+mod rotation;
 mod storage;
 mod tracking;
 mod trailer;
@@ -3,7 +3,10 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+};
 
 use pretty_assertions::assert_eq;
 
@@ -98,6 +101,7 @@ fn replicate_looks_legit() {
                 rad: Default::default(),
                 tags: Default::default(),
                 notes: Default::default(),
+                cobs: Default::default(),
                 remotes: Remotes::new(),
             },
         ),
@@ -114,6 +118,7 @@ fn replicate_looks_legit() {
                 rad: Default::default(),
                 tags: Default::default(),
                 notes: Default::default(),
+                cobs: Default::default(),
                 remotes: Remotes::new(),
             },
         ),
@@ -188,8 +193,10 @@ fn replicate_looks_legit() {
 
     let specs = Fetchspecs::Replicate {
         tracked_sigrefs,
+        policies: BTreeMap::new(),
         delegates,
         limit: Default::default(),
+        partial: Default::default(),
     }
     .refspecs(&*PROJECT_URN, TOLA.clone(), &remote_heads);
 
@@ -315,3 +322,171 @@ fn replicate_looks_legit() {
         .collect::<BTreeSet<String>>()
     )
 }
+
+#[test]
+fn replicate_honours_policy() {
+    use librad::git::{
+        refs::{Refs, Remotes},
+        tracking::Policy,
+    };
+
+    lazy_static! {
+        static ref ZERO: ext::Oid = ext::Oid::from(git2::Oid::zero());
+    }
+
+    let tracked_sigrefs = Some((
+        LOLEK.clone(),
+        Refs {
+            heads: [
+                (ext::OneLevel::from(reflike!("main")), *ZERO),
+                (ext::OneLevel::from(reflike!("wip")), *ZERO),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+            rad: Default::default(),
+            tags: [(ext::OneLevel::from(reflike!("v1")), *ZERO)]
+                .iter()
+                .cloned()
+                .collect(),
+            notes: Default::default(),
+            cobs: Default::default(),
+            remotes: Remotes::new(),
+        },
+    ))
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+
+    let policies = Some((
+        LOLEK.clone(),
+        Policy {
+            allow: [refspec_pattern!("heads/main"), refspec_pattern!("tags/*")]
+                .iter()
+                .cloned()
+                .collect(),
+            ..Default::default()
+        },
+    ))
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+
+    let remote_heads = ["refs/heads/main", "refs/heads/wip", "refs/tags/v1"]
+        .iter()
+        .map(|name| {
+            (
+                PROJECT_NAMESPACE
+                    .join(reflike!("refs/remotes/lolek"))
+                    .join(ext::RefLike::try_from(name.strip_prefix("refs/").unwrap()).unwrap()),
+                *ZERO,
+            )
+        })
+        .collect::<BTreeMap<_, _>>()
+        .into();
+
+    let specs = Fetchspecs::Replicate {
+        tracked_sigrefs,
+        policies,
+        delegates: BTreeSet::new(),
+        limit: Default::default(),
+        partial: Default::default(),
+    }
+    .refspecs(&*PROJECT_URN, TOLA.clone(), &remote_heads)
+    .into_iter()
+    .map(|spec| spec.to_string())
+    .collect::<BTreeSet<_>>();
+
+    let lolek_spec = |name: &str| {
+        let name = PROJECT_NAMESPACE
+            .join(reflike!("refs/remotes/lolek"))
+            .join(ext::RefLike::try_from(name).unwrap());
+        format!("{}:{}", name, name)
+    };
+
+    assert!(specs.contains(&lolek_spec("heads/main")));
+    assert!(specs.contains(&lolek_spec("tags/v1")));
+    assert!(!specs.contains(&lolek_spec("heads/wip")));
+}
+
+#[test]
+fn replicate_honours_cobs_deny() {
+    use librad::git::{
+        refs::{Refs, Remotes},
+        tracking::Policy,
+    };
+
+    lazy_static! {
+        static ref ZERO: ext::Oid = ext::Oid::from(git2::Oid::zero());
+    }
+
+    let tracked_sigrefs = Some((
+        LOLEK.clone(),
+        Refs {
+            heads: [(ext::OneLevel::from(reflike!("main")), *ZERO)]
+                .iter()
+                .cloned()
+                .collect(),
+            rad: Default::default(),
+            tags: Default::default(),
+            notes: Default::default(),
+            cobs: [(ext::OneLevel::from(reflike!("issues/1")), *ZERO)]
+                .iter()
+                .cloned()
+                .collect(),
+            remotes: Remotes::new(),
+        },
+    ))
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+
+    let remote_heads = ["refs/heads/main", "refs/cobs/issues/1"]
+        .iter()
+        .map(|name| {
+            (
+                PROJECT_NAMESPACE
+                    .join(reflike!("refs/remotes/lolek"))
+                    .join(ext::RefLike::try_from(name.strip_prefix("refs/").unwrap()).unwrap()),
+                *ZERO,
+            )
+        })
+        .collect::<BTreeMap<_, _>>()
+        .into();
+
+    let specs = |policies: BTreeMap<_, _>| {
+        Fetchspecs::Replicate {
+            tracked_sigrefs: tracked_sigrefs.clone(),
+            policies,
+            delegates: BTreeSet::new(),
+            limit: Default::default(),
+            partial: Default::default(),
+        }
+        .refspecs(&*PROJECT_URN, TOLA.clone(), &remote_heads)
+        .into_iter()
+        .map(|spec| spec.to_string())
+        .collect::<BTreeSet<_>>()
+    };
+
+    let lolek_spec = |name: &str| {
+        let name = PROJECT_NAMESPACE
+            .join(reflike!("refs/remotes/lolek"))
+            .join(ext::RefLike::try_from(name).unwrap());
+        format!("{}:{}", name, name)
+    };
+
+    let everything = specs(BTreeMap::new());
+    assert!(everything.contains(&lolek_spec("heads/main")));
+    assert!(everything.contains(&lolek_spec("cobs/issues/1")));
+
+    let except_cobs = specs(
+        Some((
+            LOLEK.clone(),
+            Policy {
+                deny: Some(refspec_pattern!("cobs/*")).into_iter().collect(),
+                ..Default::default()
+            },
+        ))
+        .into_iter()
+        .collect(),
+    );
+    assert!(except_cobs.contains(&lolek_spec("heads/main")));
+    assert!(!except_cobs.contains(&lolek_spec("cobs/issues/1")));
+}
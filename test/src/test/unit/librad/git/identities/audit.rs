@@ -0,0 +1,94 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use librad::{
+    git::identities::{
+        audit::{self, Delegate, Status},
+        enrollment::{self, Request},
+    },
+    keys::SecretKey,
+    peer::PeerId,
+};
+
+use crate::{
+    librad::git::storage::storage,
+    rad::identities::{TestPerson, TestProject},
+};
+
+#[test]
+fn person_with_proposal() {
+    let key = SecretKey::new();
+    let store = storage(key.clone());
+    let TestPerson { owner } = TestPerson::create(&store).unwrap();
+    let urn = owner.urn();
+    let device = SecretKey::new();
+    let proposal = enrollment::enroll(&store, &Request::new(&urn, &device).unwrap()).unwrap();
+
+    let audit = audit::person(&*store, &urn).unwrap().unwrap();
+    assert_eq!(audit.urn, urn);
+    assert_eq!(audit.verified().unwrap().content_id, owner.content_id);
+    match audit.revisions.as_slice() {
+        [root, next] => {
+            assert_eq!(root.status, Status::Verified);
+            assert!(root.quorum);
+            assert_eq!(
+                root.delegations.added,
+                Some(Delegate::Key(key.public())).into_iter().collect()
+            );
+
+            assert_eq!(next.content_id, proposal.content_id);
+            assert_eq!(next.replaces, Some(root.revision));
+            assert_eq!(next.status, Status::Proposal);
+            assert!(!next.quorum);
+            assert_eq!(
+                next.delegations.added,
+                Some(Delegate::Key(device.public())).into_iter().collect()
+            );
+            assert!(next.delegations.removed.is_empty());
+            match next.signatures.as_slice() {
+                [sig] => {
+                    assert_eq!(sig.peer, PeerId::from(&key));
+                    assert!(sig.eligible);
+                    assert_eq!(sig.person, None);
+                },
+                sigs => panic!("expected one signature, got {:?}", sigs),
+            }
+        },
+        revs => panic!("expected two revisions, got {:?}", revs),
+    }
+
+    let json = serde_json::to_value(&audit).unwrap();
+    assert_eq!(json["revisions"][1]["status"], "proposal");
+    assert_eq!(
+        json["revisions"][1]["delegations"]["added"][0]["key"],
+        serde_json::to_value(device.public()).unwrap()
+    );
+}
+
+#[test]
+fn project_indirect() {
+    let store = storage(SecretKey::new());
+    let TestProject { owner, project } = TestProject::create(&store).unwrap();
+
+    let audit = audit::project(&*store, &project.urn()).unwrap().unwrap();
+    match audit.revisions.as_slice() {
+        [root] => {
+            assert_eq!(root.status, Status::Verified);
+            assert_eq!(
+                root.delegations.added,
+                Some(Delegate::Person(owner.urn())).into_iter().collect()
+            );
+            assert_eq!(root.signatures[0].person, Some(owner.urn()));
+        },
+        revs => panic!("expected one revision, got {:?}", revs),
+    }
+}
+
+#[test]
+fn not_found() {
+    let TestPerson { owner } = TestPerson::create(&storage(SecretKey::new())).unwrap();
+    let store = storage(SecretKey::new());
+    assert!(audit::person(&*store, &owner.urn()).unwrap().is_none());
+}
@@ -0,0 +1,70 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use assert_matches::assert_matches;
+use librad::{
+    git::{
+        identities::{
+            enrollment::{self, Request},
+            person,
+        },
+        tracking,
+    },
+    keys::SecretKey,
+    peer::PeerId,
+};
+
+use crate::{librad::git::storage::storage, rad::identities::TestPerson};
+
+#[test]
+fn request_roundtrip() {
+    let key = SecretKey::new();
+    let urn = TestPerson::create(&storage(SecretKey::new()))
+        .unwrap()
+        .owner
+        .urn();
+    let request = Request::new(&urn, &key).unwrap();
+    assert_eq!(request.peer_id(), PeerId::from(&key));
+    assert!(request.verify().is_ok());
+
+    let json = serde_json::to_string(&request).unwrap();
+    let decoded: Request = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, request);
+}
+
+#[test]
+fn request_forged() {
+    let urn = TestPerson::create(&storage(SecretKey::new()))
+        .unwrap()
+        .owner
+        .urn();
+    let mut request = Request::new(&urn, &SecretKey::new()).unwrap();
+    request.key = *PeerId::from(SecretKey::new()).as_public_key();
+    assert_matches!(
+        request.verify(),
+        Err(enrollment::Error::InvalidSignature(_))
+    );
+}
+
+#[test]
+fn enroll_proposes_delegation() {
+    let store = storage(SecretKey::new());
+    let TestPerson { owner } = TestPerson::create(&store).unwrap();
+    let urn = owner.urn();
+    let device = SecretKey::new();
+    let request = Request::new(&urn, &device).unwrap();
+
+    let proposal = enrollment::enroll(&store, &request).unwrap();
+    assert!(proposal.delegations().contains(&request.key));
+    assert!(tracking::is_tracked(&store, &urn, request.peer_id()).unwrap());
+
+    // Not signed by the enrolled device yet
+    let verified = person::verify(&*store, &urn).unwrap().unwrap();
+    assert_eq!(verified.content_id, owner.content_id);
+
+    // Enrolling again is a no-op
+    let again = enrollment::enroll(&store, &request).unwrap();
+    assert_eq!(again.content_id, proposal.content_id);
+}
@@ -3,18 +3,31 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::convert::TryFrom as _;
+
+use either::Either;
+
 use librad::{
     git::{
-        include::{Error, Include},
+        include::{Error, Include, GIT_CONFIG_LFS_URL_KEY},
         local::url::LocalUrl,
         Urn,
     },
     git_ext as ext,
+    identities::{
+        delegation,
+        payload::{self, Contributor, UrlTemplate},
+        Identities,
+        Person,
+        Project,
+    },
     keys::SecretKey,
     peer::PeerId,
     reflike,
 };
 
+use crate::librad::git::repo;
+
 const LOCAL_SEED: [u8; 32] = [
     0, 10, 109, 178, 52, 203, 96, 195, 109, 177, 87, 178, 159, 70, 238, 41, 20, 168, 163, 180, 197,
     235, 118, 84, 216, 231, 56, 80, 83, 31, 227, 102,
@@ -136,3 +149,117 @@ fn can_create_and_update() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[test]
+fn largefiles() -> Result<(), Error> {
+    let tmp_dir = tempfile::tempdir()?;
+    let url = LocalUrl::from(Urn::new(git2::Oid::zero().into()));
+    let contributor = |template: &str| Contributor {
+        largefiles: Some(UrlTemplate::try_from(template).unwrap()),
+    };
+
+    let mut include = Include::new(tmp_dir.path().to_path_buf(), url);
+    assert_eq!(include.set_largefiles(&Contributor::default()), None);
+    assert_eq!(
+        include.set_largefiles(&contributor("ipfs://{SHA256_CID}")),
+        None
+    );
+    assert_eq!(
+        include
+            .set_largefiles(&contributor(
+                "https://github.com/me/rad-mirror.git/info/lfs/objects/batch"
+            ))
+            .map(|url| url.as_str()),
+        Some("https://github.com/me/rad-mirror.git/info/lfs")
+    );
+
+    let path = include.file_path();
+    include.save()?;
+    let config = git2::Config::open(&path)?;
+    assert_eq!(
+        config.get_string(GIT_CONFIG_LFS_URL_KEY)?,
+        "https://github.com/me/rad-mirror.git/info/lfs"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn largefiles_from_local_and_delegates() -> Result<(), Error> {
+    let tmp_dir = tempfile::tempdir()?;
+    let url = LocalUrl::from(Urn::new(git2::Oid::zero().into()));
+    let repo = repo().unwrap();
+    let person = |name: &str, largefiles: Option<&str>| {
+        let key = SecretKey::new();
+        let payload = payload::PersonPayload::from(payload::Person { name: name.into() })
+            .with_ext(Contributor {
+                largefiles: largefiles.map(|template| UrlTemplate::try_from(template).unwrap()),
+            })
+            .unwrap();
+        Identities::<Person>::from(&*repo)
+            .create(payload, Some(key.public()).into_iter().collect(), &key)
+            .unwrap()
+    };
+    let local = person("local", Some("https://lfs.example.com/local"));
+    let lyla = person("lyla", Some("https://lfs.example.com/lyla"));
+    let rover = person("rover", Some("ipfs://{SHA256_CID}"));
+    let lingling = person("lingling", Some("https://lfs.example.com/lingling"));
+    let project = Identities::<Project>::from(&*repo)
+        .create(
+            payload::Project {
+                name: "radicle-link".into(),
+                description: None,
+                default_branch: None,
+            }
+            .into(),
+            delegation::Indirect::try_from_iter(
+                vec![lyla.clone(), rover, lingling.clone()]
+                    .into_iter()
+                    .map(Either::Right),
+            )
+            .unwrap(),
+            &SecretKey::new(),
+        )
+        .unwrap();
+
+    let mut include = Include::from_tracked_persons(
+        tmp_dir.path().to_path_buf(),
+        url,
+        vec![
+            ((*LYLA_HANDLE).clone(), *LYLA_PEER_ID),
+            ((*ROVER_HANDLE).clone(), *ROVER_PEER_ID),
+            ((*LINGLING_HANDLE).clone(), *LINGLING_PEER_ID),
+        ],
+    );
+
+    // The delegates are considered in the order of their URNs
+    let first = if lyla.urn() < lingling.urn() {
+        "https://lfs.example.com/lyla"
+    } else {
+        "https://lfs.example.com/lingling"
+    };
+    assert_eq!(
+        include
+            .set_largefiles_from(None, &project)
+            .map(|url| url.as_str()),
+        Some(first)
+    );
+
+    // The local identity takes precedence
+    assert_eq!(
+        include
+            .set_largefiles_from(Some(&local), &project)
+            .map(|url| url.as_str()),
+        Some("https://lfs.example.com/local")
+    );
+
+    let path = include.file_path();
+    include.save()?;
+    let config = git2::Config::open(&path)?;
+    assert_eq!(
+        config.get_string(GIT_CONFIG_LFS_URL_KEY)?,
+        "https://lfs.example.com/local"
+    );
+
+    Ok(())
+}
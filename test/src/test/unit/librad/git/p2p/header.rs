@@ -3,9 +3,15 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::num::NonZeroU32;
+
 use git2::transport::Service as GitService;
 use librad::{
-    git::{p2p::header::Header, Urn},
+    git::{
+        fetch::{Filter, Partial},
+        p2p::header::Header,
+        Urn,
+    },
     git_ext as ext,
     keys::SecretKey,
     peer::PeerId,
@@ -34,3 +40,25 @@ fn roundtrip_nonced() {
 
     assert_eq!(hdr, hdr.to_string().parse::<Header<Urn>>().unwrap())
 }
+
+#[test]
+fn roundtrip_partial() {
+    let hdr = Header::new(
+        GitService::UploadPack,
+        Urn::new(ext::Oid::from(git2::Oid::zero())),
+        PeerId::from(SecretKey::new()),
+        Some(69),
+    )
+    .with_partial(
+        Partial {
+            depth: NonZeroU32::new(1),
+            filter: Some(Filter::BlobNone),
+        },
+        vec![
+            ext::Oid::from(git2::Oid::zero()),
+            ext::Oid::from(git2::Oid::hash_object(git2::ObjectType::Blob, b"x").unwrap()),
+        ],
+    );
+
+    assert_eq!(hdr, hdr.to_string().parse::<Header<Urn>>().unwrap())
+}
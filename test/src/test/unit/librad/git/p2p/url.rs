@@ -3,9 +3,20 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    num::NonZeroU32,
+};
 
-use librad::{git::p2p::url::GitUrl, identities::git, keys::SecretKey, peer::PeerId};
+use librad::{
+    git::{
+        fetch::{Filter, Partial},
+        p2p::url::GitUrl,
+    },
+    identities::git,
+    keys::SecretKey,
+    peer::PeerId,
+};
 
 use crate::roundtrip::str_roundtrip;
 
@@ -25,6 +36,26 @@ fn test_str_roundtrip() {
         ],
         repo: git::Revision::from(git2::Oid::zero()),
         nonce: Some(42),
+        partial: Partial::default(),
+        shallow: vec![],
+    };
+
+    str_roundtrip(url)
+}
+
+#[test]
+fn test_str_roundtrip_partial() {
+    let url = GitUrl {
+        local_peer: PeerId::from(SecretKey::new()),
+        remote_peer: PeerId::from(SecretKey::new()),
+        addr_hints: vec![],
+        repo: git::Revision::from(git2::Oid::zero()),
+        nonce: None,
+        partial: Partial {
+            depth: NonZeroU32::new(3),
+            filter: Some(Filter::BlobNone),
+        },
+        shallow: vec![git2::Oid::zero().into()],
     };
 
     str_roundtrip(url)
@@ -0,0 +1,172 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use librad::{
+    git::{
+        identities,
+        refs::Refs,
+        rotation,
+        storage::Storage,
+        tracking::{self, Policy},
+    },
+    identities::{
+        delegation,
+        git::Verifying,
+        payload::{self, Revocations},
+        Identities,
+        Person,
+    },
+    keys::SecretKey,
+    paths::Paths,
+    peer::PeerId,
+};
+
+use crate::{librad::git::storage::storage, rad::identities::TestPerson};
+
+#[test]
+fn rotate_person_and_storage() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = Paths::from_root(&tmp).unwrap();
+    let old_key = SecretKey::new();
+    let new_key = SecretKey::new();
+    let old = PeerId::from(&old_key);
+    let new = PeerId::from(&new_key);
+
+    let storage = Storage::open(&paths, old_key.clone()).unwrap();
+    let TestPerson { owner } = TestPerson::create(&storage).unwrap();
+    let urn = owner.urn();
+    let remote = PeerId::from(SecretKey::new());
+    tracking::track(&storage, &urn, remote).unwrap();
+
+    let (storage, rotated) = rotation::rotate(storage, &urn, new_key.clone()).unwrap();
+    assert_eq!(storage.peer_id(), &new);
+
+    let person = identities::person::verify(&storage, &urn).unwrap().unwrap();
+    assert_eq!(person.content_id, rotated.content_id);
+    assert!(person.delegations().contains(new.as_public_key()));
+    assert!(!person.delegations().contains(old.as_public_key()));
+    assert_eq!(
+        person.payload().get_ext::<Revocations>().unwrap(),
+        Some(Revocations {
+            keys: Some(*old.as_public_key()).into_iter().collect()
+        })
+    );
+    assert_eq!(
+        rotation::revoked(&storage).unwrap(),
+        Some(old).into_iter().collect()
+    );
+
+    // Signed by the new key
+    assert!(Refs::load(&storage, &urn, None).unwrap().is_some());
+    // Tracking remotes point to the new key
+    {
+        let repo = git2::Repository::open_bare(storage.path()).unwrap();
+        let remote = repo
+            .find_remote(&format!("{}/{}", urn.encode_id(), remote))
+            .unwrap();
+        assert!(remote.url().unwrap().contains(&new.to_string()));
+    }
+
+    drop(storage);
+    assert!(Storage::open(&paths, old_key).is_err());
+    assert!(Storage::open(&paths, new_key).is_ok());
+}
+
+#[test]
+fn ignores_revocation_of_foreign_key() {
+    let store = storage(SecretKey::new());
+    let TestPerson { owner } = TestPerson::create(&store).unwrap();
+    let urn = owner.urn();
+
+    let foreign = PeerId::from(SecretKey::new());
+    let payload = payload::PersonPayload::from(payload::Person {
+        name: "alice".into(),
+    })
+    .with_ext(Revocations {
+        keys: Some(*foreign.as_public_key()).into_iter().collect(),
+    })
+    .unwrap();
+    identities::person::update(&store, &urn, None, Some(payload), None).unwrap();
+
+    let person = identities::person::verify(&*store, &urn).unwrap().unwrap();
+    assert!(rotation::adopt_revocations(&store, &person)
+        .unwrap()
+        .is_empty());
+    assert!(rotation::revoked(&store).unwrap().is_empty())
+}
+
+#[test]
+fn ignores_revocation_by_non_delegate() {
+    let key = SecretKey::new();
+    let store = storage(key.clone());
+    let TestPerson { owner } = TestPerson::create(&store).unwrap();
+    let urn = owner.urn();
+
+    // Delegating to somebody else's key doesn't require their signature, so a
+    // revision listing it can reach a quorum without them
+    let sidekick = SecretKey::new();
+    let victim = PeerId::from(SecretKey::new());
+    let repo = git2::Repository::open_bare(store.path()).unwrap();
+    let ids = Identities::<Person>::from(&repo);
+    let parent = identities::person::verify(&*store, &urn).unwrap().unwrap();
+    let payload = parent
+        .payload()
+        .clone()
+        .with_ext(Revocations {
+            keys: Some(*victim.as_public_key()).into_iter().collect(),
+        })
+        .unwrap();
+    let delegations = delegation::Direct::from(
+        vec![key.public(), sidekick.public(), *victim.as_public_key()]
+            .into_iter()
+            .collect::<std::collections::BTreeSet<_>>(),
+    );
+    let proposal = ids
+        .update(
+            Verifying::from(owner).signed().unwrap(),
+            payload,
+            delegations,
+            &key,
+        )
+        .unwrap();
+    let revoking = ids
+        .create_from(Verifying::from(proposal).signed().unwrap(), &sidekick)
+        .unwrap();
+    let revoking = Verifying::from(revoking).verified(Some(&parent)).unwrap();
+
+    assert!(rotation::adopt_revocations(&store, &revoking)
+        .unwrap()
+        .is_empty());
+    assert!(rotation::revoked(&store).unwrap().is_empty())
+}
+
+#[test]
+fn replace_moves_tracking_and_policy() {
+    let store = storage(SecretKey::new());
+    let TestPerson { owner } = TestPerson::create(&store).unwrap();
+    let urn = owner.urn();
+
+    let old = PeerId::from(SecretKey::new());
+    let new = PeerId::from(SecretKey::new());
+    let policy = Policy {
+        data_limit: Some(1024),
+        ..Default::default()
+    };
+    tracking::track(&store, &urn, old).unwrap();
+    tracking::set_policy(&store, &urn, old, &policy).unwrap();
+    {
+        let mut config = store.config().unwrap();
+        config.revoke(old).unwrap();
+        config.set_replaced_by(old, Some(new)).unwrap();
+    }
+
+    assert_eq!(
+        rotation::replace(&store, &urn, old).unwrap(),
+        Some(new).into_iter().collect()
+    );
+    assert!(!tracking::is_tracked(&store, &urn, old).unwrap());
+    assert!(tracking::is_tracked(&store, &urn, new).unwrap());
+    assert_eq!(tracking::policy(&store, &urn, new).unwrap(), policy);
+}
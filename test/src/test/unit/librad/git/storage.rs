@@ -4,4 +4,9 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 mod config;
+mod gc;
+mod quarantine;
+mod remove;
+mod shallow;
+mod transaction;
 mod watch;
@@ -3,8 +3,14 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use std::num::NonZeroU32;
+
 use librad::{
-    git::storage::config::{Config, Error},
+    git::{
+        fetch::{Filter, Partial},
+        storage::config::{Config, Error},
+        Urn,
+    },
     keys::SecretKey,
     peer::PeerId,
 };
@@ -41,3 +47,35 @@ fn reinit_with_different_key() {
         Err(Error::AlreadyInitialised(pid)) if pid == *ALICE_PEER_ID
     )
 }
+
+#[test]
+fn tracking_graph_depth_roundtrip() {
+    let mut config = setup(&*ALICE_KEY);
+    let urn = Urn::new(git2::Oid::zero().into());
+
+    assert_eq!(config.tracking_graph_depth(&urn).unwrap(), None);
+
+    config.set_tracking_graph_depth(&urn, Some(5)).unwrap();
+    assert_eq!(config.tracking_graph_depth(&urn).unwrap(), Some(5));
+
+    config.set_tracking_graph_depth(&urn, None).unwrap();
+    assert_eq!(config.tracking_graph_depth(&urn).unwrap(), None);
+}
+
+#[test]
+fn partial_roundtrip() {
+    let mut config = setup(&*ALICE_KEY);
+    let urn = Urn::new(git2::Oid::zero().into());
+
+    assert!(config.partial(&urn).unwrap().is_full());
+
+    let partial = Partial {
+        depth: NonZeroU32::new(5),
+        filter: Some(Filter::BlobNone),
+    };
+    config.set_partial(&urn, partial).unwrap();
+    assert_eq!(config.partial(&urn).unwrap(), partial);
+
+    config.set_partial(&urn, Partial::default()).unwrap();
+    assert!(config.partial(&urn).unwrap().is_full());
+}
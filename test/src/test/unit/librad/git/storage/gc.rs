@@ -0,0 +1,154 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::time::Duration;
+
+use librad::{
+    git::{
+        storage::{fetcher, gc, Fetchers, Quarantine, ReadOnlyStorage as _, Storage},
+        types::{Namespace, Reference},
+        Urn,
+    },
+    git_ext as ext,
+    keys::SecretKey,
+    paths::Paths,
+    peer::PeerId,
+    reflike,
+};
+use url::Url;
+
+use crate::{librad::git::storage::storage, rad::identities::TestProject};
+
+#[test]
+fn prunes_namespaces_without_rad_id() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let rad_id = Reference::rad_id(Namespace::from(&urn));
+    let tip = store.reference_oid(&rad_id).unwrap();
+
+    let dangling = Urn::new(git2::Oid::zero().into());
+    let main = Reference::head(Namespace::from(&dangling), None, reflike!("main"));
+    let mut tx = store.transaction().unwrap();
+    tx.create(&ext::RefLike::from(&main), tip.into(), "main")
+        .unwrap();
+    tx.commit().unwrap();
+
+    let report = gc::collect(
+        &store,
+        gc::Options {
+            expiry: Duration::from_secs(0),
+            prune_namespaces: true,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        report.pruned_namespaces,
+        vec![dangling].into_iter().collect()
+    );
+    assert!(!store.has_ref(&main).unwrap());
+    assert!(store.has_ref(&rad_id).unwrap());
+    assert!(store.has_object(tip).unwrap())
+}
+
+#[test]
+fn keeps_namespaces_if_disabled() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let tip = store
+        .reference_oid(&Reference::rad_id(Namespace::from(&urn)))
+        .unwrap();
+
+    let dangling = Urn::new(git2::Oid::zero().into());
+    let main = Reference::head(Namespace::from(&dangling), None, reflike!("main"));
+    let mut tx = store.transaction().unwrap();
+    tx.create(&ext::RefLike::from(&main), tip.into(), "main")
+        .unwrap();
+    tx.commit().unwrap();
+
+    let report = gc::collect(
+        &store,
+        gc::Options {
+            expiry: Duration::from_secs(0),
+            prune_namespaces: false,
+        },
+    )
+    .unwrap();
+
+    assert!(report.pruned_namespaces.is_empty());
+    assert!(store.has_ref(&main).unwrap())
+}
+
+#[test]
+fn keeps_namespaces_fetched_by_other_storage() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = Paths::from_root(tmp.path()).unwrap();
+    let key = SecretKey::new();
+    let fetchers = Fetchers::default();
+    let store = Storage::with_fetchers(&paths, key.clone(), fetchers.clone()).unwrap();
+    let other = Storage::with_fetchers(&paths, key, fetchers).unwrap();
+
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let tip = store
+        .reference_oid(&Reference::rad_id(Namespace::from(&project.urn())))
+        .unwrap();
+
+    let inflight = Urn::new(git2::Oid::zero().into());
+    let main = Reference::head(Namespace::from(&inflight), None, reflike!("main"));
+    let mut tx = store.transaction().unwrap();
+    tx.create(&ext::RefLike::from(&main), tip.into(), "main")
+        .unwrap();
+    tx.commit().unwrap();
+
+    // A fetch of the namespace is in-flight on the other storage
+    let _fetcher = fetcher::AnyUrl {
+        urn: inflight,
+        remote_peer: PeerId::from(SecretKey::new()),
+        url: Url::from_file_path(paths.git_dir()).unwrap(),
+    }
+    .build(&other)
+    .unwrap()
+    .unwrap();
+
+    let report = gc::collect(
+        &store,
+        gc::Options {
+            expiry: Duration::from_secs(0),
+            prune_namespaces: true,
+        },
+    )
+    .unwrap();
+
+    assert!(report.pruned_namespaces.is_empty());
+    assert!(store.has_ref(&main).unwrap())
+}
+
+#[test]
+fn removes_stale_quarantines() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+
+    // Simulate a crash, which leaves the quarantine behind
+    let quarantine = Quarantine::new(&store, &project.urn()).unwrap();
+    let path = quarantine.storage().path().to_path_buf();
+    std::mem::forget(quarantine);
+
+    let report = gc::collect(&store, gc::Options::default()).unwrap();
+    assert_eq!(report.pruned_quarantines, 0);
+    assert!(path.exists());
+
+    let report = gc::collect(
+        &store,
+        gc::Options {
+            expiry: Duration::from_secs(0),
+            prune_namespaces: true,
+        },
+    )
+    .unwrap();
+    assert_eq!(report.pruned_quarantines, 1);
+    assert!(!path.exists())
+}
@@ -0,0 +1,243 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use librad::{
+    git::{
+        storage::{
+            quarantine::{self, Change, Target},
+            Quarantine,
+            ReadOnlyStorage as _,
+            Storage,
+        },
+        types::{Namespace, Reference},
+    },
+    git_ext as ext,
+    keys::SecretKey,
+    reflike,
+};
+
+use crate::{librad::git::storage::storage, rad::identities::TestProject};
+
+#[test]
+fn copies_namespace_and_links() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, owner } = TestProject::create(&store).unwrap();
+
+    let quarantine = Quarantine::new(&store, &project.urn()).unwrap();
+    let staged = quarantine.storage();
+    assert!(staged.has_urn(&project.urn()).unwrap());
+    assert!(staged.has_urn(&owner.urn()).unwrap());
+    assert!(quarantine.changes().unwrap().is_empty())
+}
+
+#[test]
+fn changes_are_not_applied() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let rad_id = Reference::rad_id(Namespace::from(&urn));
+    let tip = store.reference_oid(&rad_id).unwrap();
+
+    let quarantine = Quarantine::new(&store, &urn).unwrap();
+    let main = ext::RefLike::from(&Reference::head(
+        Namespace::from(&urn),
+        None,
+        reflike!("main"),
+    ));
+    let mut tx = quarantine.storage().transaction().unwrap();
+    tx.create(&main, tip.into(), "main").unwrap();
+    tx.commit().unwrap();
+
+    let changes = quarantine.changes().unwrap();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(
+        changes.get(&main),
+        Some(&Change::Created {
+            new: Target::Direct(tip)
+        })
+    );
+    assert!(!store
+        .has_ref(&Reference::head(
+            Namespace::from(&urn),
+            None,
+            reflike!("main")
+        ))
+        .unwrap());
+}
+
+#[test]
+fn removed_when_dropped() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+
+    let quarantine = Quarantine::new(&store, &project.urn()).unwrap();
+    let path = quarantine.storage().path().to_path_buf();
+    assert!(path.starts_with(store.path()));
+    drop(quarantine);
+    assert!(!path.exists())
+}
+
+#[test]
+fn migrate_applies_changes() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+
+    let quarantine = Quarantine::new(&store, &project.urn()).unwrap();
+    let TestProject {
+        project: staged, ..
+    } = TestProject::create(quarantine.storage()).unwrap();
+    let rad_id = Reference::rad_id(Namespace::from(&staged.urn()));
+    let tip = quarantine.storage().reference_oid(&rad_id).unwrap();
+    quarantine
+        .storage()
+        .config()
+        .unwrap()
+        .set_tracking_graph_depth(&project.urn(), Some(7))
+        .unwrap();
+
+    assert!(!store.has_urn(&staged.urn()).unwrap());
+    assert!(!store.has_object(tip).unwrap());
+
+    let changes = quarantine.migrate(&store).unwrap();
+    assert!(changes.contains_key(&ext::RefLike::from(&rad_id)));
+    assert!(store.has_urn(&staged.urn()).unwrap());
+    assert!(store.has_object(tip).unwrap());
+    assert_eq!(
+        store
+            .config()
+            .unwrap()
+            .tracking_graph_depth(&project.urn())
+            .unwrap(),
+        Some(7)
+    );
+}
+
+#[test]
+fn does_not_copy_other_namespaces() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let other = TestProject::create(&store).unwrap();
+
+    let quarantine = Quarantine::new(&store, &project.urn()).unwrap();
+    let staged = quarantine.storage();
+    assert!(!staged.has_urn(&other.project.urn()).unwrap());
+    assert!(!staged.has_urn(&other.owner.urn()).unwrap());
+    assert!(quarantine.changes().unwrap().is_empty())
+}
+
+#[test]
+fn migrate_reconciles_other_namespaces() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let other = TestProject::create(&store).unwrap().project.urn();
+    let head = Reference::head(Namespace::from(&other), None, reflike!("main"));
+    let main = ext::RefLike::from(&head);
+
+    let (base, ahead) = {
+        let repo = git2::Repository::open(store.path()).unwrap();
+        let sig = git2::Signature::now("apollo", "apollo@cerulean.de").unwrap();
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let base = repo.commit(None, &sig, &sig, "base", &tree, &[]).unwrap();
+        let ahead = repo
+            .commit(
+                None,
+                &sig,
+                &sig,
+                "ahead",
+                &tree,
+                &[&repo.find_commit(base).unwrap()],
+            )
+            .unwrap();
+        (base, ahead)
+    };
+    let set = |storage: &Storage, oid: git2::Oid| {
+        let mut tx = storage.transaction().unwrap();
+        tx.set_target(&main, oid, "main").unwrap();
+        tx.commit().unwrap();
+    };
+
+    // The quarantine is ahead: fast-forward
+    set(&store, base);
+    let quarantine = Quarantine::new(&store, &project.urn()).unwrap();
+    set(quarantine.storage(), ahead);
+    quarantine.migrate(&store).unwrap();
+    assert_eq!(store.reference_oid(&head).unwrap(), ahead.into());
+
+    // The monorepo is ahead: skip
+    let quarantine = Quarantine::new(&store, &project.urn()).unwrap();
+    set(quarantine.storage(), base);
+    quarantine.migrate(&store).unwrap();
+    assert_eq!(store.reference_oid(&head).unwrap(), ahead.into());
+}
+
+#[test]
+fn migrate_skips_identical_changes() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let tip = store
+        .reference_oid(&Reference::rad_id(Namespace::from(&urn)))
+        .unwrap();
+    let main = ext::RefLike::from(&Reference::head(
+        Namespace::from(&urn),
+        None,
+        reflike!("main"),
+    ));
+
+    let quarantine = Quarantine::new(&store, &urn).unwrap();
+    let mut tx = quarantine.storage().transaction().unwrap();
+    tx.create(&main, tip.into(), "main").unwrap();
+    tx.commit().unwrap();
+
+    let mut tx = store.transaction().unwrap();
+    tx.create(&main, tip.into(), "main").unwrap();
+    tx.commit().unwrap();
+
+    quarantine.migrate(&store).unwrap();
+    assert_eq!(
+        store
+            .reference_oid(&Reference::head(
+                Namespace::from(&urn),
+                None,
+                reflike!("main")
+            ))
+            .unwrap(),
+        tip
+    )
+}
+
+#[test]
+fn migrate_detects_concurrent_modification() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, owner } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let tip = store
+        .reference_oid(&Reference::rad_id(Namespace::from(&urn)))
+        .unwrap();
+    let other = store
+        .reference_oid(&Reference::rad_id(Namespace::from(&owner.urn())))
+        .unwrap();
+    let main = ext::RefLike::from(&Reference::head(
+        Namespace::from(&urn),
+        None,
+        reflike!("main"),
+    ));
+
+    let quarantine = Quarantine::new(&store, &urn).unwrap();
+    let mut tx = quarantine.storage().transaction().unwrap();
+    tx.create(&main, tip.into(), "main").unwrap();
+    tx.commit().unwrap();
+
+    let mut tx = store.transaction().unwrap();
+    tx.create(&main, other.into(), "main").unwrap();
+    tx.commit().unwrap();
+
+    assert!(matches!(
+        quarantine.migrate(&store),
+        Err(quarantine::Error::Concurrent { name }) if name == main
+    ))
+}
@@ -0,0 +1,57 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use librad::{
+    git::{storage::ReadOnlyStorage as _, tracking},
+    keys::SecretKey,
+    peer::PeerId,
+};
+
+use crate::{librad::git::storage::storage, rad::identities::TestProject};
+
+#[test]
+fn remove_namespace() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, owner } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let remote = PeerId::from(SecretKey::new());
+    tracking::track(&store, &urn, remote).unwrap();
+
+    assert!(store
+        .linked_from(&owner.urn())
+        .unwrap()
+        .contains(&project.urn()));
+
+    assert!(store.remove_namespace(&urn).unwrap());
+    assert!(!store.has_urn(&urn).unwrap());
+    assert!(!tracking::is_tracked(&store, &urn, remote).unwrap());
+    assert!(store.has_urn(&owner.urn()).unwrap());
+    assert!(store.linked_from(&owner.urn()).unwrap().is_empty());
+
+    assert!(!store.remove_namespace(&urn).unwrap())
+}
+
+#[test]
+fn remove_linked_namespace() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, owner } = TestProject::create(&store).unwrap();
+
+    assert!(store.remove_namespace(&owner.urn()).unwrap());
+    assert!(!store.has_urn(&owner.urn()).unwrap());
+    assert!(store.linked_from(&owner.urn()).unwrap().is_empty());
+    assert!(store.has_urn(&project.urn()).unwrap())
+}
+
+#[test]
+fn self_linked_from() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, owner } = TestProject::create(&store).unwrap();
+
+    assert!(store
+        .self_linked_from(&owner.urn())
+        .unwrap()
+        .contains(&project.urn()));
+    assert!(store.self_linked_from(&project.urn()).unwrap().is_empty())
+}
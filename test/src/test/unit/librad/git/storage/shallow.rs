@@ -0,0 +1,79 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{fs, num::NonZeroU32};
+
+use librad::{
+    git::{
+        storage::shallow,
+        types::{Namespace, Reference},
+        Urn,
+    },
+    git_ext as ext,
+    keys::SecretKey,
+    reflike,
+};
+
+use crate::librad::git::storage::storage;
+
+/// Write a commit with a fixed signature, so it can be re-created.
+fn commit(repo: &git2::Repository, parents: &[&git2::Commit]) -> git2::Oid {
+    let author =
+        git2::Signature::new("The Animal", "animal@muppets.com", &git2::Time::new(0, 0)).unwrap();
+    let tree = {
+        let oid = repo.treebuilder(None).unwrap().write().unwrap();
+        repo.find_tree(oid).unwrap()
+    };
+    repo.commit(None, &author, &author, "shallow", &tree, parents)
+        .unwrap()
+}
+
+fn remove_object(repo: &git2::Repository, oid: git2::Oid) {
+    let hex = oid.to_string();
+    fs::remove_file(repo.path().join("objects").join(&hex[..2]).join(&hex[2..])).unwrap()
+}
+
+#[test]
+fn update_tracks_missing_parents() {
+    let store = storage(SecretKey::new());
+    let repo = git2::Repository::open_bare(store.path()).unwrap();
+    let urn = Urn::new(git2::Oid::zero().into());
+
+    let root = commit(&repo, &[]);
+    let tip = commit(&repo, &[&repo.find_commit(root).unwrap()]);
+    let main = Reference::head(Namespace::from(&urn), None, reflike!("main"));
+    repo.reference(ext::RefLike::from(&main).as_str(), tip, false, "shallow")
+        .unwrap();
+    remove_object(&repo, root);
+
+    shallow::update(&store, &urn, NonZeroU32::new(1)).unwrap();
+    assert_eq!(
+        shallow::read(&repo).unwrap(),
+        vec![tip.into()].into_iter().collect()
+    );
+
+    // Deepen, using a fresh `Repository`, as the old one has `root` cached
+    let repo = git2::Repository::open_bare(store.path()).unwrap();
+    assert_eq!(commit(&repo, &[]), root);
+    shallow::update(&store, &urn, None).unwrap();
+    assert!(shallow::read(&repo).unwrap().is_empty());
+    assert!(!shallow::path(&repo).exists())
+}
+
+#[test]
+fn update_ignores_complete_history() {
+    let store = storage(SecretKey::new());
+    let repo = git2::Repository::open_bare(store.path()).unwrap();
+    let urn = Urn::new(git2::Oid::zero().into());
+
+    let root = commit(&repo, &[]);
+    let tip = commit(&repo, &[&repo.find_commit(root).unwrap()]);
+    let main = Reference::head(Namespace::from(&urn), None, reflike!("main"));
+    repo.reference(ext::RefLike::from(&main).as_str(), tip, false, "shallow")
+        .unwrap();
+
+    shallow::update(&store, &urn, NonZeroU32::new(1)).unwrap();
+    assert!(shallow::read(&repo).unwrap().is_empty())
+}
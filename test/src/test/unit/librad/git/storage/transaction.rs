@@ -0,0 +1,106 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use librad::{
+    git::{
+        refs::Refs,
+        storage::ReadOnlyStorage as _,
+        types::{Namespace, Reference},
+    },
+    git_ext as ext,
+    keys::SecretKey,
+    reflike,
+};
+
+use crate::{librad::git::storage::storage, rad::identities::TestProject};
+
+#[test]
+fn commit_applies_all() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let tip = store
+        .reference_oid(&Reference::rad_id(Namespace::from(&urn)))
+        .unwrap();
+
+    let main = Reference::head(Namespace::from(&urn), None, reflike!("main"));
+    let next = Reference::head(Namespace::from(&urn), None, reflike!("next"));
+
+    let mut tx = store.transaction().unwrap();
+    assert!(tx
+        .create(&ext::RefLike::from(&main), tip.into(), "main")
+        .unwrap());
+    assert!(tx
+        .create(&ext::RefLike::from(&next), tip.into(), "next")
+        .unwrap());
+    tx.commit().unwrap();
+
+    assert!(store.has_ref(&main).unwrap());
+    assert!(store.has_ref(&next).unwrap());
+
+    let mut tx = store.transaction().unwrap();
+    assert!(tx.remove(&ext::RefLike::from(&main)).unwrap());
+    assert!(tx.remove(&ext::RefLike::from(&next)).unwrap());
+    tx.commit().unwrap();
+
+    assert!(!store.has_ref(&main).unwrap());
+    assert!(!store.has_ref(&next).unwrap())
+}
+
+#[test]
+fn drop_applies_nothing() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let tip = store
+        .reference_oid(&Reference::rad_id(Namespace::from(&urn)))
+        .unwrap();
+
+    let main = Reference::head(Namespace::from(&urn), None, reflike!("main"));
+    {
+        let mut tx = store.transaction().unwrap();
+        tx.create(&ext::RefLike::from(&main), tip.into(), "main")
+            .unwrap();
+    }
+
+    assert!(!store.has_ref(&main).unwrap())
+}
+
+#[test]
+fn locked_refs_cannot_be_modified() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+    let tip = store
+        .reference_oid(&Reference::rad_id(Namespace::from(&urn)))
+        .unwrap();
+
+    let main = ext::RefLike::from(&Reference::head(
+        Namespace::from(&urn),
+        None,
+        reflike!("main"),
+    ));
+    let mut tx = store.transaction().unwrap();
+    tx.lock(&main).unwrap();
+
+    let mut other = store.transaction().unwrap();
+    assert!(other.set_target(&main, tip.into(), "main").is_err())
+}
+
+#[test]
+fn signed_refs_update_locks_signed_refs() {
+    let store = storage(SecretKey::new());
+    let TestProject { project, .. } = TestProject::create(&store).unwrap();
+    let urn = project.urn();
+
+    let rad_id = ext::RefLike::from(&Reference::rad_id(Namespace::from(&urn)));
+    let mut tx = store.transaction().unwrap();
+    tx.lock(&rad_id).unwrap();
+
+    // `rad/id` is one of the signed refs, so the update needs to lock it
+    assert!(Refs::update(&store, &urn).is_err());
+    drop(tx);
+    assert!(Refs::update(&store, &urn).is_ok())
+}
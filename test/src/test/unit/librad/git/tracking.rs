@@ -8,13 +8,26 @@ use std::collections::BTreeSet;
 use librad::{
     git::{
         storage::Storage,
-        tracking::{is_tracked, track, tracked, untrack},
+        tracking::{
+            is_tracked,
+            is_tracked_any,
+            policy,
+            set_default_policy,
+            set_policy,
+            track,
+            track_any,
+            tracked,
+            untrack,
+            untrack_any,
+            Policy,
+        },
         Urn,
     },
     keys::SecretKey,
     paths::Paths,
     peer::PeerId,
     reflike,
+    refspec_pattern,
 };
 
 #[test]
@@ -111,3 +124,108 @@ fn tracked_ignores_urn_path() {
         assert_eq!(Some(remote_peer), tracked(&storage, &urn).unwrap().next())
     }
 }
+
+#[test]
+fn policy_defaults_to_allow_all() {
+    let tmp = tempfile::tempdir().unwrap();
+    {
+        let paths = Paths::from_root(&tmp).unwrap();
+        let storage = Storage::open(&paths, SecretKey::new()).unwrap();
+        let remote_peer = PeerId::from(SecretKey::new());
+        let urn = Urn::new(git2::Oid::zero().into());
+
+        track(&storage, &urn, remote_peer).unwrap();
+        let policy = policy(&storage, &urn, remote_peer).unwrap();
+        assert_eq!(Policy::default(), policy);
+        assert!(policy.allows(&reflike!("heads/main")))
+    }
+}
+
+#[test]
+fn set_policy_roundtrip() {
+    let tmp = tempfile::tempdir().unwrap();
+    {
+        let paths = Paths::from_root(&tmp).unwrap();
+        let storage = Storage::open(&paths, SecretKey::new()).unwrap();
+        let remote_peer = PeerId::from(SecretKey::new());
+        let urn = Urn::new(git2::Oid::zero().into());
+        let expected = Policy {
+            allow: [refspec_pattern!("heads/main"), refspec_pattern!("tags/*")]
+                .iter()
+                .cloned()
+                .collect(),
+            deny: Some(refspec_pattern!("cobs/*")).into_iter().collect(),
+            data_limit: Some(1024),
+        };
+
+        track(&storage, &urn, remote_peer).unwrap();
+        set_policy(&storage, &urn, remote_peer, &expected).unwrap();
+        let actual = policy(&storage, &urn, remote_peer).unwrap();
+        assert_eq!(expected, actual);
+        assert!(actual.allows(&reflike!("heads/main")));
+        assert!(actual.allows(&reflike!("tags/v1.0")));
+        assert!(!actual.allows(&reflike!("heads/next")));
+        assert!(!actual.allows(&reflike!("cobs/issues")))
+    }
+}
+
+#[test]
+fn policy_falls_back_to_default() {
+    let tmp = tempfile::tempdir().unwrap();
+    {
+        let paths = Paths::from_root(&tmp).unwrap();
+        let storage = Storage::open(&paths, SecretKey::new()).unwrap();
+        let remote_peer = PeerId::from(SecretKey::new());
+        let urn = Urn::new(git2::Oid::zero().into());
+        let default = Policy {
+            deny: Some(refspec_pattern!("cobs/*")).into_iter().collect(),
+            ..Default::default()
+        };
+
+        set_default_policy(&storage, remote_peer, &default).unwrap();
+        assert_eq!(default, policy(&storage, &urn, remote_peer).unwrap());
+    }
+}
+
+#[test]
+fn untrack_removes_policy() {
+    let tmp = tempfile::tempdir().unwrap();
+    {
+        let paths = Paths::from_root(&tmp).unwrap();
+        let storage = Storage::open(&paths, SecretKey::new()).unwrap();
+        let remote_peer = PeerId::from(SecretKey::new());
+        let urn = Urn::new(git2::Oid::zero().into());
+        let restricted = Policy {
+            allow: Some(refspec_pattern!("heads/main")).into_iter().collect(),
+            ..Default::default()
+        };
+
+        track(&storage, &urn, remote_peer).unwrap();
+        set_policy(&storage, &urn, remote_peer, &restricted).unwrap();
+        untrack(&storage, &urn, remote_peer).unwrap();
+        assert_eq!(
+            Policy::default(),
+            policy(&storage, &urn, remote_peer).unwrap()
+        )
+    }
+}
+
+#[test]
+fn track_any_is_tracked_any() {
+    let tmp = tempfile::tempdir().unwrap();
+    {
+        let paths = Paths::from_root(&tmp).unwrap();
+        let storage = Storage::open(&paths, SecretKey::new()).unwrap();
+        let urn = Urn::new(git2::Oid::zero().into());
+
+        assert!(!is_tracked_any(&storage, &urn).unwrap());
+        assert!(track_any(&storage, &urn).unwrap());
+        assert!(!track_any(&storage, &urn).unwrap());
+        assert!(is_tracked_any(&storage, &urn).unwrap());
+        assert_eq!(None, tracked(&storage, &urn).unwrap().next());
+
+        assert!(untrack_any(&storage, &urn).unwrap());
+        assert!(!untrack_any(&storage, &urn).unwrap());
+        assert!(!is_tracked_any(&storage, &urn).unwrap())
+    }
+}
@@ -3,10 +3,15 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
+use url::Url;
+
 use librad::{
     identities::{
         git::{error, VerificationError},
+        payload::{self, Contributor, HasNamespace},
         Identities,
+        Person,
+        Threshold,
     },
     keys::SecretKey,
 };
@@ -121,3 +126,124 @@ fn revoke_a_trois() -> anyhow::Result<()> {
         desktop.assert_verifies()
     }
 }
+
+#[test]
+fn threshold_unreachable() -> anyhow::Result<()> {
+    let repo = repo()?;
+    {
+        let git = Identities::<Person>::from(&*repo);
+        let person = git.create_with_threshold(
+            payload::Person {
+                name: "dylan".into(),
+            }
+            .into(),
+            Some(DESKTOP.public()).into_iter().collect(),
+            Some(Threshold::new(nonzero!(2usize))),
+            &*DESKTOP,
+        );
+        assert_matches!(
+            person,
+            Err(error::Store::UnreachableThreshold { delegates: 1, .. })
+        );
+
+        // Removing a delegate must not render the threshold unreachable either
+        let desktop = Device::new(&*DESKTOP, Identities::from(&*repo))?.update_with_threshold(
+            Some(
+                vec![DESKTOP.public(), LAPTOP.public()]
+                    .into_iter()
+                    .collect(),
+            ),
+            Some(Threshold::new(nonzero!(2usize))),
+        )?;
+        let laptop = Device::create_from(&*LAPTOP, &desktop)?;
+        assert!(laptop
+            .update(Some(Some(LAPTOP.public()).into_iter().collect()))
+            .is_err());
+
+        Ok(())
+    }
+}
+
+#[test]
+fn threshold_delegation_changes() -> anyhow::Result<()> {
+    let repo = repo()?;
+    {
+        let any = Threshold::new(nonzero!(1usize));
+
+        // The root revision has a majority of one
+        let desktop = Device::new(&*DESKTOP, Identities::from(&*repo))?.update_with_threshold(
+            Some(
+                vec![DESKTOP.public(), LAPTOP.public(), PALMTOP.public()]
+                    .into_iter()
+                    .collect(),
+            ),
+            Some(any.with_delegations(nonzero!(3usize))),
+        )?;
+        desktop.assert_verifies()?;
+        assert_eq!(
+            desktop.verify()?.doc.threshold,
+            Some(any.with_delegations(nonzero!(3usize)))
+        );
+
+        // Any one delegate forms a quorum
+        let laptop = Device::create_from(&*LAPTOP, &desktop)?;
+        laptop.assert_verifies()?;
+
+        // But changing the delegations requires all of them -- until then, the
+        // change is a proposal
+        let confirmed = laptop.current().content_id;
+        let laptop = laptop.update_with_threshold(
+            Some(
+                vec![DESKTOP.public(), LAPTOP.public()]
+                    .into_iter()
+                    .collect(),
+            ),
+            Some(any),
+        )?;
+        assert_eq!(laptop.verify()?.content_id, confirmed);
+        let desktop = Device::create_from(&*DESKTOP, &laptop)?;
+        assert_eq!(desktop.verify()?.content_id, confirmed);
+
+        let palmtop = Device::create_from(&*PALMTOP, &desktop)?;
+        palmtop.assert_verifies()?;
+        assert_eq!(palmtop.verify()?.doc.threshold, Some(any));
+
+        Ok(())
+    }
+}
+
+/// A [`Contributor`] whose `largefiles` is not a valid template.
+#[derive(serde::Serialize)]
+struct MalformedContributor {
+    largefiles: &'static str,
+}
+
+impl HasNamespace for MalformedContributor {
+    fn namespace() -> &'static Url {
+        Contributor::namespace()
+    }
+}
+
+#[test]
+fn invalid_extension() -> anyhow::Result<()> {
+    let repo = repo()?;
+    {
+        let git = Identities::<Person>::from(&*repo);
+        let person = git.create(
+            payload::PersonPayload::from(payload::Person {
+                name: "dylan".into(),
+            })
+            .with_ext(MalformedContributor {
+                largefiles: "ipfs://{SHA256_CID",
+            })?,
+            Some(DESKTOP.public()).into_iter().collect(),
+            &*DESKTOP,
+        )?;
+        assert_matches!(
+            git.verify(*person.content_id),
+            Err(error::VerifyPerson::Payload(_))
+        );
+
+        Ok(())
+    }
+}
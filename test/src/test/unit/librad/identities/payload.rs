@@ -3,20 +3,26 @@
 // This file is part of radicle-link, distributed under the GPLv3 with Radicle
 // Linking Exception. For full terms see the included LICENSE file.
 
-use std::fmt::Debug;
+use std::{convert::TryFrom as _, fmt::Debug};
 
 use pretty_assertions::assert_eq;
 use proptest::prelude::*;
+use url::Url;
 
 use librad::{
     git_ext::Oid,
     identities::payload::{
+        registry::{self, Extension},
+        Contributor,
+        HasNamespace,
         Person,
         PersonDelegations,
         PersonPayload,
         Project,
         ProjectDelegations,
         ProjectPayload,
+        Registry,
+        UrlTemplate,
     },
     keys::SecretKey,
 };
@@ -209,6 +215,204 @@ fn null_extension() {
     assert_eq!(json_actual, json_expected);
 }
 
+lazy_static! {
+    static ref RELEASE_NAMESPACE: Url = Url::parse("https://semantic.me/release/v1").unwrap();
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Release {
+    version: String,
+}
+
+impl HasNamespace for Release {
+    fn namespace() -> &'static Url {
+        &RELEASE_NAMESPACE
+    }
+}
+
+impl Extension for Release {
+    const VERSION: u32 = 1;
+
+    fn validate(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        if self.version.split('.').count() == 3 {
+            Ok(())
+        } else {
+            Err(format!("`{}` is not a semantic version", self.version).into())
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct NotRelease {}
+
+impl HasNamespace for NotRelease {
+    fn namespace() -> &'static Url {
+        &RELEASE_NAMESPACE
+    }
+}
+
+impl Extension for NotRelease {
+    const VERSION: u32 = 2;
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct NotProject {}
+
+impl HasNamespace for NotProject {
+    fn namespace() -> &'static Url {
+        Project::namespace()
+    }
+}
+
+impl Extension for NotProject {
+    const VERSION: u32 = 1;
+}
+
+#[test]
+fn registered_extension() {
+    let mut registry = Registry::default();
+    registry.register::<Release>().unwrap();
+    // Registering again is fine
+    registry.register::<Release>().unwrap();
+
+    let valid = r#"{
+            "https://radicle.xyz/link/identities/project/v1": {
+                "name": "foo"
+            },
+            "https://semantic.me/release/v1": {
+                "version": "1.0.0"
+            }
+        }"#;
+    let payload = serde_json::from_str::<ProjectPayload>(valid).unwrap();
+    assert!(payload.validate(&registry).is_ok());
+    assert_eq!(
+        payload.get_ext::<Release>().unwrap(),
+        Some(Release {
+            version: "1.0.0".to_owned()
+        })
+    );
+    let registered = payload.registered_ext(&registry);
+    assert_eq!(registered.len(), 1);
+    assert_eq!(registered[0].namespace, Release::namespace());
+    assert_eq!(registered[0].version, Release::VERSION);
+
+    let invalid = r#"{
+            "https://radicle.xyz/link/identities/project/v1": {
+                "name": "foo"
+            },
+            "https://semantic.me/release/v1": {
+                "version": "latest"
+            }
+        }"#;
+    let payload = serde_json::from_str::<ProjectPayload>(invalid).unwrap();
+    assert!(matches!(
+        payload.validate(&registry),
+        Err(registry::Invalid::Validation { .. })
+    ));
+    // Unknown to the default registry
+    assert!(payload.validate(&Registry::default()).is_ok());
+
+    let malformed = r#"{
+            "https://radicle.xyz/link/identities/project/v1": {
+                "name": "foo"
+            },
+            "https://semantic.me/release/v1": 42
+        }"#;
+    let payload = serde_json::from_str::<ProjectPayload>(malformed).unwrap();
+    assert!(matches!(
+        payload.validate(&registry),
+        Err(registry::Invalid::Malformed { .. })
+    ));
+}
+
+#[test]
+fn unregistered_extension() {
+    let json = r#"{
+            "https://radicle.xyz/link/identities/person/v1": {
+                "name": "foo"
+            },
+            "https://semantic.me/unregistered/v1": {
+                "anything": ["goes"]
+            }
+        }"#;
+    let payload = serde_json::from_str::<PersonPayload>(json).unwrap();
+    assert!(payload.validate(Registry::builtin()).is_ok());
+    assert!(payload.registered_ext(Registry::builtin()).is_empty());
+    assert_eq!(
+        serde_json::to_value(&payload).unwrap(),
+        serde_json::from_str::<serde_json::Value>(json).unwrap()
+    );
+}
+
+#[test]
+fn register_conflict() {
+    let mut registry = Registry::default();
+    registry.register::<Release>().unwrap();
+    assert!(matches!(
+        registry.register::<NotRelease>(),
+        Err(registry::Error::Conflict { .. })
+    ));
+    assert!(matches!(
+        registry.register::<NotProject>(),
+        Err(registry::Error::ExtensionIsSubject)
+    ));
+    assert!(registry
+        .registered()
+        .any(|descriptor| descriptor.namespace == Release::namespace()));
+}
+
+#[test]
+fn contributor_largefiles() {
+    let json = r#"{
+            "https://radicle.xyz/link/identities/person/v1": {
+                "name": "cloudhead"
+            },
+            "https://radicle.xyz/link/identities/contributor/v1": {
+                "largefiles": "ipfs://{SHA256_CID}"
+            }
+        }"#;
+    let payload = serde_json::from_str::<PersonPayload>(json).unwrap();
+    let largefiles = payload
+        .get_ext::<Contributor>()
+        .unwrap()
+        .and_then(|contributor| contributor.largefiles)
+        .unwrap();
+    assert!(largefiles.has_expressions());
+    assert_eq!(largefiles.as_url(), None);
+
+    let unbalanced = r#"{
+            "https://radicle.xyz/link/identities/person/v1": {
+                "name": "cloudhead"
+            },
+            "https://radicle.xyz/link/identities/contributor/v1": {
+                "largefiles": "ipfs://{SHA256_CID"
+            }
+        }"#;
+    let payload = serde_json::from_str::<PersonPayload>(unbalanced).unwrap();
+    assert!(payload.get_ext::<Contributor>().is_err());
+    assert!(payload.validate(Registry::builtin()).is_err());
+}
+
+#[test]
+fn url_template() {
+    for valid in &[
+        "https://github.com/me/rad-mirror.git/info/lfs/objects/batch",
+        "ipfs://{SHA256_CID}",
+        "dat://778f8d955175c92e4ced5e4f5563f69bfec0c86cc6f670352c457943666fe639/{SHA256}",
+    ] {
+        assert!(UrlTemplate::try_from(*valid).is_ok(), "{}", valid)
+    }
+
+    for invalid in &[
+        "{SHA256}",
+        "ipfs://{SHA256",
+        "ipfs://SHA256}",
+        "ipfs://{{SHA256}}",
+    ] {
+        assert!(UrlTemplate::try_from(*invalid).is_err(), "{}", invalid)
+    }
+}
+
 /// All serialisation roundtrips required for payload types
 fn trippin<A>(a: A)
 where
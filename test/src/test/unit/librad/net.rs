@@ -4,6 +4,7 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 mod codec;
+mod discovery;
 mod peer;
 mod protocol;
 mod tls;
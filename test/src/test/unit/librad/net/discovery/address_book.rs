@@ -0,0 +1,171 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{
+    collections::BTreeSet,
+    iter,
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+use futures::{executor::block_on, StreamExt as _};
+
+use librad::{
+    data::BoundedVec,
+    git::Urn,
+    keys::SecretKey,
+    net::{
+        discovery::{address_book, AddressBook, Discovery as _},
+        protocol::{
+            broadcast::PutResult,
+            event::{upstream, Upstream},
+            gossip,
+            membership::Transition,
+            PeerAdvertisement,
+            PeerInfo,
+        },
+    },
+    peer::PeerId,
+};
+
+fn addr(port: u16) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], port))
+}
+
+fn peer_info(peer_id: PeerId, listen: u16, seen: u16) -> PeerInfo<SocketAddr> {
+    PeerInfo {
+        peer_id,
+        advertised_info: PeerAdvertisement {
+            listen_addrs: BoundedVec::try_from_length(vec![addr(listen)]).unwrap(),
+            capabilities: BTreeSet::new(),
+        },
+        seen_addrs: BoundedVec::try_from_length(vec![addr(seen)]).unwrap(),
+    }
+}
+
+fn discover(book: &AddressBook) -> Vec<(PeerId, Vec<SocketAddr>)> {
+    block_on(book.clone().discover().collect())
+}
+
+#[test]
+fn records_transitions() {
+    let tmp = tempfile::tempdir().unwrap();
+    let book = AddressBook::load(tmp.path().join("peers.json"), Default::default()).unwrap();
+    let peer = PeerId::from(SecretKey::new());
+
+    book.record_transition(&Transition::Demoted(peer_info(peer, 1, 2)));
+    book.record_transition(&Transition::Evicted(peer_info(peer, 1, 2).into()));
+    let entry = book.get(&peer).unwrap();
+    assert_eq!(entry.failures, 1);
+    assert_eq!(
+        entry.addrs.keys().copied().collect::<Vec<_>>(),
+        vec![addr(1), addr(2)]
+    );
+
+    book.record_transition(&Transition::Promoted(peer_info(peer, 3, 2).into()));
+    let entry = book.get(&peer).unwrap();
+    assert_eq!(entry.failures, 0);
+    assert_eq!(entry.addrs.len(), 3);
+}
+
+#[test]
+fn ignores_gossip_providers() {
+    let tmp = tempfile::tempdir().unwrap();
+    let book = AddressBook::load(tmp.path().join("peers.json"), Default::default()).unwrap();
+    let peer = PeerId::from(SecretKey::new());
+
+    let put = Upstream::from(upstream::Gossip::Put {
+        provider: peer_info(peer, 1, 2),
+        payload: gossip::Payload {
+            urn: Urn::new(git2::Oid::zero().into()),
+            rev: None,
+            origin: None,
+        },
+        result: PutResult::Uninteresting,
+    });
+    assert!(!book.observe(&put));
+    assert!(book.get(&peer).is_none());
+
+    let promoted = Upstream::Membership(Transition::Promoted(peer_info(peer, 1, 2).into()));
+    assert!(book.observe(&promoted));
+    assert!(book.get(&peer).is_some());
+}
+
+#[test]
+fn forget_failing_peers() {
+    let tmp = tempfile::tempdir().unwrap();
+    let book = AddressBook::load(
+        tmp.path().join("peers.json"),
+        address_book::Config {
+            max_failures: 2,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let peer = PeerId::from(SecretKey::new());
+
+    book.record_peer_info(&peer_info(peer, 1, 2));
+    book.failed(&peer);
+    assert!(book.get(&peer).is_some());
+    book.failed(&peer);
+    assert!(book.get(&peer).is_none());
+}
+
+#[test]
+fn age_out() {
+    let tmp = tempfile::tempdir().unwrap();
+    let config = address_book::Config::default();
+    let book = AddressBook::load(tmp.path().join("peers.json"), config).unwrap();
+    let peer = PeerId::from(SecretKey::new());
+
+    book.seen(peer, iter::once(addr(1)), true);
+    book.prune(SystemTime::now());
+    assert!(book.get(&peer).is_some());
+
+    book.prune(SystemTime::now() + config.max_age + Duration::from_secs(1));
+    assert!(book.is_empty());
+}
+
+#[test]
+fn max_peers() {
+    let tmp = tempfile::tempdir().unwrap();
+    let book = AddressBook::load(
+        tmp.path().join("peers.json"),
+        address_book::Config {
+            max_peers: 2,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    for port in 1..=3 {
+        book.seen(PeerId::from(SecretKey::new()), iter::once(addr(port)), true);
+    }
+    book.prune(SystemTime::now());
+    assert_eq!(book.len(), 2);
+}
+
+#[test]
+fn persist_and_discover() {
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("net").join("peers.json");
+    let peer = PeerId::from(SecretKey::new());
+
+    let book = AddressBook::load(&path, Default::default()).unwrap();
+    assert!(discover(&book).is_empty());
+    book.record_peer_info(&peer_info(peer, 1, 2));
+    book.save().unwrap();
+
+    let reloaded = AddressBook::load(&path, Default::default()).unwrap();
+    assert_eq!(reloaded.get(&peer), book.get(&peer));
+
+    let found = discover(&reloaded);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].0, peer);
+    assert_eq!(
+        found[0].1.iter().copied().collect::<BTreeSet<_>>(),
+        vec![addr(1), addr(2)].into_iter().collect()
+    );
+}
@@ -0,0 +1,114 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use assert_matches::assert_matches;
+use futures::{executor::block_on, StreamExt as _};
+
+use librad::{
+    keys::SecretKey,
+    net::{
+        discovery::{
+            lan::{self, Announcement},
+            Discovery as _,
+            Lan,
+        },
+        Network,
+    },
+    peer::PeerId,
+};
+
+use crate::librad::net::discovery::loopback;
+
+fn rt() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn announcement_roundtrip() {
+    let key = SecretKey::new();
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 12345));
+    let ann = Announcement::new(PeerId::from(&key), &Network::Main, &[addr]);
+    let signed = block_on(ann.sign(&key)).unwrap();
+
+    assert_eq!(Announcement::verify(&signed).unwrap(), ann);
+}
+
+#[test]
+fn announcement_forged() {
+    let key = SecretKey::new();
+    let ann = Announcement::new(PeerId::from(SecretKey::new()), &Network::Main, &[]);
+    let signed = block_on(ann.sign(&key)).unwrap();
+
+    assert_matches!(
+        Announcement::verify(&signed),
+        Err(lan::Error::InvalidSignature)
+    )
+}
+
+#[test]
+fn unspecified_addrs() {
+    let ann = Announcement::new(
+        PeerId::from(SecretKey::new()),
+        &Network::Main,
+        &[SocketAddr::from((Ipv4Addr::UNSPECIFIED, 12345))],
+    );
+    let sender = SocketAddr::from(([192, 168, 1, 2], 17677));
+
+    assert_eq!(
+        ann.addrs(sender),
+        vec![SocketAddr::from(([192, 168, 1, 2], 12345))]
+    )
+}
+
+#[test]
+fn discover_on_loopback() {
+    let config = loopback();
+    let alice = SecretKey::new();
+    let bob = SecretKey::new();
+    let bob_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 12345));
+
+    let found = rt().block_on(async {
+        let alice = Lan::new(alice, Network::Main, vec![], config).discover();
+        let bob = Lan::new(bob.clone(), Network::Main, vec![bob_addr], config).discover();
+        tokio::spawn(bob.for_each(|_| futures::future::ready(())));
+        tokio::time::timeout(Duration::from_secs(5), alice.into_future())
+            .await
+            .expect("timed out waiting for announcement")
+            .0
+    });
+
+    assert_eq!(found, Some((PeerId::from(&bob), vec![bob_addr])))
+}
+
+#[test]
+fn ignore_other_network() {
+    let config = loopback();
+    let alice = SecretKey::new();
+    let bob = SecretKey::new();
+    let bob_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 12345));
+
+    let found = rt().block_on(async {
+        let alice = Lan::new(alice, Network::Main, vec![], config).discover();
+        let bob = Lan::new(
+            bob,
+            "devnet".parse::<Network>().unwrap(),
+            vec![bob_addr],
+            config,
+        )
+        .discover();
+        tokio::spawn(bob.for_each(|_| futures::future::ready(())));
+        tokio::time::timeout(Duration::from_millis(500), alice.into_future()).await
+    });
+
+    assert!(found.is_err(), "discovered a peer of another network")
+}
@@ -4,4 +4,7 @@
 // Linking Exception. For full terms see the included LICENSE file.
 
 mod gossip;
+mod info;
 mod io;
+mod relay;
+mod sync;
@@ -0,0 +1,58 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use std::{collections::BTreeSet, net::SocketAddr};
+
+use minicbor::Encode;
+
+use librad::net::protocol::{Capability, PeerAdvertisement};
+
+use crate::roundtrip::*;
+
+/// A capability from the future.
+#[derive(Eq, Ord, PartialEq, PartialOrd, Encode)]
+enum FutureCapability {
+    #[n(1)]
+    Relay,
+    #[n(42)]
+    Teleport,
+}
+
+/// A [`PeerAdvertisement`] from the future.
+#[derive(Encode)]
+#[cbor(array)]
+struct FutureAdvertisement {
+    #[n(0)]
+    listen_addrs: Vec<SocketAddr>,
+
+    #[n(2)]
+    capabilities: BTreeSet<FutureCapability>,
+}
+
+#[test]
+fn roundtrip_advertisement() {
+    let mut ad = PeerAdvertisement::new("127.0.0.1:12345".parse::<SocketAddr>().unwrap());
+    ad.capabilities.insert(Capability::Relay);
+    cbor_roundtrip(ad)
+}
+
+#[test]
+fn skips_unknown_capabilities() {
+    let addr = "127.0.0.1:12345".parse().unwrap();
+    let future = FutureAdvertisement {
+        listen_addrs: vec![addr],
+        capabilities: vec![FutureCapability::Relay, FutureCapability::Teleport]
+            .into_iter()
+            .collect(),
+    };
+    let ad: PeerAdvertisement<SocketAddr> =
+        minicbor::decode(&minicbor::to_vec(&future).unwrap()).unwrap();
+
+    assert_eq!(ad.listen_addrs.into_inner(), vec![addr]);
+    assert_eq!(
+        ad.capabilities,
+        vec![Capability::Relay].into_iter().collect::<BTreeSet<_>>()
+    )
+}
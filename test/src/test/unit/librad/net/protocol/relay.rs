@@ -0,0 +1,100 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use assert_matches::assert_matches;
+use futures::{executor::block_on, io::AsyncWriteExt as _, try_join};
+
+use librad::{
+    keys::SecretKey,
+    net::{
+        connection::RemotePeer as _,
+        protocol::relay::{self, Header, Relayed},
+        upgrade::{with_upgraded, SomeUpgraded, UpgradeRequest},
+    },
+    peer::PeerId,
+};
+
+use crate::{librad::net::connection::MockStream, roundtrip::*};
+
+fn header(upgrade: UpgradeRequest) -> Header {
+    Header {
+        from: PeerId::from(SecretKey::new()),
+        to: PeerId::from(SecretKey::new()),
+        upgrade,
+    }
+}
+
+#[test]
+fn roundtrip_header() {
+    cbor_roundtrip(header(UpgradeRequest::Git))
+}
+
+#[test]
+fn relayable() {
+    assert!(header(UpgradeRequest::Git).is_relayable());
+    assert!(header(UpgradeRequest::Gossip).is_relayable());
+    assert!(!header(UpgradeRequest::Membership).is_relayable());
+    assert!(!header(UpgradeRequest::Interrogation).is_relayable());
+    assert!(!header(UpgradeRequest::Relay).is_relayable());
+    assert!(!header(UpgradeRequest::SyncRefs).is_relayable());
+}
+
+#[test]
+fn request() {
+    let hdr = header(UpgradeRequest::Gossip);
+    let (initiator, receiver) = MockStream::pair(hdr.from, hdr.to, 512);
+
+    let received = block_on(async {
+        try_join!(
+            async {
+                relay::request(initiator, &hdr)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            async {
+                match with_upgraded(receiver).await {
+                    Ok(SomeUpgraded::Relay(up)) => {
+                        let mut stream = up.into_stream();
+                        relay::read_header(&mut stream)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    _ => Err("expected relay upgrade".to_owned()),
+                }
+            }
+        )
+    })
+    .unwrap()
+    .1;
+
+    assert_eq!(received, hdr)
+}
+
+#[test]
+fn header_too_large() {
+    let (mut a, mut b) = MockStream::pair(
+        PeerId::from(SecretKey::new()),
+        PeerId::from(SecretKey::new()),
+        512,
+    );
+    block_on(a.write_all(&u16::MAX.to_be_bytes())).unwrap();
+
+    assert_matches!(
+        block_on(relay::read_header(&mut b)),
+        Err(relay::Error::TooLarge)
+    )
+}
+
+#[test]
+fn relayed_remote_peer() {
+    let origin = PeerId::from(SecretKey::new());
+    let (stream, _) = MockStream::pair(
+        PeerId::from(SecretKey::new()),
+        PeerId::from(SecretKey::new()),
+        512,
+    );
+
+    assert_eq!(Relayed::new(stream, origin).remote_peer_id(), origin)
+}
@@ -0,0 +1,80 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use librad::{
+    git::Urn,
+    git_ext,
+    keys::SecretKey,
+    net::protocol::sync::{Digest, Request},
+    peer::PeerId,
+};
+
+use crate::roundtrip::*;
+
+lazy_static! {
+    static ref OID: git_ext::Oid =
+        git_ext::Oid::from(git2::Oid::hash_object(git2::ObjectType::Commit, b"chrzbrr").unwrap());
+    static ref OTHER_OID: git_ext::Oid =
+        git_ext::Oid::from(git2::Oid::hash_object(git2::ObjectType::Commit, b"brrzchr").unwrap());
+}
+
+fn digest<I>(tips: I) -> Digest
+where
+    I: IntoIterator<Item = (PeerId, Option<git_ext::Oid>)>,
+{
+    Digest {
+        urn: Urn::new(git_ext::Oid::from(git2::Oid::zero())),
+        tips: tips.into_iter().collect(),
+    }
+}
+
+#[test]
+fn roundtrip_request() {
+    let peer = PeerId::from(SecretKey::new());
+    let absent = PeerId::from(SecretKey::new());
+    cbor_roundtrip(Request::Digests(vec![digest(vec![
+        (peer, Some(*OID)),
+        (absent, None),
+    ])]))
+}
+
+#[test]
+fn same_tips_do_not_diverge() {
+    let peer = PeerId::from(SecretKey::new());
+    let ours = digest(vec![(peer, Some(*OID))]);
+    let theirs = ours.clone();
+
+    assert!(!ours.diverges(&theirs))
+}
+
+#[test]
+fn different_tips_diverge() {
+    let peer = PeerId::from(SecretKey::new());
+    let ours = digest(vec![(peer, Some(*OID))]);
+    let theirs = digest(vec![(peer, Some(*OTHER_OID))]);
+
+    assert!(ours.diverges(&theirs));
+    assert!(theirs.diverges(&ours))
+}
+
+#[test]
+fn missing_tip_diverges_one_way() {
+    let peer = PeerId::from(SecretKey::new());
+    let ours = digest(vec![(peer, Some(*OID))]);
+    let theirs = digest(vec![(peer, None)]);
+
+    assert!(ours.diverges(&theirs));
+    assert!(!theirs.diverges(&ours))
+}
+
+#[test]
+fn unknown_peer_diverges_one_way() {
+    let peer = PeerId::from(SecretKey::new());
+    let ours = digest(vec![(peer, Some(*OID))]);
+    let theirs = digest(vec![]);
+
+    assert!(ours.diverges(&theirs));
+    assert!(!theirs.diverges(&ours))
+}
@@ -15,7 +15,9 @@ use librad::{
         Gossip,
         Interrogation,
         Membership,
+        Relay,
         SomeUpgraded,
+        SyncRefs,
         UpgradeRequest,
     },
     peer::PeerId,
@@ -75,10 +77,22 @@ async fn upgrade_interrogation() {
     )
 }
 
+#[async_test]
+async fn upgrade_relay() {
+    assert_matches!(test_upgrade(Relay).await, Ok(SomeUpgraded::Relay(_)))
+}
+
+#[async_test]
+async fn upgrade_sync_refs() {
+    assert_matches!(test_upgrade(SyncRefs).await, Ok(SomeUpgraded::SyncRefs(_)))
+}
+
 #[test]
 fn roundtrip_upgrade_request() {
     cbor_roundtrip(UpgradeRequest::Gossip);
     cbor_roundtrip(UpgradeRequest::Git);
     cbor_roundtrip(UpgradeRequest::Membership);
     cbor_roundtrip(UpgradeRequest::Interrogation);
+    cbor_roundtrip(UpgradeRequest::Relay);
+    cbor_roundtrip(UpgradeRequest::SyncRefs);
 }
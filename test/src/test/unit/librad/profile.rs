@@ -6,7 +6,12 @@
 use std::{collections::BTreeSet, fs};
 use tempfile::TempDir;
 
-use librad::profile::{id, Error, Profile, ProfileId, RadHome};
+use librad::{
+    git::storage::{ReadOnly, Storage},
+    keys::SecretKey,
+    peer::PeerId,
+    profile::{id, Error, Profile, ProfileId, RadHome},
+};
 
 pub struct TempHome {
     tmp: TempDir,
@@ -154,3 +159,85 @@ fn set_profile() {
     let err = Profile::set(&tmp_home.home, "i-dont-exist".parse().unwrap()).unwrap_err();
     assert!(matches!(err, Error::DoesNotExist { .. }));
 }
+
+#[test]
+fn delete_profile() {
+    let tmp_home = temp();
+
+    let active = Profile::new(&tmp_home.home).unwrap();
+    Profile::set(&tmp_home.home, active.id().clone()).unwrap();
+    let other = Profile::new(&tmp_home.home).unwrap();
+
+    let err = Profile::delete(&tmp_home.home, active.id().clone()).unwrap_err();
+    assert!(matches!(err, Error::Active(id) if &id == active.id()));
+
+    Profile::delete(&tmp_home.home, other.id().clone()).unwrap();
+    assert!(Profile::get(&tmp_home.home, other.id().clone())
+        .unwrap()
+        .is_none());
+    assert!(!other.paths().git_dir().exists());
+
+    let err = Profile::delete(&tmp_home.home, other.id().clone()).unwrap_err();
+    assert!(matches!(err, Error::DoesNotExist { .. }));
+}
+
+#[test]
+fn rename_profile() {
+    let tmp_home = temp();
+
+    let p = Profile::new(&tmp_home.home).unwrap();
+    Profile::set(&tmp_home.home, p.id().clone()).unwrap();
+    let key = SecretKey::new();
+    Storage::open(p.paths(), key.clone()).unwrap();
+
+    let to: ProfileId = "renamed".parse().unwrap();
+    let renamed = Profile::rename(&tmp_home.home, p.id().clone(), to.clone()).unwrap();
+    assert_eq!(renamed.id(), &to);
+    assert_eq!(ProfileId::active(&tmp_home.home).unwrap(), Some(to.clone()));
+    assert!(Profile::get(&tmp_home.home, p.id().clone())
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        ReadOnly::open(renamed.paths()).unwrap().peer_id(),
+        &PeerId::from(key)
+    );
+
+    let other = Profile::new(&tmp_home.home).unwrap();
+    let err = Profile::rename(&tmp_home.home, other.id().clone(), to).unwrap_err();
+    assert!(matches!(err, Error::AlreadyExists { .. }));
+}
+
+#[test]
+fn migrate_profile() {
+    let from = temp();
+    let to = temp();
+
+    let p = Profile::new(&from.home).unwrap();
+    Profile::set(&from.home, p.id().clone()).unwrap();
+    let key = SecretKey::new();
+    Storage::open(p.paths(), key.clone()).unwrap();
+    let blob = {
+        let repo = git2::Repository::open_bare(p.paths().git_dir()).unwrap();
+        let blob = repo.blob(b"migrate me").unwrap();
+        repo.reference("refs/heads/migrated", blob, false, "test")
+            .unwrap();
+        blob
+    };
+
+    let migrated = Profile::migrate(&from.home, &to.home, p.id().clone()).unwrap();
+    assert_eq!(migrated.id(), p.id());
+    assert!(!p.paths().git_dir().exists());
+    assert_eq!(ProfileId::active(&from.home).unwrap(), None);
+    assert_eq!(ProfileId::active(&to.home).unwrap().as_ref(), Some(p.id()));
+
+    let storage = ReadOnly::open(migrated.paths()).unwrap();
+    assert_eq!(storage.peer_id(), &PeerId::from(key));
+    let migrated_ref = git2::Repository::open_bare(migrated.paths().git_dir())
+        .unwrap()
+        .refname_to_id("refs/heads/migrated")
+        .unwrap();
+    assert_eq!(migrated_ref, blob);
+
+    let again = Profile::migrate(&from.home, &to.home, p.id().clone()).unwrap_err();
+    assert!(matches!(again, Error::DoesNotExist { .. }));
+}
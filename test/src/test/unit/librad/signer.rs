@@ -0,0 +1,75 @@
+// Copyright © 2021 The Radicle Link Contributors
+//
+// This file is part of radicle-link, distributed under the GPLv3 with Radicle
+// Linking Exception. For full terms see the included LICENSE file.
+
+use librad::{
+    keys::SecretKey,
+    peer::PeerId,
+    signer::{
+        agent::{Error, SshAgent},
+        BoxedSigner,
+        Signer as _,
+    },
+};
+
+use crate::librad::signer::MockAgent;
+
+const DATA_TO_SIGN: &[u8] = b"alors monsieur";
+
+#[test]
+fn sign_via_agent() {
+    let key = SecretKey::new();
+    let public = key.public();
+    let mock = MockAgent::spawn().unwrap().with_key(key);
+
+    let agent = SshAgent::new(mock.path(), public).check().unwrap();
+    let sig = agent.sign_with_agent(DATA_TO_SIGN).unwrap();
+    assert!(sig.verify(DATA_TO_SIGN, &public));
+
+    let signer = BoxedSigner::from(agent);
+    assert_eq!(signer.peer_id(), PeerId::from(public));
+    assert!(signer.sign_blocking(DATA_TO_SIGN).is_ok());
+}
+
+#[test]
+fn key_not_found() {
+    let mock = MockAgent::spawn().unwrap().with_key(SecretKey::new());
+    let public = SecretKey::new().public();
+
+    let agent = SshAgent::new(mock.path(), public);
+    assert_matches!(agent.clone().check(), Err(Error::KeyNotFound(key)) if key == public);
+    assert_matches!(
+        agent.sign_with_agent(DATA_TO_SIGN),
+        Err(Error::KeyNotFound(key)) if key == public
+    );
+}
+
+#[test]
+fn add_key() {
+    let key = SecretKey::new();
+    let public = key.public();
+    let mock = MockAgent::spawn().unwrap();
+
+    let agent = SshAgent::new(mock.path(), public);
+    assert!(agent.identities().unwrap().is_empty());
+    agent.add(&key, "test").unwrap();
+    assert_eq!(agent.identities().unwrap(), vec![public]);
+
+    let sig = agent
+        .check()
+        .unwrap()
+        .sign_with_agent(DATA_TO_SIGN)
+        .unwrap();
+    assert!(sig.verify(DATA_TO_SIGN, &public));
+}
+
+#[test]
+fn no_agent() {
+    let mock = MockAgent::spawn().unwrap();
+    let agent = SshAgent::new(
+        mock.path().with_extension("gone"),
+        SecretKey::new().public(),
+    );
+    assert_matches!(agent.identities(), Err(Error::Io(_)))
+}